@@ -0,0 +1,22 @@
+// the setup the benches that run monkey code share
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use monkey::evaluator::eval;
+use monkey::gc;
+use monkey::lexer::Lexer;
+use monkey::module::{self, ModuleLoader};
+use monkey::object::Environment;
+use monkey::parser::Parser;
+
+// evaluates the source like a file without a path, with the builtins and the prelude, and returns the last result.
+// the environment is tracked, so the collector frees it even if a function stored in it closes over it, like fib does
+pub fn evaluate(source: &str) -> String {
+    let mut lexer = Lexer::new(String::from(source));
+    let program = Parser::new(&mut lexer).parse_programm().unwrap();
+    let loader = Rc::new(RefCell::new(ModuleLoader::new(Vec::new())));
+    let prelude = module::prelude_env(&loader).unwrap();
+    let env = gc::track(Environment::new_module(None, loader, Some(prelude)));
+    let result = eval(&program.statements, env).unwrap();
+    result.last().unwrap().inspect()
+}
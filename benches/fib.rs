@@ -0,0 +1,28 @@
+// runs a recursive fib of growing n and prints the time per call of fib.
+// almost all of the time goes into calls and variable lookups, so this shows how fast those are.
+// run with: cargo bench --bench fib
+use std::time::Instant;
+
+mod common;
+use common::evaluate;
+
+const SCRIPT: &str = "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(N);";
+
+// the number of calls fib(n) makes
+fn calls(n: u64) -> u64 {
+    if n < 2 { 1 } else { calls(n - 1) + calls(n - 2) + 1 }
+}
+
+fn run(n: u64) -> String {
+    evaluate(&SCRIPT.replace("N", &n.to_string()))
+}
+
+fn main() {
+    for n in [15, 20, 25] {
+        let start = Instant::now();
+        let result = run(n);
+        let elapsed = start.elapsed();
+        let ns_per_call = elapsed.as_nanos() as f64 / calls(n) as f64;
+        println!("fib({}) = {:>6} in {:>10.2?} ({:.0} ns/call)", n, result, elapsed, ns_per_call);
+    }
+}
@@ -0,0 +1,46 @@
+// lexes generated scripts of growing size and prints the time per byte.
+// the lexer is linear if the time per byte stays roughly the same for every size.
+// run with: cargo bench --bench lexer
+use std::time::Instant;
+
+use monkey::lexer::Lexer;
+use monkey::token::TokenType;
+
+const SNIPPET: &str = "let größe_1 = fn(a, b) { return a * 0x_FF + b; };\n\
+    let grüße = \"héllo wörld ✓ \\\"quoted\\\"\";\n\
+    if (größe_1(3, 4) >= 1_000) { grüße } else { !true };\n";
+
+fn generate_script(size: usize) -> String {
+    let mut script = String::with_capacity(size + SNIPPET.len());
+    while script.len() < size {
+        script.push_str(SNIPPET);
+    }
+    script
+}
+
+fn lex(input: String) -> usize {
+    let mut lexer = Lexer::new(input);
+    let mut count = 0;
+    while lexer.next_token().tokentype != TokenType::EOF {
+        count += 1;
+    }
+    count
+}
+
+fn main() {
+    let sizes = [1_250_000, 2_500_000, 5_000_000, 10_000_000];
+    let mut first_ns_per_byte: Option<f64> = None;
+    for size in sizes {
+        let script = generate_script(size);
+        let bytes = script.len();
+        let start = Instant::now();
+        let tokens = lex(script);
+        let elapsed = start.elapsed();
+        let ns_per_byte = elapsed.as_nanos() as f64 / bytes as f64;
+        let ratio = ns_per_byte / *first_ns_per_byte.get_or_insert(ns_per_byte);
+        println!(
+            "lexed {:>9} bytes into {:>8} tokens in {:>10.2?} ({:.2} ns/byte, {:.2}x the smallest input)",
+            bytes, tokens, elapsed, ns_per_byte, ratio
+        );
+    }
+}
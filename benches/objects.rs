@@ -0,0 +1,31 @@
+// runs a recursive fib that reads a captured array and string on every call and prints the time per run.
+// reading a variable clones its value, so the time should stay the same no matter how large the captured values are.
+// run with: cargo bench --bench objects
+use std::time::Instant;
+
+mod common;
+use common::evaluate;
+
+const SCRIPT: &str = "let data = range(SIZE);\n\
+    let double = fn(s, times) { if (times == 0) { s } else { double(s + s, times - 1) } };\n\
+    let text = double(\"x\", TIMES);\n\
+    let fib = fn(n) { if (n < 2) { (len(data) + len(text)) * 0 + n } else { fib(n - 1) + fib(n - 2) } };\n\
+    fib(18);\n";
+
+fn run(size: usize) -> String {
+    // the string has about as many characters as the array has elements
+    let times = (size as f64).log2().ceil() as usize;
+    evaluate(&SCRIPT.replace("SIZE", &size.to_string()).replace("TIMES", &times.to_string()))
+}
+
+fn main() {
+    let sizes = [1, 1_000, 10_000, 100_000];
+    let mut first: Option<f64> = None;
+    for size in sizes {
+        let start = Instant::now();
+        let result = run(size);
+        let elapsed = start.elapsed();
+        let ratio = elapsed.as_secs_f64() / *first.get_or_insert(elapsed.as_secs_f64());
+        println!("fib(18) = {} capturing {:>6} elements in {:>10.2?} ({:.2}x the smallest input)", result, size, elapsed, ratio);
+    }
+}
@@ -0,0 +1,74 @@
+// parses generated scripts of growing size into the boxed ast and into the arena and prints the time per byte
+// and the number of allocations of both. the lexer runs in both, so the difference is the cost of building the nodes.
+// run with: cargo bench --bench parser
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use monkey::arena::Ast;
+use monkey::lexer::Lexer;
+use monkey::parser::Parser;
+
+// counts the calls to alloc
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const SNIPPET: &str = "let add = fn(a, b) { a + b * (a - b) / 2 };\n\
+    let point = {\"x\": add(1, 2), \"y\": [1, 2, 3, 4][2], \"z\": !true};\n\
+    let total = if (point[\"x\"] >= 10 | 1 << 2) { -point[\"x\"] } else { add(point[\"x\"], ((1 + 2) * (3 + 4)) ^ 2) };\n";
+
+fn generate_script(size: usize) -> String {
+    let mut script = String::with_capacity(size + SNIPPET.len());
+    while script.len() < size {
+        script.push_str(SNIPPET);
+    }
+    script
+}
+
+// the time and the number of allocations of parse
+fn measure<T>(parse: impl FnOnce() -> T) -> (Duration, usize) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let result = parse();
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    drop(result);
+    (elapsed, allocations)
+}
+
+fn main() {
+    let sizes = [1_000_000, 2_000_000, 4_000_000];
+    for size in sizes {
+        let script = generate_script(size);
+        let bytes = script.len() as f64;
+        let (tree, tree_allocations) = measure(|| {
+            let mut lexer = Lexer::new(script.clone());
+            Parser::new(&mut lexer).parse_programm().unwrap()
+        });
+        let (arena, arena_allocations) = measure(|| {
+            let mut lexer = Lexer::new(script.clone());
+            Ast::parse(&mut lexer).unwrap()
+        });
+        println!(
+            "parsed {:>8} bytes: tree {:>10.2?} ({:.1} ns/byte, {:>8} allocations), arena {:>10.2?} ({:.1} ns/byte, {:>8} allocations), {:.2}x faster",
+            script.len(),
+            tree, tree.as_nanos() as f64 / bytes, tree_allocations,
+            arena, arena.as_nanos() as f64 / bytes, arena_allocations,
+            tree.as_secs_f64() / arena.as_secs_f64(),
+        );
+    }
+}
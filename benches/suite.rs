@@ -0,0 +1,235 @@
+// runs every case a few times, prints a table and writes the results as json, so two runs can be compared
+// after a change to the lexer, the parser or the evaluator. the file is target/bench/results.json,
+// MONKEY_BENCH_OUTPUT sets another path. the median of every case is compared to the one in the file
+// from the run before, so running the suite before and after a change shows what the change did. arguments that do not start with "-" only run the cases containing them.
+// run with: cargo bench --bench suite
+// or:       cargo bench --bench suite -- fib closures
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use monkey::arena::Ast;
+use monkey::ast::Programm;
+use monkey::json::{self, JsonValue};
+use monkey::lexer::Lexer;
+use monkey::parser::Parser;
+use monkey::token::TokenType;
+
+mod common;
+use common::evaluate;
+
+// a case is run until it took this long in total, but at least MIN_RUNS and at most MAX_RUNS times
+const TARGET_TIME: Duration = Duration::from_secs(2);
+const MIN_RUNS: usize = 3;
+const MAX_RUNS: usize = 50;
+
+const LEX_SNIPPET: &str = "let größe_1 = fn(a, b) { return a * 0x_FF + b; };\n\
+    let grüße = \"héllo wörld ✓ \\\"quoted\\\"\";\n\
+    if (größe_1(3, 4) >= 1_000) { grüße } else { !true };\n";
+
+const FIB: &str = "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } };\n\
+    fib(25);";
+
+// creates a closure for every number and calls it right away
+const CLOSURES: &str = "let make_adder = fn(x) { fn(y) { x + y } };\n\
+    let compose = fn(f, g) { fn(x) { g(f(x)) } };\n\
+    reduce(range(20000), 0, fn(acc, i) { compose(make_adder(i), make_adder(1))(acc) });";
+
+const COLLECTIONS: &str = "let xs = map(range(3000), fn(x) { x * 7919 - (x * 7919 / 3001) * 3001 });\n\
+    let evens = filter(xs, fn(x) { x / 2 * 2 == x });\n\
+    let sorted = sort(evens);\n\
+    let table = {\"xs\": xs, \"sorted\": sorted, \"pairs\": zip(xs, sorted)};\n\
+    sum(table[\"sorted\"]) + len(table[\"pairs\"]) + len(filter(keys(table), fn(k) { contains(k, \"s\") }));";
+
+// builds an array with push in a reduce, every push should add to the accumulator without copying it
+const PUSH: &str = "let xs = reduce(range(20000), [], fn(acc, x) { push(acc, x * 2) });\n\
+    len(xs);";
+
+// the prelude and builtins on large arrays, none of them should take longer than linear time
+const LARGE_COLLECTIONS: &str = "let xs = map(range(100000), fn(x) { x * 3 });\n\
+    let odd = filter(xs, fn(x) { x / 2 * 2 != x });\n\
+    len(join(reverse(odd), \",\")) + len(reverse(join(xs, \"\")));";
+
+struct Case {
+    name: &'static str,
+    // the size of the input, bytes for the lexer and the parser
+    bytes: Option<usize>,
+    run: Box<dyn Fn() -> String>,
+}
+
+fn generate_script(snippet: &str, size: usize) -> String {
+    let mut script = String::with_capacity(size + snippet.len());
+    while script.len() < size {
+        script.push_str(snippet);
+    }
+    script
+}
+
+// a chain of operators of every precedence and expressions nested deeply in parentheses, arrays and calls
+fn deep_expressions() -> String {
+    let operators = ["+", "-", "*", "/", "^", "|", "&", "<<", ">>", "==", "<", ">="];
+    let mut script = String::new();
+    for line in 0..100 {
+        script.push_str("let chain = 1");
+        for i in 0..500 {
+            script.push_str(&format!(" {} {}", operators[(line + i) % operators.len()], i));
+        }
+        script.push_str(";\n");
+        let depth = 200;
+        script.push_str("let nested = ");
+        for i in 0..depth {
+            script.push_str(["(", "[", "f("][i % 3]);
+        }
+        script.push_str("-x");
+        for i in (0..depth).rev() {
+            script.push_str([")", "]", ")"][i % 3]);
+        }
+        script.push_str(";\n");
+    }
+    script
+}
+
+fn parse(source: &str) -> Programm {
+    let mut lexer = Lexer::new(String::from(source));
+    Parser::new(&mut lexer).parse_programm().unwrap()
+}
+
+fn cases() -> Vec<Case> {
+    let large = Rc::new(generate_script(LEX_SNIPPET, 4_000_000));
+    let deep = Rc::new(deep_expressions());
+    let (lex_input, tree_input, arena_input) = (large.clone(), deep.clone(), deep.clone());
+    vec![
+        Case {
+            name: "lex_large",
+            bytes: Some(large.len()),
+            run: Box::new(move || {
+                let mut lexer = Lexer::new(String::from(lex_input.as_str()));
+                let mut count = 0;
+                while lexer.next_token().tokentype != TokenType::EOF {
+                    count += 1;
+                }
+                format!("{} tokens", count)
+            }),
+        },
+        Case {
+            name: "parse_deep_tree",
+            bytes: Some(deep.len()),
+            run: Box::new(move || format!("{} statements", parse(&tree_input).statements.len())),
+        },
+        Case {
+            name: "parse_deep_arena",
+            bytes: Some(deep.len()),
+            run: Box::new(move || {
+                let mut lexer = Lexer::new(String::from(arena_input.as_str()));
+                format!("{} nodes", Ast::parse(&mut lexer).unwrap().len())
+            }),
+        },
+        Case { name: "fib_25", bytes: None, run: Box::new(|| evaluate(FIB)) },
+        Case { name: "closures", bytes: None, run: Box::new(|| evaluate(CLOSURES)) },
+        Case { name: "collections", bytes: None, run: Box::new(|| evaluate(COLLECTIONS)) },
+        Case { name: "push", bytes: None, run: Box::new(|| evaluate(PUSH)) },
+        Case { name: "large_collections", bytes: None, run: Box::new(|| evaluate(LARGE_COLLECTIONS)) },
+    ]
+}
+
+struct Measurement {
+    runs: Vec<Duration>,
+    result: String,
+}
+impl Measurement {
+    fn min(&self) -> Duration {
+        *self.runs.iter().min().unwrap()
+    }
+    fn median(&self) -> Duration {
+        let mut runs = self.runs.clone();
+        runs.sort();
+        runs[runs.len() / 2]
+    }
+    fn mean(&self) -> Duration {
+        self.runs.iter().sum::<Duration>() / self.runs.len() as u32
+    }
+}
+
+fn measure(case: &Case) -> Measurement {
+    // the first run warms up the caches and is not counted
+    let result = (case.run)();
+    let mut runs = Vec::new();
+    let start = Instant::now();
+    while runs.len() < MIN_RUNS || (runs.len() < MAX_RUNS && start.elapsed() < TARGET_TIME) {
+        let run = Instant::now();
+        let output = (case.run)();
+        runs.push(run.elapsed());
+        assert_eq!(output, result, "{} gave a different result on another run", case.name);
+    }
+    Measurement { runs, result }
+}
+
+fn output_path() -> PathBuf {
+    match env::var_os("MONKEY_BENCH_OUTPUT") {
+        Some(x) => PathBuf::from(x),
+        None => PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target").join("bench").join("results.json"),
+    }
+}
+
+// the median of every case in the results of the run before, empty if there are none
+fn previous_medians(path: &PathBuf) -> HashMap<String, i64> {
+    let mut medians = HashMap::new();
+    let report = match fs::read_to_string(path).ok().and_then(|x| json::parse(&x).ok()) {
+        Some(x) => x,
+        None => return medians,
+    };
+    for case in report.get("cases").and_then(|x| x.as_array()).map(|x| x.as_slice()).unwrap_or_default() {
+        if let (Some(name), Some(median)) = (case.get("name").and_then(|x| x.as_str()), case.get("median_ns").and_then(|x| x.as_i64())) {
+            medians.insert(String::from(name), median);
+        }
+    }
+    medians
+}
+
+fn main() {
+    // cargo passes --bench to every bench binary
+    let filters: Vec<String> = env::args().skip(1).filter(|x| !x.starts_with('-')).collect();
+    let path = output_path();
+    let previous = previous_medians(&path);
+    let mut results: Vec<JsonValue> = Vec::new();
+    println!("{:<18} {:>6} {:>12} {:>12} {:>12} {:>10} {:>9}  result", "case", "runs", "min", "median", "mean", "ns/byte", "change");
+    for case in cases() {
+        if !filters.is_empty() && !filters.iter().any(|x| case.name.contains(x.as_str())) {
+            continue
+        }
+        let measurement = measure(&case);
+        let ns_per_byte = case.bytes.map(|x| measurement.median().as_nanos() as f64 / x as f64);
+        // how much faster or slower the median is than in the run before
+        let change = previous.get(case.name).map(|x| (measurement.median().as_nanos() as f64 / *x as f64 - 1.0) * 100.0);
+        println!(
+            "{:<18} {:>6} {:>12.2?} {:>12.2?} {:>12.2?} {:>10} {:>9}  {}",
+            case.name, measurement.runs.len(), measurement.min(), measurement.median(), measurement.mean(),
+            ns_per_byte.map(|x| format!("{:.2}", x)).unwrap_or_default(), change.map(|x| format!("{:+.1}%", x)).unwrap_or_default(),
+            measurement.result,
+        );
+        results.push(JsonValue::object(vec![
+            ("name", JsonValue::string(case.name)),
+            ("runs", JsonValue::NUMBER(measurement.runs.len() as i64)),
+            ("min_ns", JsonValue::NUMBER(measurement.min().as_nanos() as i64)),
+            ("median_ns", JsonValue::NUMBER(measurement.median().as_nanos() as i64)),
+            ("mean_ns", JsonValue::NUMBER(measurement.mean().as_nanos() as i64)),
+            ("bytes", case.bytes.map(|x| JsonValue::NUMBER(x as i64)).unwrap_or(JsonValue::NULL)),
+            ("result", JsonValue::string(&measurement.result)),
+        ]));
+    }
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_secs()).unwrap_or(0);
+    let report = JsonValue::object(vec![
+        ("version", JsonValue::string(env!("CARGO_PKG_VERSION"))),
+        ("timestamp", JsonValue::NUMBER(timestamp as i64)),
+        ("cases", JsonValue::ARRAY(results)),
+    ]);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).unwrap();
+    }
+    fs::write(&path, format!("{}\n", report)).unwrap();
+    println!("results written to {}", path.display());
+}
@@ -0,0 +1,657 @@
+use std::ops::Index;
+use std::rc::Rc;
+
+use crate::ast::{self, Programm, TypeAnnotation};
+use crate::lexer::TokenSource;
+use crate::parser::{AstBuilder, ParseError, Parser};
+use crate::resolver;
+use crate::symbol::Symbol;
+use crate::token::{self, Boolean, Identifier, ImportStatement, IntegerLiteral, StringLiteral, Token};
+
+// the ast of ast.rs gives every child expression its own box and every list its own vec, so parsing a large
+// script spends most of its time allocating. here every kind of node is stored in a vector of its own:
+// expressions, statements, blocks, functions and type annotations. a node refers to its children by their index
+// in those vectors. the children of a list node (arguments, elements, the statements of a block) are stored one
+// after the other in a vector of ids as well. blocks, functions and types are large and rare, keeping them out
+// of the expressions keeps every expression small.
+//
+// the nodes have the names and fields of the ones in ast.rs and token.rs, only the children are ids.
+// Visitor walks them the same way ast::Visitor walks the tree. the evaluator and the other passes still work on
+// the tree, to_program builds it from the arena
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct ExprId(u32);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct StmtId(u32);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct BlockId(u32);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct FunctionId(u32);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct TypeId(u32);
+
+// a run of ids in Ast::expression_lists
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct ExprList {
+    start: u32,
+    len: u32,
+}
+
+// a run of ids in Ast::statement_lists
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct StmtList {
+    start: u32,
+    len: u32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum MonkeyExpression {
+    IDENT(Identifier),
+    INTEGERLITERAL(IntegerLiteral),
+    STRINGLITERAL(StringLiteral),
+    BOOLEAN(Boolean),
+    PREFIX(PrefixExpression),
+    INFIX(InfixExpression),
+    IF(IfExpression),
+    FUNCTIONLITERAL(FunctionId),
+    CALL(CallExpression),
+    MEMBER(MemberExpression),
+    ARRAY(ArrayLiteral),
+    HASH(HashLiteral),
+    INDEX(IndexExpression),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Statement {
+    LET(LetStatement),
+    RETURN(ReturnStatement),
+    EXPRESSION(ExpressionStatement),
+    IMPORT(ImportStatement),
+    EXPORT(ExportStatement),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LetStatement {
+    pub token: Token,
+    pub name: Identifier,
+    pub annotation: Option<TypeId>,
+    pub value: ExprId,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReturnStatement {
+    pub token: Token,
+    pub return_value: ExprId,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExpressionStatement {
+    pub token: Token,
+    pub expression: ExprId,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExportStatement {
+    pub token: Token,
+    pub statement: LetStatement,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BlockStatement {
+    pub token: Token,
+    pub statements: StmtList,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PrefixExpression {
+    pub token: Token,
+    pub operator: Symbol,
+    pub right: ExprId,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct InfixExpression {
+    pub token: Token,
+    pub operator: Symbol,
+    pub left: ExprId,
+    pub right: ExprId,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IfExpression {
+    pub tok: Token,
+    pub condition: ExprId,
+    pub consequence: BlockId,
+    pub alternative: Option<BlockId>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FunctionLiteral {
+    pub token: Token,
+    pub parameters: Vec<Identifier>,
+    pub parameter_types: Vec<Option<TypeAnnotation>>,
+    pub return_type: Option<TypeAnnotation>,
+    pub blockstatment: BlockId,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CallExpression {
+    pub token: Token,
+    pub function: ExprId,
+    pub arguments: Option<ExprList>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MemberExpression {
+    pub token: Token,
+    pub object: ExprId,
+    pub property: Identifier,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ArrayLiteral {
+    pub token: Token,
+    pub elements: ExprList,
+}
+
+// keys and values take turns in the list, see Ast::pairs
+#[derive(Debug, PartialEq, Clone)]
+pub struct HashLiteral {
+    pub token: Token,
+    pub pairs: ExprList,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IndexExpression {
+    pub token: Token,
+    pub left: ExprId,
+    pub index: ExprId,
+}
+
+// a whole program, the parser fills it through AstBuilder
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Ast {
+    expressions: Vec<MonkeyExpression>,
+    statements: Vec<Statement>,
+    blocks: Vec<BlockStatement>,
+    functions: Vec<FunctionLiteral>,
+    types: Vec<TypeAnnotation>,
+    expression_lists: Vec<ExprId>,
+    statement_lists: Vec<StmtId>,
+    program: StmtList,
+}
+
+impl Ast {
+    pub fn new() -> Ast {
+        Ast::default()
+    }
+
+    // parses everything the lexer hands out, with the same errors as Parser::parse_programm
+    pub fn parse(lexer: &mut dyn TokenSource) -> Result<Ast, ParseError> {
+        Parser::with_builder(lexer, Ast::new()).parse_programm()
+    }
+
+    // the top level statements
+    pub fn program(&self) -> &[StmtId] {
+        self.statement_list(self.program)
+    }
+
+    pub fn expression(&self, id: ExprId) -> &MonkeyExpression {
+        &self.expressions[id.0 as usize]
+    }
+
+    pub fn statement(&self, id: StmtId) -> &Statement {
+        &self.statements[id.0 as usize]
+    }
+
+    pub fn block(&self, id: BlockId) -> &BlockStatement {
+        &self.blocks[id.0 as usize]
+    }
+
+    pub fn function(&self, id: FunctionId) -> &FunctionLiteral {
+        &self.functions[id.0 as usize]
+    }
+
+    pub fn type_annotation(&self, id: TypeId) -> &TypeAnnotation {
+        &self.types[id.0 as usize]
+    }
+
+    pub fn expression_list(&self, list: ExprList) -> &[ExprId] {
+        &self.expression_lists[list.start as usize..(list.start + list.len) as usize]
+    }
+
+    pub fn statement_list(&self, list: StmtList) -> &[StmtId] {
+        &self.statement_lists[list.start as usize..(list.start + list.len) as usize]
+    }
+
+    pub fn pairs(&self, hash: &HashLiteral) -> impl Iterator<Item = (ExprId, ExprId)> + '_ {
+        self.expression_list(hash.pairs).chunks(2).map(|x| (x[0], x[1]))
+    }
+
+    // the number of nodes, type annotations included
+    pub fn len(&self) -> usize {
+        self.expressions.len() + self.statements.len() + self.blocks.len() + self.functions.len() + self.types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn push_expression(&mut self, expression: MonkeyExpression) -> ExprId {
+        self.expressions.push(expression);
+        ExprId(self.expressions.len() as u32 - 1)
+    }
+
+    fn push_statement(&mut self, statement: Statement) -> StmtId {
+        self.statements.push(statement);
+        StmtId(self.statements.len() as u32 - 1)
+    }
+
+    fn push_block(&mut self, block: BlockStatement) -> BlockId {
+        self.blocks.push(block);
+        BlockId(self.blocks.len() as u32 - 1)
+    }
+
+    fn push_function(&mut self, function: FunctionLiteral) -> FunctionId {
+        self.functions.push(function);
+        FunctionId(self.functions.len() as u32 - 1)
+    }
+
+    fn push_type(&mut self, annotation: TypeAnnotation) -> TypeId {
+        self.types.push(annotation);
+        TypeId(self.types.len() as u32 - 1)
+    }
+
+    // the lists of nested nodes are finished first, so the ids of every list end up next to each other
+    fn push_expression_list(&mut self, ids: impl IntoIterator<Item = ExprId>) -> ExprList {
+        let start = self.expression_lists.len();
+        self.expression_lists.extend(ids);
+        ExprList { start: start as u32, len: (self.expression_lists.len() - start) as u32 }
+    }
+
+    fn push_statement_list(&mut self, ids: Vec<StmtId>) -> StmtList {
+        let start = self.statement_lists.len();
+        self.statement_lists.extend(ids);
+        StmtList { start: start as u32, len: (self.statement_lists.len() - start) as u32 }
+    }
+
+    // the same program as the ast of ast.rs, with the variables resolved like after Parser::parse_programm
+    pub fn to_program(&self) -> Programm {
+        let statements = self.program().iter().map(|x| self.tree_statement(*x)).collect();
+        let mut programm = Programm { statements };
+        resolver::resolve(&mut programm);
+        programm
+    }
+
+    fn tree_statement(&self, id: StmtId) -> ast::Statement {
+        match self.statement(id) {
+            Statement::LET(x) => ast::Statement::LET(self.tree_let_statement(x)),
+            Statement::RETURN(x) => ast::Statement::RETURN(token::ReturnStatement::new(x.token.clone(), self.tree_expression(x.return_value))),
+            Statement::EXPRESSION(x) => ast::Statement::EXPRESSION(token::ExpressionStatement::new(x.token.clone(), self.tree_expression(x.expression))),
+            Statement::IMPORT(x) => ast::Statement::IMPORT(x.clone()),
+            Statement::EXPORT(x) => ast::Statement::EXPORT(token::ExportStatement::new(x.token.clone(), self.tree_let_statement(&x.statement))),
+        }
+    }
+
+    fn tree_let_statement(&self, statement: &LetStatement) -> token::LetStatement {
+        let mut tree = token::LetStatement::new(statement.token.clone(), statement.name.clone(), self.tree_expression(statement.value));
+        tree.annotation = statement.annotation.map(|x| self.types[x.0 as usize].clone());
+        tree
+    }
+
+    fn tree_block(&self, block: BlockId) -> token::BlockStatement {
+        let block = self.block(block);
+        let statements = self.statement_list(block.statements).iter().map(|x| self.tree_statement(*x)).collect();
+        token::BlockStatement::new(block.token.clone(), statements)
+    }
+
+    fn tree_expressions(&self, list: ExprList) -> Vec<ast::MonkeyExpression> {
+        self.expression_list(list).iter().map(|x| self.tree_expression(*x)).collect()
+    }
+
+    fn tree_expression(&self, id: ExprId) -> ast::MonkeyExpression {
+        match self.expression(id) {
+            MonkeyExpression::IDENT(x) => ast::MonkeyExpression::IDENT(x.clone()),
+            MonkeyExpression::INTEGERLITERAL(x) => ast::MonkeyExpression::INTEGERLITERAL(x.clone()),
+            MonkeyExpression::STRINGLITERAL(x) => ast::MonkeyExpression::STRINGLITERAL(x.clone()),
+            MonkeyExpression::BOOLEAN(x) => ast::MonkeyExpression::BOOLEAN(x.clone()),
+            MonkeyExpression::PREFIX(x) => ast::MonkeyExpression::PREFIX(
+                token::PrefixExpression::new(x.token.clone(), x.operator.to_string(), self.tree_expression(x.right))
+            ),
+            MonkeyExpression::INFIX(x) => ast::MonkeyExpression::INFIX(
+                token::InfixExpression::new(x.operator.to_string(), x.token.clone(), self.tree_expression(x.left), self.tree_expression(x.right))
+            ),
+            MonkeyExpression::IF(x) => ast::MonkeyExpression::IF(token::IfExpression::new(
+                x.tok.clone(),
+                Box::new(self.tree_expression(x.condition)),
+                self.tree_block(x.consequence),
+                x.alternative.map(|x| self.tree_block(x)),
+            )),
+            MonkeyExpression::FUNCTIONLITERAL(x) => {
+                let x = self.function(*x);
+                let mut function = token::FunctionLiteral::new(x.token.clone(), x.parameters.clone(), self.tree_block(x.blockstatment));
+                function.parameter_types = x.parameter_types.clone();
+                function.return_type = x.return_type.clone();
+                ast::MonkeyExpression::FUNCTIONLITERAL(Rc::new(function))
+            },
+            MonkeyExpression::CALL(x) => ast::MonkeyExpression::CALL(
+                token::CallExpression::new(x.token.clone(), self.tree_expression(x.function), x.arguments.map(|x| self.tree_expressions(x)))
+            ),
+            MonkeyExpression::MEMBER(x) => ast::MonkeyExpression::MEMBER(
+                token::MemberExpression::new(x.token.clone(), self.tree_expression(x.object), x.property.clone())
+            ),
+            MonkeyExpression::ARRAY(x) => ast::MonkeyExpression::ARRAY(token::ArrayLiteral::new(x.token.clone(), self.tree_expressions(x.elements))),
+            MonkeyExpression::HASH(x) => {
+                let pairs = self.pairs(x).map(|(key, value)| (self.tree_expression(key), self.tree_expression(value))).collect();
+                ast::MonkeyExpression::HASH(token::HashLiteral::new(x.token.clone(), pairs))
+            },
+            MonkeyExpression::INDEX(x) => ast::MonkeyExpression::INDEX(
+                token::IndexExpression::new(x.token.clone(), self.tree_expression(x.left), self.tree_expression(x.index))
+            ),
+        }
+    }
+}
+
+impl Index<ExprId> for Ast {
+    type Output = MonkeyExpression;
+    fn index(&self, id: ExprId) -> &MonkeyExpression {
+        self.expression(id)
+    }
+}
+
+impl Index<StmtId> for Ast {
+    type Output = Statement;
+    fn index(&self, id: StmtId) -> &Statement {
+        self.statement(id)
+    }
+}
+
+impl Index<BlockId> for Ast {
+    type Output = BlockStatement;
+    fn index(&self, id: BlockId) -> &BlockStatement {
+        self.block(id)
+    }
+}
+
+impl Index<FunctionId> for Ast {
+    type Output = FunctionLiteral;
+    fn index(&self, id: FunctionId) -> &FunctionLiteral {
+        self.function(id)
+    }
+}
+
+impl Index<TypeId> for Ast {
+    type Output = TypeAnnotation;
+    fn index(&self, id: TypeId) -> &TypeAnnotation {
+        self.type_annotation(id)
+    }
+}
+
+impl AstBuilder for Ast {
+    type Expression = ExprId;
+    type Statement = StmtId;
+    type Let = LetStatement;
+    type Block = BlockId;
+    type Program = Ast;
+
+    fn identifier(&mut self, identifier: Identifier) -> ExprId {
+        self.push_expression(MonkeyExpression::IDENT(identifier))
+    }
+    fn integer_literal(&mut self, literal: IntegerLiteral) -> ExprId {
+        self.push_expression(MonkeyExpression::INTEGERLITERAL(literal))
+    }
+    fn string_literal(&mut self, literal: StringLiteral) -> ExprId {
+        self.push_expression(MonkeyExpression::STRINGLITERAL(literal))
+    }
+    fn boolean(&mut self, boolean: Boolean) -> ExprId {
+        self.push_expression(MonkeyExpression::BOOLEAN(boolean))
+    }
+    fn prefix_expression(&mut self, tok: Token, right: ExprId) -> ExprId {
+        self.push_expression(MonkeyExpression::PREFIX(PrefixExpression { operator: tok.literal.symbol(), token: tok, right }))
+    }
+    fn infix_expression(&mut self, tok: Token, left: ExprId, right: ExprId) -> ExprId {
+        self.push_expression(MonkeyExpression::INFIX(InfixExpression { operator: tok.literal.symbol(), token: tok, left, right }))
+    }
+    fn if_expression(&mut self, tok: Token, condition: ExprId, consequence: BlockId, alternative: Option<BlockId>) -> ExprId {
+        self.push_expression(MonkeyExpression::IF(IfExpression { tok, condition, consequence, alternative }))
+    }
+    fn function_literal(&mut self, tok: Token, params: Vec<Identifier>, parameter_types: Vec<Option<TypeAnnotation>>, return_type: Option<TypeAnnotation>, body: BlockId) -> ExprId {
+        let function = self.push_function(FunctionLiteral { token: tok, parameters: params, parameter_types, return_type, blockstatment: body });
+        self.push_expression(MonkeyExpression::FUNCTIONLITERAL(function))
+    }
+    fn call_expression(&mut self, tok: Token, function: ExprId, arguments: Option<Vec<ExprId>>) -> ExprId {
+        let arguments = arguments.map(|x| self.push_expression_list(x));
+        self.push_expression(MonkeyExpression::CALL(CallExpression { token: tok, function, arguments }))
+    }
+    fn member_expression(&mut self, tok: Token, object: ExprId, property: Identifier) -> ExprId {
+        self.push_expression(MonkeyExpression::MEMBER(MemberExpression { token: tok, object, property }))
+    }
+    fn array_literal(&mut self, tok: Token, elements: Vec<ExprId>) -> ExprId {
+        let elements = self.push_expression_list(elements);
+        self.push_expression(MonkeyExpression::ARRAY(ArrayLiteral { token: tok, elements }))
+    }
+    fn hash_literal(&mut self, tok: Token, pairs: Vec<(ExprId, ExprId)>) -> ExprId {
+        let pairs = self.push_expression_list(pairs.into_iter().flat_map(|(key, value)| [key, value]));
+        self.push_expression(MonkeyExpression::HASH(HashLiteral { token: tok, pairs }))
+    }
+    fn index_expression(&mut self, tok: Token, left: ExprId, index: ExprId) -> ExprId {
+        self.push_expression(MonkeyExpression::INDEX(IndexExpression { token: tok, left, index }))
+    }
+    fn let_binding(&mut self, tok: Token, name: Identifier, annotation: Option<TypeAnnotation>, value: ExprId) -> LetStatement {
+        let annotation = annotation.map(|x| self.push_type(x));
+        LetStatement { token: tok, name, annotation, value }
+    }
+    fn let_statement(&mut self, statement: LetStatement) -> StmtId {
+        self.push_statement(Statement::LET(statement))
+    }
+    fn export_statement(&mut self, tok: Token, statement: LetStatement) -> StmtId {
+        self.push_statement(Statement::EXPORT(ExportStatement { token: tok, statement }))
+    }
+    fn return_statement(&mut self, tok: Token, value: ExprId) -> StmtId {
+        self.push_statement(Statement::RETURN(ReturnStatement { token: tok, return_value: value }))
+    }
+    fn expression_statement(&mut self, tok: Token, expression: ExprId) -> StmtId {
+        self.push_statement(Statement::EXPRESSION(ExpressionStatement { token: tok, expression }))
+    }
+    fn import_statement(&mut self, statement: ImportStatement) -> StmtId {
+        self.push_statement(Statement::IMPORT(statement))
+    }
+    fn block_statement(&mut self, tok: Token, statements: Vec<StmtId>) -> BlockId {
+        let statements = self.push_statement_list(statements);
+        self.push_block(BlockStatement { token: tok, statements })
+    }
+    // the parser keeps the builder, the finished program is moved out of it
+    fn program(&mut self, statements: Vec<StmtId>) -> Ast {
+        self.program = self.push_statement_list(statements);
+        std::mem::take(self)
+    }
+}
+
+// the same as ast::Visitor for the arena, every method also gets the ast the node is in.
+// expressions and statements are passed by id, so a visitor can keep what it finds out about them in a side table
+pub trait Visitor {
+    fn visit_program(&mut self, ast: &Ast) {
+        walk_program(self, ast)
+    }
+    fn visit_statement(&mut self, ast: &Ast, statement: StmtId) {
+        walk_statement(self, ast, statement)
+    }
+    fn visit_let_statement(&mut self, ast: &Ast, statement: &LetStatement) {
+        walk_let_statement(self, ast, statement)
+    }
+    fn visit_return_statement(&mut self, ast: &Ast, statement: &ReturnStatement) {
+        walk_return_statement(self, ast, statement)
+    }
+    fn visit_expression_statement(&mut self, ast: &Ast, statement: &ExpressionStatement) {
+        walk_expression_statement(self, ast, statement)
+    }
+    fn visit_block_statement(&mut self, ast: &Ast, block: &BlockStatement) {
+        walk_block_statement(self, ast, block)
+    }
+    fn visit_import_statement(&mut self, ast: &Ast, statement: &ImportStatement) {
+        walk_import_statement(self, ast, statement)
+    }
+    fn visit_export_statement(&mut self, ast: &Ast, statement: &ExportStatement) {
+        walk_export_statement(self, ast, statement)
+    }
+    fn visit_expression(&mut self, ast: &Ast, expression: ExprId) {
+        walk_expression(self, ast, expression)
+    }
+    fn visit_identifier(&mut self, _ast: &Ast, _identifier: &Identifier) {}
+    fn visit_integer_literal(&mut self, _ast: &Ast, _integer_literal: &IntegerLiteral) {}
+    fn visit_string_literal(&mut self, _ast: &Ast, _string_literal: &StringLiteral) {}
+    fn visit_boolean(&mut self, _ast: &Ast, _boolean: &Boolean) {}
+    fn visit_prefix_expression(&mut self, ast: &Ast, expression: &PrefixExpression) {
+        walk_prefix_expression(self, ast, expression)
+    }
+    fn visit_infix_expression(&mut self, ast: &Ast, expression: &InfixExpression) {
+        walk_infix_expression(self, ast, expression)
+    }
+    fn visit_if_expression(&mut self, ast: &Ast, expression: &IfExpression) {
+        walk_if_expression(self, ast, expression)
+    }
+    fn visit_function_literal(&mut self, ast: &Ast, expression: &FunctionLiteral) {
+        walk_function_literal(self, ast, expression)
+    }
+    fn visit_call_expression(&mut self, ast: &Ast, expression: &CallExpression) {
+        walk_call_expression(self, ast, expression)
+    }
+    fn visit_member_expression(&mut self, ast: &Ast, expression: &MemberExpression) {
+        walk_member_expression(self, ast, expression)
+    }
+    fn visit_array_literal(&mut self, ast: &Ast, expression: &ArrayLiteral) {
+        walk_array_literal(self, ast, expression)
+    }
+    fn visit_hash_literal(&mut self, ast: &Ast, expression: &HashLiteral) {
+        walk_hash_literal(self, ast, expression)
+    }
+    fn visit_index_expression(&mut self, ast: &Ast, expression: &IndexExpression) {
+        walk_index_expression(self, ast, expression)
+    }
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast) {
+    for statement in ast.program() {
+        visitor.visit_statement(ast, *statement);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, statement: StmtId) {
+    match &ast[statement] {
+        Statement::LET(x) => visitor.visit_let_statement(ast, x),
+        Statement::RETURN(x) => visitor.visit_return_statement(ast, x),
+        Statement::EXPRESSION(x) => visitor.visit_expression_statement(ast, x),
+        Statement::IMPORT(x) => visitor.visit_import_statement(ast, x),
+        Statement::EXPORT(x) => visitor.visit_export_statement(ast, x),
+    }
+}
+
+pub fn walk_let_statement<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, statement: &LetStatement) {
+    visitor.visit_identifier(ast, &statement.name);
+    visitor.visit_expression(ast, statement.value);
+}
+
+pub fn walk_return_statement<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, statement: &ReturnStatement) {
+    visitor.visit_expression(ast, statement.return_value);
+}
+
+pub fn walk_expression_statement<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, statement: &ExpressionStatement) {
+    visitor.visit_expression(ast, statement.expression);
+}
+
+pub fn walk_block_statement<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, block: &BlockStatement) {
+    for statement in ast.statement_list(block.statements) {
+        visitor.visit_statement(ast, *statement);
+    }
+}
+
+pub fn walk_import_statement<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, statement: &ImportStatement) {
+    if let Some(alias) = &statement.alias {
+        visitor.visit_identifier(ast, alias);
+    }
+    for name in &statement.names {
+        visitor.visit_identifier(ast, name);
+    }
+}
+
+pub fn walk_export_statement<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, statement: &ExportStatement) {
+    visitor.visit_let_statement(ast, &statement.statement);
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, expression: ExprId) {
+    match &ast[expression] {
+        MonkeyExpression::IDENT(x) => visitor.visit_identifier(ast, x),
+        MonkeyExpression::INTEGERLITERAL(x) => visitor.visit_integer_literal(ast, x),
+        MonkeyExpression::STRINGLITERAL(x) => visitor.visit_string_literal(ast, x),
+        MonkeyExpression::BOOLEAN(x) => visitor.visit_boolean(ast, x),
+        MonkeyExpression::PREFIX(x) => visitor.visit_prefix_expression(ast, x),
+        MonkeyExpression::INFIX(x) => visitor.visit_infix_expression(ast, x),
+        MonkeyExpression::IF(x) => visitor.visit_if_expression(ast, x),
+        MonkeyExpression::FUNCTIONLITERAL(x) => visitor.visit_function_literal(ast, &ast[*x]),
+        MonkeyExpression::CALL(x) => visitor.visit_call_expression(ast, x),
+        MonkeyExpression::MEMBER(x) => visitor.visit_member_expression(ast, x),
+        MonkeyExpression::ARRAY(x) => visitor.visit_array_literal(ast, x),
+        MonkeyExpression::HASH(x) => visitor.visit_hash_literal(ast, x),
+        MonkeyExpression::INDEX(x) => visitor.visit_index_expression(ast, x),
+    }
+}
+
+pub fn walk_prefix_expression<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, expression: &PrefixExpression) {
+    visitor.visit_expression(ast, expression.right);
+}
+
+pub fn walk_infix_expression<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, expression: &InfixExpression) {
+    visitor.visit_expression(ast, expression.left);
+    visitor.visit_expression(ast, expression.right);
+}
+
+pub fn walk_if_expression<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, expression: &IfExpression) {
+    visitor.visit_expression(ast, expression.condition);
+    visitor.visit_block_statement(ast, &ast[expression.consequence]);
+    if let Some(alternative) = expression.alternative {
+        visitor.visit_block_statement(ast, &ast[alternative]);
+    }
+}
+
+pub fn walk_function_literal<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, expression: &FunctionLiteral) {
+    for param in &expression.parameters {
+        visitor.visit_identifier(ast, param);
+    }
+    visitor.visit_block_statement(ast, &ast[expression.blockstatment]);
+}
+
+pub fn walk_call_expression<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, expression: &CallExpression) {
+    visitor.visit_expression(ast, expression.function);
+    if let Some(arguments) = expression.arguments {
+        for arg in ast.expression_list(arguments) {
+            visitor.visit_expression(ast, *arg);
+        }
+    }
+}
+
+pub fn walk_member_expression<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, expression: &MemberExpression) {
+    visitor.visit_expression(ast, expression.object);
+    visitor.visit_identifier(ast, &expression.property);
+}
+
+pub fn walk_array_literal<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, expression: &ArrayLiteral) {
+    for element in ast.expression_list(expression.elements) {
+        visitor.visit_expression(ast, *element);
+    }
+}
+
+pub fn walk_hash_literal<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, expression: &HashLiteral) {
+    for (key, value) in ast.pairs(expression) {
+        visitor.visit_expression(ast, key);
+        visitor.visit_expression(ast, value);
+    }
+}
+
+pub fn walk_index_expression<V: Visitor + ?Sized>(visitor: &mut V, ast: &Ast, expression: &IndexExpression) {
+    visitor.visit_expression(ast, expression.left);
+    visitor.visit_expression(ast, expression.index);
+}
@@ -1,10 +1,13 @@
+use crate::parser::Precedence;
 use crate::token::*;
+use std::fmt;
+use std::rc::Rc;
 use std::fmt::Debug;
 
 pub trait MonkeyExpr: Expression + Node + Debug + Clone {}
 
 pub trait Node {
-    fn token_literal(&self) -> Option<&String>;
+    fn token_literal(&self) -> Option<Literal>;
     fn node_type(&self) -> NodeType;
 }
 pub trait MonkeyStatement: Node {}
@@ -25,14 +28,28 @@ pub enum NodeType {
 pub enum MonkeyExpression {
     IDENT(Identifier),
     INTEGERLITERAL(IntegerLiteral),
+    STRINGLITERAL(StringLiteral),
     BOOLEAN(Boolean),
     PREFIX(PrefixExpression),
     INFIX(InfixExpression),
     IF(IfExpression),
-    FUNCTIONLITERAL(FunctionLiteral),
+    // shared with the functions created from it, so calling one does not copy its body
+    FUNCTIONLITERAL(Rc<FunctionLiteral>),
     CALL(CallExpression),
+    MEMBER(MemberExpression),
+    ARRAY(ArrayLiteral),
+    HASH(HashLiteral),
+    INDEX(IndexExpression),
 }
 impl MonkeyExpression {
+    // how strongly the expression holds together when it is written out, see fmt_operand
+    pub fn precedence(&self) -> i32 {
+        match self {
+            Self::INFIX(x) => Precedence::of(&x.token.tokentype).into_i32(),
+            Self::PREFIX(_) => Precedence::PREFIX.into_i32(),
+            _ => Precedence::INDEX.into_i32(),
+        }
+    }
     // pub fn into_expr(self) -> Box<dyn MonkeyExpr> {
     //     match self {
     //         Self::IDENT(x) => Box::new(x),
@@ -47,22 +64,46 @@ impl MonkeyExpression {
     // }
 }
 impl Node for MonkeyExpression {
-    fn token_literal(&self) -> Option<&String> {
+    fn token_literal(&self) -> Option<Literal> {
         match &self {
             Self::IDENT(expr) => expr.token_literal(),
             Self::INTEGERLITERAL(expr) =>expr.token_literal(),
+            Self::STRINGLITERAL(expr) => expr.token_literal(),
             Self::BOOLEAN(expr) => expr.token_literal(),
             Self::PREFIX(expr) => expr.token_literal(),
             Self::INFIX(expr) => expr.token_literal(),
             Self::IF(expr) => expr.token_literal(),
             Self::FUNCTIONLITERAL(expr) => expr.token_literal(),
             Self::CALL(expr) => expr.token_literal(),
+            Self::MEMBER(expr) => expr.token_literal(),
+            Self::ARRAY(expr) => expr.token_literal(),
+            Self::HASH(expr) => expr.token_literal(),
+            Self::INDEX(expr) => expr.token_literal(),
         }
     }
     fn node_type(&self) -> NodeType {
         NodeType::EXPRESSION
     }
 }
+impl fmt::Display for MonkeyExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::IDENT(expr) => write!(f, "{}", expr),
+            Self::INTEGERLITERAL(expr) => write!(f, "{}", expr),
+            Self::STRINGLITERAL(expr) => write!(f, "{}", expr),
+            Self::BOOLEAN(expr) => write!(f, "{}", expr),
+            Self::PREFIX(expr) => write!(f, "{}", expr),
+            Self::INFIX(expr) => write!(f, "{}", expr),
+            Self::IF(expr) => write!(f, "{}", expr),
+            Self::FUNCTIONLITERAL(expr) => write!(f, "{}", expr),
+            Self::CALL(expr) => write!(f, "{}", expr),
+            Self::MEMBER(expr) => write!(f, "{}", expr),
+            Self::ARRAY(expr) => write!(f, "{}", expr),
+            Self::HASH(expr) => write!(f, "{}", expr),
+            Self::INDEX(expr) => write!(f, "{}", expr),
+        }
+    }
+}
 impl Expression for MonkeyExpression {
     fn expression_node(&self) {
         
@@ -70,47 +111,110 @@ impl Expression for MonkeyExpression {
 }
 impl MonkeyExpr for MonkeyExpression {}
 
+// a type written after a let name, a parameter or the parameters of a function:
+// int, bool, string, any, [int], {string: int} or fn(int, int) -> bool.
+// the evaluator ignores them, only "monkey check --types" reads them
+#[derive(Debug, PartialEq, Clone)]
+pub enum TypeAnnotation {
+    NAME(Identifier),
+    ARRAY(Token, Box<TypeAnnotation>),
+    HASH(Token, Box<TypeAnnotation>, Box<TypeAnnotation>),
+    FUNCTION(Token, Vec<TypeAnnotation>, Box<TypeAnnotation>),
+}
+impl TypeAnnotation {
+    pub const NAMES: [&'static str; 4] = ["int", "bool", "string", "any"];
+
+    // the first token of the annotation
+    pub fn token(&self) -> &Token {
+        match self {
+            Self::NAME(x) => &x.token,
+            Self::ARRAY(tok, _) | Self::HASH(tok, _, _) | Self::FUNCTION(tok, _, _) => tok,
+        }
+    }
+}
+impl fmt::Display for TypeAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NAME(x) => write!(f, "{}", x),
+            Self::ARRAY(_, element) => write!(f, "[{}]", element),
+            Self::HASH(_, key, value) => write!(f, "{{{}: {}}}", key, value),
+            Self::FUNCTION(_, params, result) => {
+                let params: Vec<String> = params.iter().map(|x| x.to_string()).collect();
+                write!(f, "fn({}) -> {}", params.join(", "), result)
+            },
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Statement {
     LET(LetStatement),
     RETURN(ReturnStatement),
     EXPRESSION(ExpressionStatement),
-    BLOCK(BlockStatement)
+    BLOCK(BlockStatement),
+    IMPORT(ImportStatement),
+    EXPORT(ExportStatement),
 }
 impl Node for Statement {
-    fn token_literal(&self) -> Option<&String> {
+    fn token_literal(&self) -> Option<Literal> {
         match &self {
             Self::LET(statement) => statement.token_literal(),
             Self::RETURN(statement) => statement.token_literal(),
             Self::EXPRESSION(statement) => statement.token_literal(),
             Self::BLOCK(statement) => statement.token_literal(),
+            Self::IMPORT(statement) => statement.token_literal(),
+            Self::EXPORT(statement) => statement.token_literal(),
         }
     }
     fn node_type(&self) -> NodeType {
         NodeType::STATEMENT
     }
 }
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::LET(statement) => write!(f, "{}", statement),
+            Self::RETURN(statement) => write!(f, "{}", statement),
+            Self::EXPRESSION(statement) => write!(f, "{}", statement),
+            Self::BLOCK(statement) => write!(f, "{}", statement),
+            Self::IMPORT(statement) => write!(f, "{}", statement),
+            Self::EXPORT(statement) => write!(f, "{}", statement),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Programm {
     pub statements: Vec<Statement>,
 }
 impl Programm {
-    fn print_program(&self) {
-        let mut result = String::new();
-        for stmt in &self.statements{
-            result.push_str(stmt.token_literal().unwrap())
+    pub fn print_program(&self) {
+        println!("{}", self);
+    }
+}
+// one statement per line, statements spanning several lines are separated by an empty line
+impl fmt::Display for Programm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut previous_multiline = false;
+        for (i, stmt) in self.statements.iter().enumerate() {
+            let source = stmt.to_string();
+            let multiline = source.contains('\n');
+            if i > 0 {
+                writeln!(f)?;
+                if multiline || previous_multiline {
+                    writeln!(f)?;
+                }
+            }
+            write!(f, "{}", source)?;
+            previous_multiline = multiline;
         }
-        println!("{}", result);
+        Ok(())
     }
 }
 impl Node for Programm {
-    fn token_literal(&self) -> Option<&String> {
-        if self.statements.len() > 0 {
-            match &self.statements[0].token_literal() {
-                Some(x) => return Some(x),
-                None => return None,
-            }
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.statements.is_empty() {
+            self.statements[0].token_literal()
         } else {
             None
         }
@@ -120,3 +224,439 @@ impl Node for Programm {
     }
 }
 
+
+// writes expr and puts it in parentheses if it binds less strongly than min_precedence requires
+pub fn fmt_operand(f: &mut dyn fmt::Write, expr: &MonkeyExpression, min_precedence: i32) -> fmt::Result {
+    if expr.precedence() < min_precedence {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+// a block with a single short expression is written as "{ expr }" unless it has to be multiline,
+// otherwise every statement gets its own line indented by four spaces.
+// the last expression of a block is the value of the block and is written without ";"
+pub fn fmt_block(f: &mut dyn fmt::Write, block: &BlockStatement) -> fmt::Result {
+    fmt_block_with(f, block, true)
+}
+
+pub fn fmt_block_with(f: &mut dyn fmt::Write, block: &BlockStatement, allow_inline: bool) -> fmt::Result {
+    let statements = &block.statements;
+    if statements.is_empty() && !block.multiline {
+        return write!(f, "{{}}")
+    }
+    if let (true, false, [Statement::EXPRESSION(stmt)]) = (allow_inline, block.multiline, statements.as_slice()) {
+        let source = stmt.expression.to_string();
+        if !source.contains('\n') && source.len() <= 60 {
+            return write!(f, "{{ {} }}", source)
+        }
+    }
+    writeln!(f, "{{")?;
+    for (i, stmt) in statements.iter().enumerate() {
+        let source = match stmt {
+            Statement::EXPRESSION(x) if i == statements.len() - 1 => x.expression.to_string(),
+            other => other.to_string(),
+        };
+        for line in source.lines() {
+            if line.is_empty() {
+                writeln!(f)?;
+            } else {
+                writeln!(f, "    {}", line)?;
+            }
+        }
+    }
+    write!(f, "}}")
+}
+
+// the string as a monkey string literal, quotes included
+pub fn quote_string(value: &str) -> String {
+    let mut result = String::from('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\r' => result.push_str("\\r"),
+            other => result.push(other),
+        }
+    }
+    result.push('"');
+    result
+}
+
+// every pass over the ast can be written as a visitor: it overrides the visit_ methods of the nodes
+// it cares about and calls the walk_ function of the node inside of them to go on into the children.
+// the default of every visit_ method just walks, so a visitor that overrides nothing visits the whole tree.
+// identifiers are visited everywhere they appear: names of lets and imports, parameters,
+// properties of member expressions and identifier expressions.
+// the nodes are borrowed for 'a, the lifetime of the ast, so a visitor can keep references to them
+pub trait Visitor<'a> {
+    fn visit_program(&mut self, program: &'a Programm) {
+        walk_program(self, program)
+    }
+    fn visit_statement(&mut self, statement: &'a Statement) {
+        walk_statement(self, statement)
+    }
+    fn visit_let_statement(&mut self, statement: &'a LetStatement) {
+        walk_let_statement(self, statement)
+    }
+    fn visit_return_statement(&mut self, statement: &'a ReturnStatement) {
+        walk_return_statement(self, statement)
+    }
+    fn visit_expression_statement(&mut self, statement: &'a ExpressionStatement) {
+        walk_expression_statement(self, statement)
+    }
+    fn visit_block_statement(&mut self, block: &'a BlockStatement) {
+        walk_block_statement(self, block)
+    }
+    fn visit_import_statement(&mut self, statement: &'a ImportStatement) {
+        walk_import_statement(self, statement)
+    }
+    fn visit_export_statement(&mut self, statement: &'a ExportStatement) {
+        walk_export_statement(self, statement)
+    }
+    fn visit_expression(&mut self, expression: &'a MonkeyExpression) {
+        walk_expression(self, expression)
+    }
+    fn visit_identifier(&mut self, _identifier: &'a Identifier) {}
+    fn visit_integer_literal(&mut self, _integer_literal: &'a IntegerLiteral) {}
+    fn visit_string_literal(&mut self, _string_literal: &'a StringLiteral) {}
+    fn visit_boolean(&mut self, _boolean: &'a Boolean) {}
+    fn visit_prefix_expression(&mut self, expression: &'a PrefixExpression) {
+        walk_prefix_expression(self, expression)
+    }
+    fn visit_infix_expression(&mut self, expression: &'a InfixExpression) {
+        walk_infix_expression(self, expression)
+    }
+    fn visit_if_expression(&mut self, expression: &'a IfExpression) {
+        walk_if_expression(self, expression)
+    }
+    fn visit_function_literal(&mut self, expression: &'a FunctionLiteral) {
+        walk_function_literal(self, expression)
+    }
+    fn visit_call_expression(&mut self, expression: &'a CallExpression) {
+        walk_call_expression(self, expression)
+    }
+    fn visit_member_expression(&mut self, expression: &'a MemberExpression) {
+        walk_member_expression(self, expression)
+    }
+    fn visit_array_literal(&mut self, expression: &'a ArrayLiteral) {
+        walk_array_literal(self, expression)
+    }
+    fn visit_hash_literal(&mut self, expression: &'a HashLiteral) {
+        walk_hash_literal(self, expression)
+    }
+    fn visit_index_expression(&mut self, expression: &'a IndexExpression) {
+        walk_index_expression(self, expression)
+    }
+}
+
+pub fn walk_program<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, program: &'a Programm) {
+    for statement in &program.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, statement: &'a Statement) {
+    match statement {
+        Statement::LET(x) => visitor.visit_let_statement(x),
+        Statement::RETURN(x) => visitor.visit_return_statement(x),
+        Statement::EXPRESSION(x) => visitor.visit_expression_statement(x),
+        Statement::BLOCK(x) => visitor.visit_block_statement(x),
+        Statement::IMPORT(x) => visitor.visit_import_statement(x),
+        Statement::EXPORT(x) => visitor.visit_export_statement(x),
+    }
+}
+
+pub fn walk_let_statement<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, statement: &'a LetStatement) {
+    visitor.visit_identifier(&statement.name);
+    visitor.visit_expression(&statement.value);
+}
+
+pub fn walk_return_statement<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, statement: &'a ReturnStatement) {
+    visitor.visit_expression(&statement.return_value);
+}
+
+pub fn walk_expression_statement<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, statement: &'a ExpressionStatement) {
+    visitor.visit_expression(&statement.expression);
+}
+
+pub fn walk_block_statement<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, block: &'a BlockStatement) {
+    for statement in &block.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_import_statement<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, statement: &'a ImportStatement) {
+    if let Some(alias) = &statement.alias {
+        visitor.visit_identifier(alias);
+    }
+    for name in &statement.names {
+        visitor.visit_identifier(name);
+    }
+}
+
+pub fn walk_export_statement<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, statement: &'a ExportStatement) {
+    visitor.visit_let_statement(&statement.statement);
+}
+
+pub fn walk_expression<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: &'a MonkeyExpression) {
+    match expression {
+        MonkeyExpression::IDENT(x) => visitor.visit_identifier(x),
+        MonkeyExpression::INTEGERLITERAL(x) => visitor.visit_integer_literal(x),
+        MonkeyExpression::STRINGLITERAL(x) => visitor.visit_string_literal(x),
+        MonkeyExpression::BOOLEAN(x) => visitor.visit_boolean(x),
+        MonkeyExpression::PREFIX(x) => visitor.visit_prefix_expression(x),
+        MonkeyExpression::INFIX(x) => visitor.visit_infix_expression(x),
+        MonkeyExpression::IF(x) => visitor.visit_if_expression(x),
+        MonkeyExpression::FUNCTIONLITERAL(x) => visitor.visit_function_literal(x),
+        MonkeyExpression::CALL(x) => visitor.visit_call_expression(x),
+        MonkeyExpression::MEMBER(x) => visitor.visit_member_expression(x),
+        MonkeyExpression::ARRAY(x) => visitor.visit_array_literal(x),
+        MonkeyExpression::HASH(x) => visitor.visit_hash_literal(x),
+        MonkeyExpression::INDEX(x) => visitor.visit_index_expression(x),
+    }
+}
+
+pub fn walk_prefix_expression<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: &'a PrefixExpression) {
+    visitor.visit_expression(&expression.right);
+}
+
+pub fn walk_infix_expression<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: &'a InfixExpression) {
+    visitor.visit_expression(&expression.left);
+    visitor.visit_expression(&expression.right);
+}
+
+pub fn walk_if_expression<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: &'a IfExpression) {
+    visitor.visit_expression(&expression.condition);
+    visitor.visit_block_statement(&expression.consequence);
+    if let Some(alternative) = &expression.alternative {
+        visitor.visit_block_statement(alternative);
+    }
+}
+
+pub fn walk_function_literal<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: &'a FunctionLiteral) {
+    for param in &expression.parameters {
+        visitor.visit_identifier(param);
+    }
+    visitor.visit_block_statement(&expression.blockstatment);
+}
+
+pub fn walk_call_expression<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: &'a CallExpression) {
+    visitor.visit_expression(&expression.function);
+    if let Some(arguments) = &expression.arguments {
+        for arg in arguments {
+            visitor.visit_expression(arg);
+        }
+    }
+}
+
+pub fn walk_member_expression<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: &'a MemberExpression) {
+    visitor.visit_expression(&expression.object);
+    visitor.visit_identifier(&expression.property);
+}
+
+pub fn walk_array_literal<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: &'a ArrayLiteral) {
+    for element in &expression.elements {
+        visitor.visit_expression(element);
+    }
+}
+
+pub fn walk_hash_literal<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: &'a HashLiteral) {
+    for (key, value) in &expression.pairs {
+        visitor.visit_expression(key);
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_index_expression<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: &'a IndexExpression) {
+    visitor.visit_expression(&expression.left);
+    visitor.visit_expression(&expression.index);
+}
+
+// the same as Visitor with mutable references, expressions can be replaced in place
+pub trait VisitorMut {
+    fn visit_program_mut(&mut self, program: &mut Programm) {
+        walk_program_mut(self, program)
+    }
+    fn visit_statement_mut(&mut self, statement: &mut Statement) {
+        walk_statement_mut(self, statement)
+    }
+    fn visit_let_statement_mut(&mut self, statement: &mut LetStatement) {
+        walk_let_statement_mut(self, statement)
+    }
+    fn visit_return_statement_mut(&mut self, statement: &mut ReturnStatement) {
+        walk_return_statement_mut(self, statement)
+    }
+    fn visit_expression_statement_mut(&mut self, statement: &mut ExpressionStatement) {
+        walk_expression_statement_mut(self, statement)
+    }
+    fn visit_block_statement_mut(&mut self, block: &mut BlockStatement) {
+        walk_block_statement_mut(self, block)
+    }
+    fn visit_import_statement_mut(&mut self, statement: &mut ImportStatement) {
+        walk_import_statement_mut(self, statement)
+    }
+    fn visit_export_statement_mut(&mut self, statement: &mut ExportStatement) {
+        walk_export_statement_mut(self, statement)
+    }
+    fn visit_expression_mut(&mut self, expression: &mut MonkeyExpression) {
+        walk_expression_mut(self, expression)
+    }
+    fn visit_identifier_mut(&mut self, _identifier: &mut Identifier) {}
+    fn visit_integer_literal_mut(&mut self, _integer_literal: &mut IntegerLiteral) {}
+    fn visit_string_literal_mut(&mut self, _string_literal: &mut StringLiteral) {}
+    fn visit_boolean_mut(&mut self, _boolean: &mut Boolean) {}
+    fn visit_prefix_expression_mut(&mut self, expression: &mut PrefixExpression) {
+        walk_prefix_expression_mut(self, expression)
+    }
+    fn visit_infix_expression_mut(&mut self, expression: &mut InfixExpression) {
+        walk_infix_expression_mut(self, expression)
+    }
+    fn visit_if_expression_mut(&mut self, expression: &mut IfExpression) {
+        walk_if_expression_mut(self, expression)
+    }
+    fn visit_function_literal_mut(&mut self, expression: &mut FunctionLiteral) {
+        walk_function_literal_mut(self, expression)
+    }
+    fn visit_call_expression_mut(&mut self, expression: &mut CallExpression) {
+        walk_call_expression_mut(self, expression)
+    }
+    fn visit_member_expression_mut(&mut self, expression: &mut MemberExpression) {
+        walk_member_expression_mut(self, expression)
+    }
+    fn visit_array_literal_mut(&mut self, expression: &mut ArrayLiteral) {
+        walk_array_literal_mut(self, expression)
+    }
+    fn visit_hash_literal_mut(&mut self, expression: &mut HashLiteral) {
+        walk_hash_literal_mut(self, expression)
+    }
+    fn visit_index_expression_mut(&mut self, expression: &mut IndexExpression) {
+        walk_index_expression_mut(self, expression)
+    }
+}
+
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Programm) {
+    for statement in &mut program.statements {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::LET(x) => visitor.visit_let_statement_mut(x),
+        Statement::RETURN(x) => visitor.visit_return_statement_mut(x),
+        Statement::EXPRESSION(x) => visitor.visit_expression_statement_mut(x),
+        Statement::BLOCK(x) => visitor.visit_block_statement_mut(x),
+        Statement::IMPORT(x) => visitor.visit_import_statement_mut(x),
+        Statement::EXPORT(x) => visitor.visit_export_statement_mut(x),
+    }
+}
+
+pub fn walk_let_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut LetStatement) {
+    visitor.visit_identifier_mut(&mut statement.name);
+    visitor.visit_expression_mut(&mut statement.value);
+}
+
+pub fn walk_return_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut ReturnStatement) {
+    visitor.visit_expression_mut(&mut statement.return_value);
+}
+
+pub fn walk_expression_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut ExpressionStatement) {
+    visitor.visit_expression_mut(&mut statement.expression);
+}
+
+pub fn walk_block_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut BlockStatement) {
+    for statement in &mut block.statements {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_import_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut ImportStatement) {
+    if let Some(alias) = &mut statement.alias {
+        visitor.visit_identifier_mut(alias);
+    }
+    for name in &mut statement.names {
+        visitor.visit_identifier_mut(name);
+    }
+}
+
+pub fn walk_export_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut ExportStatement) {
+    visitor.visit_let_statement_mut(&mut statement.statement);
+}
+
+pub fn walk_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut MonkeyExpression) {
+    match expression {
+        MonkeyExpression::IDENT(x) => visitor.visit_identifier_mut(x),
+        MonkeyExpression::INTEGERLITERAL(x) => visitor.visit_integer_literal_mut(x),
+        MonkeyExpression::STRINGLITERAL(x) => visitor.visit_string_literal_mut(x),
+        MonkeyExpression::BOOLEAN(x) => visitor.visit_boolean_mut(x),
+        MonkeyExpression::PREFIX(x) => visitor.visit_prefix_expression_mut(x),
+        MonkeyExpression::INFIX(x) => visitor.visit_infix_expression_mut(x),
+        MonkeyExpression::IF(x) => visitor.visit_if_expression_mut(x),
+        MonkeyExpression::FUNCTIONLITERAL(x) => visitor.visit_function_literal_mut(Rc::make_mut(x)),
+        MonkeyExpression::CALL(x) => visitor.visit_call_expression_mut(x),
+        MonkeyExpression::MEMBER(x) => visitor.visit_member_expression_mut(x),
+        MonkeyExpression::ARRAY(x) => visitor.visit_array_literal_mut(x),
+        MonkeyExpression::HASH(x) => visitor.visit_hash_literal_mut(x),
+        MonkeyExpression::INDEX(x) => visitor.visit_index_expression_mut(x),
+    }
+}
+
+pub fn walk_prefix_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut PrefixExpression) {
+    visitor.visit_expression_mut(&mut expression.right);
+}
+
+pub fn walk_infix_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut InfixExpression) {
+    visitor.visit_expression_mut(&mut expression.left);
+    visitor.visit_expression_mut(&mut expression.right);
+}
+
+pub fn walk_if_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut IfExpression) {
+    visitor.visit_expression_mut(&mut expression.condition);
+    visitor.visit_block_statement_mut(&mut expression.consequence);
+    if let Some(alternative) = &mut expression.alternative {
+        visitor.visit_block_statement_mut(alternative);
+    }
+}
+
+pub fn walk_function_literal_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut FunctionLiteral) {
+    for param in &mut expression.parameters {
+        visitor.visit_identifier_mut(param);
+    }
+    visitor.visit_block_statement_mut(&mut expression.blockstatment);
+}
+
+pub fn walk_call_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut CallExpression) {
+    visitor.visit_expression_mut(&mut expression.function);
+    if let Some(arguments) = &mut expression.arguments {
+        for arg in arguments {
+            visitor.visit_expression_mut(arg);
+        }
+    }
+}
+
+pub fn walk_member_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut MemberExpression) {
+    visitor.visit_expression_mut(&mut expression.object);
+    visitor.visit_identifier_mut(&mut expression.property);
+}
+
+pub fn walk_array_literal_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut ArrayLiteral) {
+    for element in &mut expression.elements {
+        visitor.visit_expression_mut(element);
+    }
+}
+
+pub fn walk_hash_literal_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut HashLiteral) {
+    for (key, value) in &mut expression.pairs {
+        visitor.visit_expression_mut(key);
+        visitor.visit_expression_mut(value);
+    }
+}
+
+pub fn walk_index_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut IndexExpression) {
+    visitor.visit_expression_mut(&mut expression.left);
+    visitor.visit_expression_mut(&mut expression.index);
+}
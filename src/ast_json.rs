@@ -0,0 +1,398 @@
+use std::rc::Rc;
+
+use crate::ast::*;
+use crate::json::JsonValue;
+use crate::lexer::Lexer;
+use crate::resolver;
+use crate::symbol::Symbol;
+use crate::token::*;
+
+// the ast as json for tools outside of the interpreter. every node is an object with "kind", the name of
+// its struct in token.rs, and "span" with the byte offsets and the line and column of its first token.
+// the other keys are the children and literals of the node, a missing optional child is null:
+//
+//   {"kind":"InfixExpression","span":{...},"operator":"+","left":{...},"right":{...}}
+//
+// from_json builds the same Programm again, spans are optional there so tools can write nodes by hand
+pub fn to_json(program: &Programm) -> JsonValue {
+    JsonValue::object(vec![
+        ("kind", JsonValue::string("Programm")),
+        ("statements", JsonValue::ARRAY(program.statements.iter().map(statement_to_json).collect())),
+    ])
+}
+
+fn node(kind: &str, token: &Token, mut fields: Vec<(&str, JsonValue)>) -> JsonValue {
+    let mut pairs = vec![("kind", JsonValue::string(kind)), ("span", span_to_json(token.span))];
+    pairs.append(&mut fields);
+    JsonValue::object(pairs)
+}
+
+fn span_to_json(span: Span) -> JsonValue {
+    JsonValue::object(vec![
+        ("start", JsonValue::NUMBER(span.start as i64)),
+        ("end", JsonValue::NUMBER(span.end as i64)),
+        ("line", JsonValue::NUMBER(span.line as i64)),
+        ("column", JsonValue::NUMBER(span.column as i64)),
+    ])
+}
+
+fn optional<T>(value: Option<&T>, to_json: fn(&T) -> JsonValue) -> JsonValue {
+    match value {
+        Some(x) => to_json(x),
+        None => JsonValue::NULL,
+    }
+}
+
+fn statement_to_json(statement: &Statement) -> JsonValue {
+    match statement {
+        Statement::LET(x) => let_to_json(x),
+        Statement::RETURN(x) => node("ReturnStatement", &x.token, vec![("value", expression_to_json(&x.return_value))]),
+        Statement::EXPRESSION(x) => node("ExpressionStatement", &x.token, vec![("expression", expression_to_json(&x.expression))]),
+        Statement::BLOCK(x) => block_to_json(x),
+        Statement::IMPORT(x) => node("ImportStatement", &x.token, vec![
+            ("path", JsonValue::string(&x.path)),
+            ("alias", optional(x.alias.as_ref(), identifier_to_json)),
+            ("names", JsonValue::ARRAY(x.names.iter().map(identifier_to_json).collect())),
+        ]),
+        Statement::EXPORT(x) => node("ExportStatement", &x.token, vec![("statement", let_to_json(&x.statement))]),
+    }
+}
+
+fn let_to_json(statement: &LetStatement) -> JsonValue {
+    node("LetStatement", &statement.token, vec![
+        ("doc", match statement.doc() {
+            Some(x) => JsonValue::string(x),
+            None => JsonValue::NULL,
+        }),
+        ("name", identifier_to_json(&statement.name)),
+        ("annotation", optional(statement.annotation.as_ref(), type_to_json)),
+        ("value", expression_to_json(&statement.value)),
+    ])
+}
+
+fn block_to_json(block: &BlockStatement) -> JsonValue {
+    node("BlockStatement", &block.token, vec![("statements", JsonValue::ARRAY(block.statements.iter().map(statement_to_json).collect()))])
+}
+
+fn identifier_to_json(identifier: &Identifier) -> JsonValue {
+    node("Identifier", &identifier.token, vec![("name", JsonValue::string(&identifier.value.text()))])
+}
+
+fn type_to_json(annotation: &TypeAnnotation) -> JsonValue {
+    match annotation {
+        TypeAnnotation::NAME(x) => node("TypeName", &x.token, vec![("name", JsonValue::string(&x.value.text()))]),
+        TypeAnnotation::ARRAY(tok, element) => node("ArrayType", tok, vec![("element", type_to_json(element))]),
+        TypeAnnotation::HASH(tok, key, value) => node("HashType", tok, vec![("key", type_to_json(key)), ("value", type_to_json(value))]),
+        TypeAnnotation::FUNCTION(tok, params, result) => node("FunctionType", tok, vec![
+            ("parameters", JsonValue::ARRAY(params.iter().map(type_to_json).collect())),
+            ("result", type_to_json(result)),
+        ]),
+    }
+}
+
+fn expression_to_json(expression: &MonkeyExpression) -> JsonValue {
+    match expression {
+        MonkeyExpression::IDENT(x) => identifier_to_json(x),
+        // the literal is the number as it was written, like 0xff or 1_000
+        MonkeyExpression::INTEGERLITERAL(x) => node("IntegerLiteral", &x.token, vec![
+            ("value", JsonValue::NUMBER(x.value)),
+            ("literal", JsonValue::string(x.token.literal.as_str())),
+        ]),
+        MonkeyExpression::STRINGLITERAL(x) => node("StringLiteral", &x.token, vec![("value", JsonValue::string(x.value.as_str()))]),
+        MonkeyExpression::BOOLEAN(x) => node("Boolean", &x.token, vec![("value", JsonValue::BOOL(x.value))]),
+        MonkeyExpression::PREFIX(x) => node("PrefixExpression", &x.token, vec![
+            ("operator", JsonValue::string(&x.operator)),
+            ("right", expression_to_json(&x.right)),
+        ]),
+        MonkeyExpression::INFIX(x) => node("InfixExpression", &x.token, vec![
+            ("operator", JsonValue::string(&x.operator)),
+            ("left", expression_to_json(&x.left)),
+            ("right", expression_to_json(&x.right)),
+        ]),
+        MonkeyExpression::IF(x) => node("IfExpression", &x.tok, vec![
+            ("condition", expression_to_json(&x.condition)),
+            ("consequence", block_to_json(&x.consequence)),
+            ("alternative", optional(x.alternative.as_ref(), block_to_json)),
+        ]),
+        MonkeyExpression::FUNCTIONLITERAL(x) => node("FunctionLiteral", &x.token, vec![
+            ("parameters", JsonValue::ARRAY(x.parameters.iter().map(identifier_to_json).collect())),
+            ("parameterTypes", JsonValue::ARRAY(x.parameter_types.iter().map(|x| optional(x.as_ref(), type_to_json)).collect())),
+            ("returnType", optional(x.return_type.as_ref(), type_to_json)),
+            ("body", block_to_json(&x.blockstatment)),
+        ]),
+        MonkeyExpression::CALL(x) => node("CallExpression", &x.token, vec![
+            ("function", expression_to_json(&x.function)),
+            ("arguments", JsonValue::ARRAY(x.arguments.iter().flatten().map(expression_to_json).collect())),
+        ]),
+        MonkeyExpression::MEMBER(x) => node("MemberExpression", &x.token, vec![
+            ("object", expression_to_json(&x.object)),
+            ("property", identifier_to_json(&x.property)),
+        ]),
+        MonkeyExpression::ARRAY(x) => node("ArrayLiteral", &x.token, vec![("elements", JsonValue::ARRAY(x.elements.iter().map(expression_to_json).collect()))]),
+        MonkeyExpression::HASH(x) => node("HashLiteral", &x.token, vec![("pairs", JsonValue::ARRAY(x.pairs.iter().map(|(key, value)| {
+            JsonValue::object(vec![("key", expression_to_json(key)), ("value", expression_to_json(value))])
+        }).collect()))]),
+        MonkeyExpression::INDEX(x) => node("IndexExpression", &x.token, vec![
+            ("left", expression_to_json(&x.left)),
+            ("index", expression_to_json(&x.index)),
+        ]),
+    }
+}
+
+pub fn from_json(value: &JsonValue) -> Result<Programm, String> {
+    expect_kind(value, &["Programm"])?;
+    let mut program = Programm { statements: list(value, "statements", statement_from_json)? };
+    resolver::resolve(&mut program);
+    Ok(program)
+}
+
+fn kind(value: &JsonValue) -> Result<&str, String> {
+    match value.get("kind").and_then(|x| x.as_str()) {
+        Some(x) => Ok(x),
+        None => Err(format!("expected a node with a kind, found {}", value)),
+    }
+}
+
+fn expect_kind<'a>(value: &'a JsonValue, kinds: &[&str]) -> Result<&'a str, String> {
+    let found = kind(value)?;
+    if !kinds.contains(&found) {
+        return Err(format!("expected {}, found {}", kinds.join(" or "), found))
+    }
+    Ok(found)
+}
+
+fn field<'a>(value: &'a JsonValue, key: &str) -> Result<&'a JsonValue, String> {
+    match value.get(key) {
+        Some(x) => Ok(x),
+        None => Err(format!("{} is missing \"{}\"", kind(value)?, key)),
+    }
+}
+
+fn string_field(value: &JsonValue, key: &str) -> Result<String, String> {
+    match field(value, key)?.as_str() {
+        Some(x) => Ok(String::from(x)),
+        None => Err(format!("\"{}\" of {} has to be a string", key, kind(value)?)),
+    }
+}
+
+// a null or missing child is None
+fn optional_field<T>(value: &JsonValue, key: &str, from_json: fn(&JsonValue) -> Result<T, String>) -> Result<Option<T>, String> {
+    match value.get(key) {
+        None | Some(JsonValue::NULL) => Ok(None),
+        Some(x) => Ok(Some(from_json(x)?)),
+    }
+}
+
+fn list<T>(value: &JsonValue, key: &str, from_json: fn(&JsonValue) -> Result<T, String>) -> Result<Vec<T>, String> {
+    match field(value, key)?.as_array() {
+        Some(elements) => elements.iter().map(from_json).collect(),
+        None => Err(format!("\"{}\" of {} has to be an array", key, kind(value)?)),
+    }
+}
+
+fn span_from_json(value: &JsonValue) -> Span {
+    let number = |key: &str| value.get("span").and_then(|x| x.get(key)).and_then(|x| x.as_i64()).unwrap_or(0).max(0) as usize;
+    Span::new(number("start"), number("end"), number("line"), number("column"))
+}
+
+// the token of a keyword, operator or delimiter, its type is whatever the lexer makes of the literal
+fn token(value: &JsonValue, literal: &str) -> Token {
+    let mut tok = Lexer::new(String::from(literal)).next_token();
+    tok.span = span_from_json(value);
+    tok
+}
+
+fn token_of_type(value: &JsonValue, tok_type: TokenType, literal: &str) -> Token {
+    let mut tok = Token::new(tok_type, literal);
+    tok.span = span_from_json(value);
+    tok
+}
+
+fn statement_from_json(value: &JsonValue) -> Result<Statement, String> {
+    let statement = match kind(value)? {
+        "LetStatement" => Statement::LET(let_from_json(value)?),
+        "ReturnStatement" => Statement::RETURN(ReturnStatement::new(token(value, "return"), expression_from_json(field(value, "value")?)?)),
+        // the statement starts where its expression starts
+        "ExpressionStatement" => {
+            let expression = expression_from_json(field(value, "expression")?)?;
+            let mut tok = expression_token(&expression);
+            tok.span = span_from_json(value);
+            Statement::EXPRESSION(ExpressionStatement::new(tok, expression))
+        },
+        "BlockStatement" => Statement::BLOCK(block_from_json(value)?),
+        "ImportStatement" => Statement::IMPORT(ImportStatement::new(
+            token(value, "import"),
+            string_field(value, "path")?,
+            optional_field(value, "alias", identifier_from_json)?,
+            list(value, "names", identifier_from_json)?,
+        )),
+        "ExportStatement" => {
+            let statement = field(value, "statement")?;
+            expect_kind(statement, &["LetStatement"])?;
+            Statement::EXPORT(ExportStatement::new(token(value, "export"), let_from_json(statement)?))
+        },
+        other => return Err(format!("{} is not a statement", other)),
+    };
+    Ok(statement)
+}
+
+fn let_from_json(value: &JsonValue) -> Result<LetStatement, String> {
+    let mut tok = token(value, "let");
+    tok.doc = optional_field(value, "doc", |x| match x.as_str() {
+        Some(doc) => Ok(String::from(doc)),
+        None => Err(String::from("\"doc\" of LetStatement has to be a string")),
+    })?;
+    let mut statement = LetStatement::new(tok, identifier_from_json(field(value, "name")?)?, expression_from_json(field(value, "value")?)?);
+    statement.annotation = optional_field(value, "annotation", type_from_json)?;
+    Ok(statement)
+}
+
+fn block_from_json(value: &JsonValue) -> Result<BlockStatement, String> {
+    expect_kind(value, &["BlockStatement"])?;
+    Ok(BlockStatement::new(token(value, "{"), list(value, "statements", statement_from_json)?))
+}
+
+fn identifier_from_json(value: &JsonValue) -> Result<Identifier, String> {
+    expect_kind(value, &["Identifier"])?;
+    let name = string_field(value, "name")?;
+    Ok(Identifier::new(token_of_type(value, TokenType::IDENT, &name), Symbol::intern(&name)))
+}
+
+fn type_from_json(value: &JsonValue) -> Result<TypeAnnotation, String> {
+    let annotation = match kind(value)? {
+        "TypeName" => {
+            let name = string_field(value, "name")?;
+            if !TypeAnnotation::NAMES.contains(&name.as_str()) {
+                return Err(format!("unknown type {}", name))
+            }
+            TypeAnnotation::NAME(Identifier::new(token_of_type(value, TokenType::IDENT, &name), Symbol::intern(&name)))
+        },
+        "ArrayType" => TypeAnnotation::ARRAY(token(value, "["), Box::new(type_from_json(field(value, "element")?)?)),
+        "HashType" => TypeAnnotation::HASH(
+            token(value, "{"),
+            Box::new(type_from_json(field(value, "key")?)?),
+            Box::new(type_from_json(field(value, "value")?)?),
+        ),
+        "FunctionType" => TypeAnnotation::FUNCTION(
+            token(value, "fn"),
+            list(value, "parameters", type_from_json)?,
+            Box::new(type_from_json(field(value, "result")?)?),
+        ),
+        other => return Err(format!("{} is not a type", other)),
+    };
+    Ok(annotation)
+}
+
+fn expression_from_json(value: &JsonValue) -> Result<MonkeyExpression, String> {
+    let expression = match kind(value)? {
+        "Identifier" => MonkeyExpression::IDENT(identifier_from_json(value)?),
+        "IntegerLiteral" => {
+            let number = match field(value, "value")?.as_i64() {
+                Some(x) => x,
+                None => return Err(String::from("\"value\" of IntegerLiteral has to be a number")),
+            };
+            let literal = match value.get("literal").and_then(|x| x.as_str()) {
+                Some(x) => String::from(x),
+                None => number.to_string(),
+            };
+            MonkeyExpression::INTEGERLITERAL(IntegerLiteral::new(token_of_type(value, TokenType::INT, &literal), number))
+        },
+        "StringLiteral" => {
+            let string = string_field(value, "value")?;
+            MonkeyExpression::STRINGLITERAL(StringLiteral::new(token_of_type(value, TokenType::STRING, &string), string))
+        },
+        "Boolean" => match field(value, "value")? {
+            JsonValue::BOOL(x) => MonkeyExpression::BOOLEAN(Boolean::new(token(value, &x.to_string()), *x)),
+            _ => return Err(String::from("\"value\" of Boolean has to be true or false")),
+        },
+        "PrefixExpression" => {
+            let operator = string_field(value, "operator")?;
+            if !["!", "-", "~"].contains(&operator.as_str()) {
+                return Err(format!("{} is not a prefix operator", operator))
+            }
+            let right = expression_from_json(field(value, "right")?)?;
+            MonkeyExpression::PREFIX(PrefixExpression::new(token(value, &operator), operator, right))
+        },
+        "InfixExpression" => {
+            let operator = string_field(value, "operator")?;
+            let tok = token(value, &operator);
+            if tok.literal != operator.as_str() || !is_infix_operator(&tok.tokentype) {
+                return Err(format!("{} is not an infix operator", operator))
+            }
+            let left = expression_from_json(field(value, "left")?)?;
+            let right = expression_from_json(field(value, "right")?)?;
+            MonkeyExpression::INFIX(InfixExpression::new(operator, tok, left, right))
+        },
+        "IfExpression" => MonkeyExpression::IF(IfExpression::new(
+            token(value, "if"),
+            Box::new(expression_from_json(field(value, "condition")?)?),
+            block_from_json(field(value, "consequence")?)?,
+            optional_field(value, "alternative", block_from_json)?,
+        )),
+        "FunctionLiteral" => {
+            let parameters = list(value, "parameters", identifier_from_json)?;
+            let mut function = FunctionLiteral::new(token(value, "fn"), parameters, block_from_json(field(value, "body")?)?);
+            if value.get("parameterTypes").is_some() {
+                function.parameter_types = list(value, "parameterTypes", |x| match x {
+                    JsonValue::NULL => Ok(None),
+                    x => Ok(Some(type_from_json(x)?)),
+                })?;
+            }
+            if function.parameter_types.len() != function.parameters.len() {
+                return Err(String::from("FunctionLiteral needs one entry in \"parameterTypes\" for every parameter"))
+            }
+            function.return_type = optional_field(value, "returnType", type_from_json)?;
+            MonkeyExpression::FUNCTIONLITERAL(Rc::new(function))
+        },
+        // the parser keeps the arguments of f() as None
+        "CallExpression" => {
+            let arguments = list(value, "arguments", expression_from_json)?;
+            let arguments = if arguments.is_empty() { None } else { Some(arguments) };
+            MonkeyExpression::CALL(CallExpression::new(token(value, "("), expression_from_json(field(value, "function")?)?, arguments))
+        },
+        "MemberExpression" => MonkeyExpression::MEMBER(MemberExpression::new(
+            token(value, "."),
+            expression_from_json(field(value, "object")?)?,
+            identifier_from_json(field(value, "property")?)?,
+        )),
+        "ArrayLiteral" => MonkeyExpression::ARRAY(ArrayLiteral::new(token(value, "["), list(value, "elements", expression_from_json)?)),
+        // a pair is not a node, it has no kind to name in the error
+        "HashLiteral" => MonkeyExpression::HASH(HashLiteral::new(token(value, "{"), list(value, "pairs", |x| match (x.get("key"), x.get("value")) {
+            (Some(key), Some(value)) => Ok((expression_from_json(key)?, expression_from_json(value)?)),
+            _ => Err(String::from("every pair of HashLiteral needs a \"key\" and a \"value\"")),
+        })?)),
+        "IndexExpression" => MonkeyExpression::INDEX(IndexExpression::new(
+            token(value, "["),
+            expression_from_json(field(value, "left")?)?,
+            expression_from_json(field(value, "index")?)?,
+        )),
+        other => return Err(format!("{} is not an expression", other)),
+    };
+    Ok(expression)
+}
+
+fn is_infix_operator(tok_type: &TokenType) -> bool {
+    matches!(tok_type,
+        TokenType::PLUS | TokenType::MINUS | TokenType::ASTERISK | TokenType::SLASH | TokenType::POWER
+        | TokenType::AMPERSAND | TokenType::PIPE | TokenType::SHIFTLEFT | TokenType::SHIFTRIGHT
+        | TokenType::LT | TokenType::LTEQ | TokenType::GT | TokenType::GTEQ | TokenType::EQ | TokenType::NOTEQ)
+}
+
+// the first token of the expression, infix expressions and calls start with their left side
+fn expression_token(expression: &MonkeyExpression) -> Token {
+    match expression {
+        MonkeyExpression::IDENT(x) => x.token.clone(),
+        MonkeyExpression::INTEGERLITERAL(x) => x.token.clone(),
+        MonkeyExpression::STRINGLITERAL(x) => x.token.clone(),
+        MonkeyExpression::BOOLEAN(x) => x.token.clone(),
+        MonkeyExpression::PREFIX(x) => x.token.clone(),
+        MonkeyExpression::INFIX(x) => expression_token(&x.left),
+        MonkeyExpression::IF(x) => x.tok.clone(),
+        MonkeyExpression::FUNCTIONLITERAL(x) => x.token.clone(),
+        MonkeyExpression::CALL(x) => expression_token(&x.function),
+        MonkeyExpression::MEMBER(x) => expression_token(&x.object),
+        MonkeyExpression::ARRAY(x) => x.token.clone(),
+        MonkeyExpression::HASH(x) => x.token.clone(),
+        MonkeyExpression::INDEX(x) => expression_token(&x.left),
+    }
+}
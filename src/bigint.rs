@@ -0,0 +1,528 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, BitAnd, BitOr, Mul, Neg, Not, Shl, Shr, Sub};
+
+// sign + magnitude, the magnitude is stored as base 2^32 limbs (least significant limb first).
+// the magnitude never has trailing zero limbs and zero is never negative,
+// so derived equality and hashing work on the value.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt { negative: false, magnitude: Vec::new() }
+    }
+
+    pub fn from_i64(val: i64) -> BigInt {
+        BigInt::from_i128(val as i128)
+    }
+
+    pub fn from_i128(val: i128) -> BigInt {
+        let negative = val < 0;
+        let mut abs = val.unsigned_abs();
+        let mut magnitude = Vec::new();
+        while abs > 0 {
+            magnitude.push(abs as u32);
+            abs >>= 32;
+        }
+        BigInt { negative, magnitude }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None
+        }
+        let mut abs: u64 = 0;
+        for limb in self.magnitude.iter().rev() {
+            abs = (abs << 32) | *limb as u64;
+        }
+        if !self.negative && abs <= i64::MAX as u64 {
+            Some(abs as i64)
+        } else if self.negative && abs <= i64::MIN.unsigned_abs() {
+            Some(0i64.wrapping_sub(abs as i64))
+        } else {
+            None
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    // the number of bits of the magnitude, 0 for zero
+    pub fn bits(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(x) => self.magnitude.len() as u64 * 32 - x.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        let negative = negative && !magnitude.is_empty();
+        BigInt { negative, magnitude }
+    }
+
+    // division truncates towards zero like i64 division does, so the remainder has the sign of self.
+    // returns None if the divisor is zero
+    pub fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &divisor.magnitude);
+        Some((
+            BigInt::from_parts(self.negative != divisor.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+
+    // bitwise operators treat the value as an infinitely sign extended two's complement number like i64 does
+    fn to_twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut limbs = self.magnitude.clone();
+        limbs.resize(len, 0);
+        if self.negative {
+            negate_limbs(&mut limbs);
+        }
+        limbs
+    }
+
+    fn from_twos_complement(mut limbs: Vec<u32>) -> BigInt {
+        let negative = limbs.last().is_some_and(|limb| limb >> 31 == 1);
+        if negative {
+            negate_limbs(&mut limbs);
+        }
+        BigInt::from_parts(negative, limbs)
+    }
+
+    fn bitwise(&self, other: &BigInt, op: fn(u32, u32) -> u32) -> BigInt {
+        let len = self.magnitude.len().max(other.magnitude.len()) + 1;
+        let limbs = self.to_twos_complement(len).iter()
+            .zip(other.to_twos_complement(len).iter())
+            .map(|(l, r)| op(*l, *r))
+            .collect();
+        BigInt::from_twos_complement(limbs)
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut result = BigInt::from_i64(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.magnitude, &other.magnitude))
+        }
+        match cmp_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(other.negative, sub_magnitude(&other.magnitude, &self.magnitude)),
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.magnitude, &other.magnitude)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(self.negative != other.negative, mul_magnitude(&self.magnitude, &other.magnitude))
+    }
+}
+
+impl Not for &BigInt {
+    type Output = BigInt;
+    fn not(self) -> BigInt {
+        &(-self) - &BigInt::from_i64(1)
+    }
+}
+
+impl BitAnd for &BigInt {
+    type Output = BigInt;
+    fn bitand(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |l, r| l & r)
+    }
+}
+
+impl BitOr for &BigInt {
+    type Output = BigInt;
+    fn bitor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |l, r| l | r)
+    }
+}
+
+impl Shl<u32> for &BigInt {
+    type Output = BigInt;
+    fn shl(self, bits: u32) -> BigInt {
+        let mut magnitude = vec![0u32; (bits / 32) as usize];
+        magnitude.extend(shl_bits(&self.magnitude, bits % 32));
+        BigInt::from_parts(self.negative, magnitude)
+    }
+}
+
+// arithmetic shift, rounds towards negative infinity like i64 does
+impl Shr<u32> for &BigInt {
+    type Output = BigInt;
+    fn shr(self, bits: u32) -> BigInt {
+        let limbs = (bits / 32) as usize;
+        let shift = bits % 32;
+        if limbs >= self.magnitude.len() {
+            return if self.negative { BigInt::from_i64(-1) } else { BigInt::zero() }
+        }
+        let mut magnitude = Vec::with_capacity(self.magnitude.len() - limbs);
+        for i in limbs..self.magnitude.len() {
+            let high = match self.magnitude.get(i + 1) {
+                Some(next) if shift > 0 => next << (32 - shift),
+                _ => 0,
+            };
+            magnitude.push((self.magnitude[i] >> shift) | high);
+        }
+        let result = BigInt::from_parts(self.negative, magnitude);
+        let lost_bits = self.magnitude[..limbs].iter().any(|limb| *limb != 0)
+            || self.magnitude[limbs] & ((1u64 << shift) - 1) as u32 != 0;
+        if self.negative && lost_bits {
+            &result - &BigInt::from_i64(1)
+        } else {
+            result
+        }
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0")
+        }
+        // split the number into base 10^9 chunks, least significant first
+        let mut chunks: Vec<u32> = Vec::new();
+        let mut rest = self.magnitude.clone();
+        while !rest.is_empty() {
+            let (quotient, remainder) = div_rem_small(&rest, 1_000_000_000);
+            chunks.push(remainder);
+            rest = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks[chunks.len() - 1])?;
+        for chunk in chunks.iter().rev().skip(1) {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+fn trim(mut magnitude: Vec<u32>) -> Vec<u32> {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    magnitude
+}
+
+fn negate_limbs(limbs: &mut [u32]) {
+    let mut carry: u64 = 1;
+    for limb in limbs.iter_mut() {
+        let sum = (!*limb) as u64 + carry;
+        *limb = sum as u32;
+        carry = sum >> 32;
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len())
+    }
+    for (x, y) in a.iter().rev().zip(b.iter().rev()) {
+        if x != y {
+            return x.cmp(y)
+        }
+    }
+    Ordering::Equal
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(long.len() + 1);
+    let mut carry: u64 = 0;
+    for (i, limb) in long.iter().enumerate() {
+        let sum = *limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+// expects a >= b
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow: i64 = 0;
+    for (i, limb) in a.iter().enumerate() {
+        let diff = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 + borrow;
+        result.push(diff as u32);
+        borrow = diff >> 32;
+    }
+    trim(result)
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new()
+    }
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry: u64 = 0;
+        for (j, y) in b.iter().enumerate() {
+            let product = *x as u64 * *y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    trim(result)
+}
+
+fn div_rem_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: u64 = 0;
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    (trim(quotient), remainder as u32)
+}
+
+fn shl_bits(a: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return a.to_vec()
+    }
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry: u32 = 0;
+    for limb in a {
+        result.push((limb << shift) | carry);
+        carry = limb >> (32 - shift);
+    }
+    result.push(carry);
+    result
+}
+
+// long division, algorithm D from Knuth's TAOCP vol. 2
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec())
+    }
+    if b.len() == 1 {
+        let (quotient, remainder) = div_rem_small(a, b[0]);
+        return (quotient, trim(vec![remainder]))
+    }
+
+    // normalize so the highest limb of the divisor has its top bit set
+    let shift = b[b.len() - 1].leading_zeros();
+    let divisor = trim(shl_bits(b, shift));
+    let mut dividend = shl_bits(a, shift);
+    if dividend.len() == a.len() {
+        dividend.push(0);
+    }
+    let n = divisor.len();
+    let m = dividend.len() - n - 1;
+    let mut quotient = vec![0u32; m + 1];
+
+    for j in (0..=m).rev() {
+        let numerator = ((dividend[j + n] as u64) << 32) | dividend[j + n - 1] as u64;
+        let mut qhat = numerator / divisor[n - 1] as u64;
+        let mut rhat = numerator % divisor[n - 1] as u64;
+        while qhat > u32::MAX as u64 || qhat * divisor[n - 2] as u64 > ((rhat << 32) | dividend[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += divisor[n - 1] as u64;
+            if rhat > u32::MAX as u64 {
+                break
+            }
+        }
+
+        // subtract qhat * divisor from the current window of the dividend
+        let mut borrow: i128 = 0;
+        let mut carry: u128 = 0;
+        for i in 0..n {
+            let product = qhat as u128 * divisor[i] as u128 + carry;
+            carry = product >> 32;
+            let diff = dividend[i + j] as i128 - (product & 0xffff_ffff) as i128 + borrow;
+            dividend[i + j] = diff as u32;
+            borrow = diff >> 32;
+        }
+        let diff = dividend[j + n] as i128 - carry as i128 + borrow;
+        dividend[j + n] = diff as u32;
+
+        // qhat was one too large, add the divisor back
+        if diff < 0 {
+            qhat -= 1;
+            let mut carry: u64 = 0;
+            for i in 0..n {
+                let sum = dividend[i + j] as u64 + divisor[i] as u64 + carry;
+                dividend[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            dividend[j + n] = dividend[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = qhat as u32;
+    }
+
+    // the remainder is the lower part of the dividend, shifted back
+    let mut remainder = vec![0u32; n];
+    for i in 0..n {
+        remainder[i] = if shift == 0 {
+            dividend[i]
+        } else {
+            (dividend[i] >> shift) | (dividend[i + 1] << (32 - shift))
+        };
+    }
+    (trim(quotient), trim(remainder))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // values around the limb boundaries and the ends of i64
+    const VALUES: [i64; 16] = [
+        0, 1, -1, 2, -2, 7, -7, u32::MAX as i64, -(u32::MAX as i64), 1 << 32, -(1 << 32),
+        (1 << 32) + 1, i64::MAX, i64::MIN, i64::MIN + 1, 0x1234_5678_9abc_def0,
+    ];
+
+    fn big(value: i128) -> BigInt {
+        BigInt::from_i128(value)
+    }
+
+    #[test]
+    fn does_arithmetic_like_i128() {
+        for a in VALUES {
+            for b in VALUES {
+                let (a, b) = (a as i128, b as i128);
+                assert_eq!(&big(a) + &big(b), big(a + b), "{} + {}", a, b);
+                assert_eq!(&big(a) - &big(b), big(a - b), "{} - {}", a, b);
+                assert_eq!(&big(a) * &big(b), big(a * b), "{} * {}", a, b);
+                assert_eq!(&big(a) & &big(b), big(a & b), "{} & {}", a, b);
+                assert_eq!(&big(a) | &big(b), big(a | b), "{} | {}", a, b);
+                assert_eq!(big(a).cmp(&big(b)), a.cmp(&b), "{} <=> {}", a, b);
+            }
+            assert_eq!(!&big(a as i128), big(!(a as i128)), "~{}", a);
+            assert_eq!(-&big(a as i128), big(-(a as i128)), "-{}", a);
+        }
+    }
+
+    #[test]
+    fn truncates_division_towards_zero() {
+        let cases = [(7, 2, 3, 1), (-7, 2, -3, -1), (7, -2, -3, 1), (-7, -2, 3, -1), (6, -3, -2, 0), (1, 7, 0, 1), (-1, 7, 0, -1)];
+        for (a, b, quotient, remainder) in cases {
+            assert_eq!(big(a).div_rem(&big(b)), Some((big(quotient), big(remainder))), "{} / {}", a, b);
+        }
+        for a in VALUES {
+            for b in VALUES.iter().filter(|x| **x != 0) {
+                let (a, b) = (a as i128, *b as i128);
+                assert_eq!(big(a).div_rem(&big(b)), Some((big(a / b), big(a % b))), "{} / {}", a, b);
+            }
+            assert_eq!(big(a as i128).div_rem(&BigInt::zero()), None);
+        }
+        // the one i64 division that overflows
+        let (quotient, remainder) = BigInt::from_i64(i64::MIN).div_rem(&BigInt::from_i64(-1)).unwrap();
+        assert_eq!((quotient.to_string(), remainder), (String::from("9223372036854775808"), BigInt::zero()));
+        assert_eq!(quotient.to_i64(), None);
+    }
+
+    #[test]
+    fn converts_the_ends_of_i64() {
+        assert_eq!(BigInt::from_i64(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(BigInt::from_i64(i64::MAX).to_i64(), Some(i64::MAX));
+        assert_eq!(big(i64::MAX as i128 + 1).to_i64(), None);
+        assert_eq!(big(i64::MIN as i128 - 1).to_i64(), None);
+        assert_eq!((-&BigInt::from_i64(i64::MIN)).to_i64(), None);
+        assert_eq!(BigInt::from_i64(i64::MIN).to_string(), "-9223372036854775808");
+        assert_eq!(BigInt::from_i64(i64::MIN).bits(), 64);
+        assert_eq!((BigInt::zero().bits(), big(1).bits(), big(-1).bits()), (0, 1, 1));
+    }
+
+    #[test]
+    fn never_has_a_negative_zero() {
+        let zero = &big(-5) + &big(5);
+        assert_eq!(zero, BigInt::zero());
+        assert!(!zero.is_negative());
+        assert_eq!(-&BigInt::zero(), BigInt::zero());
+        assert_eq!(&big(-5) * &BigInt::zero(), BigInt::zero());
+        assert_eq!(big(-1).div_rem(&big(2)), Some((BigInt::zero(), big(-1))));
+        assert_eq!(BigInt::zero().to_string(), "0");
+    }
+
+    #[test]
+    fn shifts_like_i128() {
+        for a in VALUES {
+            for bits in [0, 1, 5, 31, 32, 33, 63] {
+                let a = a as i128;
+                assert_eq!(&big(a) << bits, big(a << bits), "{} << {}", a, bits);
+                assert_eq!(&big(a) >> bits, big(a >> bits), "{} >> {}", a, bits);
+            }
+            // shifting right past every bit leaves the sign
+            assert_eq!(&big(a as i128) >> 200, big(if a < 0 { -1 } else { 0 }), "{} >> 200", a);
+        }
+        assert_eq!(&big(1) << 100, big(2).pow(100));
+        assert_eq!(&(&big(-3) << 100) >> 100, big(-3));
+        assert_eq!(&(&big(-3) << 100) >> 101, big(-2));
+    }
+
+    #[test]
+    fn prints_in_decimal() {
+        assert_eq!(big(2).pow(100).to_string(), "1267650600228229401496703205376");
+        assert_eq!(big(-10).pow(19).to_string(), "-10000000000000000000");
+        assert_eq!(big(1_000_000_000).pow(2).to_string(), "1000000000000000000");
+        assert_eq!(big(3).pow(0).to_string(), "1");
+    }
+}
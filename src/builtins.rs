@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+
+use crate::evaluator::{apply_function, EvalError};
+use crate::object::*;
+use crate::symbol::Symbol;
+
+// the functions that can not be written in monkey itself. everything else of the standard library
+// lives in stdlib/prelude.mk and is built on top of these
+const BUILTINS: [(&str, BuiltinFunction); 20] = [
+    ("len", builtin_len),
+    ("first", builtin_first),
+    ("last", builtin_last),
+    ("rest", builtin_rest),
+    ("push", builtin_push),
+    ("slice", builtin_slice),
+    ("range", builtin_range),
+    ("reduce", builtin_reduce),
+    ("keys", builtin_keys),
+    ("values", builtin_values),
+    ("split", builtin_split),
+    ("join", builtin_join),
+    ("reverse", builtin_reverse),
+    ("upper", builtin_upper),
+    ("lower", builtin_lower),
+    ("trim", builtin_trim),
+    ("str", builtin_str),
+    ("int", builtin_int),
+    ("type_of", builtin_type_of),
+    ("puts", builtin_puts),
+];
+
+pub fn builtin_names() -> Vec<&'static str> {
+    BUILTINS.iter().map(|(name, _)| *name).collect()
+}
+
+// all builtins by name, ready to be used as the store of an environment
+pub fn builtin_store() -> HashMap<Symbol, MonkeyObject> {
+    let mut store: HashMap<Symbol, MonkeyObject> = HashMap::new();
+    for (name, func) in BUILTINS {
+        store.insert(Symbol::intern(name), MonkeyObject::BUILTIN(Builtin::new(name, func)));
+    }
+    store
+}
+
+fn check_arg_count(name: &str, args: &[MonkeyObject], expected: usize) -> Result<(), EvalError> {
+    if args.len() != expected {
+        return Err(EvalError::new(format!("wrong number of arguments for {}: expected {}, got {}", name, expected, args.len())))
+    }
+    Ok(())
+}
+
+fn wrong_type(name: &str, arg: &MonkeyObject) -> EvalError {
+    EvalError::new(format!("argument to {} not supported, got {}", name, arg.type_name()))
+}
+
+fn to_i64(name: &str, arg: &MonkeyObject) -> Result<i64, EvalError> {
+    match arg {
+        MonkeyObject::INTEGER(x) => match x.as_i64() {
+            Some(x) => Ok(x),
+            None => Err(EvalError::new(format!("integer argument to {} is too large", name))),
+        },
+        other => Err(wrong_type(name, other)),
+    }
+}
+
+fn integer(value: usize) -> MonkeyObject {
+    MonkeyObject::INTEGER(Integer::new(value as i64))
+}
+
+fn string(value: String) -> MonkeyObject {
+    MonkeyObject::STRING(Str::new(value))
+}
+
+fn null() -> MonkeyObject {
+    MonkeyObject::NULL(Null {})
+}
+
+// the length of strings is counted in chars, not bytes
+fn builtin_len(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("len", &args, 1)?;
+    match &args[0] {
+        MonkeyObject::STRING(x) => Ok(integer(x.value.chars().count())),
+        MonkeyObject::ARRAY(x) => Ok(integer(x.elements.len())),
+        MonkeyObject::HASH(x) => Ok(integer(x.pairs.len())),
+        other => Err(wrong_type("len", other)),
+    }
+}
+
+fn builtin_first(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("first", &args, 1)?;
+    match &args[0] {
+        MonkeyObject::ARRAY(x) => Ok(x.elements.first().cloned().unwrap_or_else(null)),
+        other => Err(wrong_type("first", other)),
+    }
+}
+
+fn builtin_last(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("last", &args, 1)?;
+    match &args[0] {
+        MonkeyObject::ARRAY(x) => Ok(x.elements.last().cloned().unwrap_or_else(null)),
+        other => Err(wrong_type("last", other)),
+    }
+}
+
+fn builtin_rest(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("rest", &args, 1)?;
+    match &args[0] {
+        MonkeyObject::ARRAY(x) if x.elements.is_empty() => Ok(null()),
+        MonkeyObject::ARRAY(x) => Ok(MonkeyObject::ARRAY(Array::new(x.elements[1..].to_vec()))),
+        other => Err(wrong_type("rest", other)),
+    }
+}
+
+// returns a new array, the array passed in is not changed
+fn builtin_push(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("push", &args, 2)?;
+    let mut args = args.into_iter();
+    match (args.next(), args.next()) {
+        (Some(MonkeyObject::ARRAY(mut x)), Some(value)) => {
+            x.elements_mut().push(value);
+            Ok(MonkeyObject::ARRAY(x))
+        },
+        (Some(other), _) => Err(wrong_type("push", &other)),
+        _ => Err(EvalError::from("wrong number of arguments for push")),
+    }
+}
+
+// slice(x, start, end) for arrays and strings. start and end are clamped to the length of x
+fn builtin_slice(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("slice", &args, 3)?;
+    let start = to_i64("slice", &args[1])?.max(0) as usize;
+    let end = to_i64("slice", &args[2])?.max(0) as usize;
+    match &args[0] {
+        MonkeyObject::ARRAY(x) => {
+            let end = end.min(x.elements.len());
+            let start = start.min(end);
+            Ok(MonkeyObject::ARRAY(Array::new(x.elements[start..end].to_vec())))
+        },
+        MonkeyObject::STRING(x) => {
+            let count = end.saturating_sub(start);
+            Ok(string(x.value.chars().skip(start).take(count).collect()))
+        },
+        other => Err(wrong_type("slice", other)),
+    }
+}
+
+// every element of a range is built right away, so something like range(1 << 62) is an error instead of using up all memory
+const MAX_RANGE_LENGTH: i128 = 10_000_000;
+
+// range(end), range(start, end) or range(start, end, step). end is not included
+fn builtin_range(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    let (start, end, step) = match args.len() {
+        1 => (0, to_i64("range", &args[0])?, 1),
+        2 => (to_i64("range", &args[0])?, to_i64("range", &args[1])?, 1),
+        3 => (to_i64("range", &args[0])?, to_i64("range", &args[1])?, to_i64("range", &args[2])?),
+        other => return Err(EvalError::new(format!("wrong number of arguments for range: expected 1 to 3, got {}", other))),
+    };
+    if step == 0 {
+        return Err(EvalError::from("step of range can not be 0"))
+    }
+    let length = (end as i128 - start as i128 + step as i128 - step.signum() as i128) / step as i128;
+    if length > MAX_RANGE_LENGTH {
+        return Err(EvalError::new(format!("range can have at most {} elements", MAX_RANGE_LENGTH)))
+    }
+    let mut elements: Vec<MonkeyObject> = Vec::new();
+    let mut current = start;
+    while (step > 0 && current < end) || (step < 0 && current > end) {
+        elements.push(MonkeyObject::INTEGER(Integer::new(current)));
+        current = match current.checked_add(step) {
+            Some(x) => x,
+            None => break,
+        };
+    }
+    Ok(MonkeyObject::ARRAY(Array::new(elements)))
+}
+
+// reduce(array, initial, fn(accumulator, element) {...})
+fn builtin_reduce(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("reduce", &args, 3)?;
+    let mut args = args.into_iter();
+    let (array, mut accumulator, func) = match (args.next(), args.next(), args.next()) {
+        (Some(MonkeyObject::ARRAY(x)), Some(initial), Some(func)) => (x, initial, func),
+        (Some(other), _, _) => return Err(wrong_type("reduce", &other)),
+        _ => return Err(EvalError::from("wrong number of arguments for reduce")),
+    };
+    for element in array.into_elements() {
+        accumulator = apply_function(func.clone(), vec![accumulator, element])?;
+    }
+    Ok(accumulator)
+}
+
+fn builtin_keys(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("keys", &args, 1)?;
+    match &args[0] {
+        MonkeyObject::HASH(x) => Ok(MonkeyObject::ARRAY(Array::new(x.pairs.keys().map(|key| key.to_object()).collect()))),
+        other => Err(wrong_type("keys", other)),
+    }
+}
+
+fn builtin_values(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("values", &args, 1)?;
+    match &args[0] {
+        MonkeyObject::HASH(x) => Ok(MonkeyObject::ARRAY(Array::new(x.pairs.values().cloned().collect()))),
+        other => Err(wrong_type("values", other)),
+    }
+}
+
+// split(s, "") splits a string into its chars
+fn builtin_split(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("split", &args, 2)?;
+    match (&args[0], &args[1]) {
+        (MonkeyObject::STRING(x), MonkeyObject::STRING(separator)) => {
+            let parts: Vec<MonkeyObject> = if separator.value.is_empty() {
+                x.value.chars().map(|ch| string(ch.to_string())).collect()
+            } else {
+                x.value.split(&*separator.value).map(|part| string(String::from(part))).collect()
+            };
+            Ok(MonkeyObject::ARRAY(Array::new(parts)))
+        },
+        (MonkeyObject::STRING(_), other) | (other, _) => Err(wrong_type("split", other)),
+    }
+}
+
+// joins the elements of an array into a string with the separator between them, the elements are converted like str does.
+// written in monkey every element would copy the string built so far
+fn builtin_join(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("join", &args, 2)?;
+    match (&args[0], &args[1]) {
+        (MonkeyObject::ARRAY(x), MonkeyObject::STRING(separator)) => {
+            let parts: Vec<String> = x.elements.iter().map(|element| element.inspect()).collect();
+            Ok(string(parts.join(&separator.value)))
+        },
+        (MonkeyObject::ARRAY(_), other) | (other, _) => Err(wrong_type("join", other)),
+    }
+}
+
+// the elements of an array in reverse order, strings are reversed char by char
+fn builtin_reverse(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("reverse", &args, 1)?;
+    let mut args = args.into_iter();
+    match args.next() {
+        Some(MonkeyObject::ARRAY(mut x)) => {
+            x.elements_mut().reverse();
+            Ok(MonkeyObject::ARRAY(x))
+        },
+        Some(MonkeyObject::STRING(x)) => Ok(string(x.value.chars().rev().collect())),
+        Some(other) => Err(wrong_type("reverse", &other)),
+        None => Err(EvalError::from("wrong number of arguments for reverse")),
+    }
+}
+
+fn builtin_upper(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("upper", &args, 1)?;
+    match &args[0] {
+        MonkeyObject::STRING(x) => Ok(string(x.value.to_uppercase())),
+        other => Err(wrong_type("upper", other)),
+    }
+}
+
+fn builtin_lower(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("lower", &args, 1)?;
+    match &args[0] {
+        MonkeyObject::STRING(x) => Ok(string(x.value.to_lowercase())),
+        other => Err(wrong_type("lower", other)),
+    }
+}
+
+fn builtin_trim(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("trim", &args, 1)?;
+    match &args[0] {
+        MonkeyObject::STRING(x) => Ok(string(String::from(x.value.trim()))),
+        other => Err(wrong_type("trim", other)),
+    }
+}
+
+fn builtin_str(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("str", &args, 1)?;
+    Ok(string(args[0].inspect()))
+}
+
+fn builtin_int(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("int", &args, 1)?;
+    match &args[0] {
+        MonkeyObject::INTEGER(x) => Ok(MonkeyObject::INTEGER(x.clone())),
+        MonkeyObject::STRING(x) => match x.value.trim().parse::<i64>() {
+            Ok(value) => Ok(MonkeyObject::INTEGER(Integer::new(value))),
+            Err(_) => Err(EvalError::new(format!("could not convert \"{}\" to an integer", x.value))),
+        },
+        other => Err(wrong_type("int", other)),
+    }
+}
+
+fn builtin_type_of(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    check_arg_count("type_of", &args, 1)?;
+    Ok(string(String::from(args[0].type_name())))
+}
+
+fn builtin_puts(args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    for arg in args {
+        println!("{}", arg.inspect());
+    }
+    Ok(null())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(value: i64) -> MonkeyObject {
+        MonkeyObject::INTEGER(Integer::new(value))
+    }
+
+    fn str(value: &str) -> MonkeyObject {
+        string(String::from(value))
+    }
+
+    fn array(elements: Vec<MonkeyObject>) -> MonkeyObject {
+        MonkeyObject::ARRAY(Array::new(elements))
+    }
+
+    // the result as inspect shows it or the message of the error
+    fn call(func: BuiltinFunction, args: Vec<MonkeyObject>) -> String {
+        match func(args) {
+            Ok(x) => x.inspect(),
+            Err(err) => err.message,
+        }
+    }
+
+    #[test]
+    fn checks_the_number_of_arguments() {
+        assert_eq!(call(builtin_len, vec![]), "wrong number of arguments for len: expected 1, got 0");
+        assert_eq!(call(builtin_push, vec![array(vec![])]), "wrong number of arguments for push: expected 2, got 1");
+        assert_eq!(call(builtin_range, vec![]), "wrong number of arguments for range: expected 1 to 3, got 0");
+        assert_eq!(call(builtin_reverse, vec![str("a"), str("b")]), "wrong number of arguments for reverse: expected 1, got 2");
+        assert_eq!(call(builtin_len, vec![int(1)]), "argument to len not supported, got INTEGER");
+        assert_eq!(call(builtin_join, vec![array(vec![]), int(1)]), "argument to join not supported, got INTEGER");
+    }
+
+    #[test]
+    fn push_and_reverse_do_not_change_shared_arrays() {
+        let xs = array(vec![int(1), int(2)]);
+        assert_eq!(call(builtin_push, vec![xs.clone(), int(3)]), "[1, 2, 3]");
+        assert_eq!(call(builtin_reverse, vec![xs.clone()]), "[2, 1]");
+        assert_eq!(xs.inspect(), "[1, 2]");
+        assert_eq!(call(builtin_reverse, vec![str("añb")]), "bña");
+    }
+
+    #[test]
+    fn counts_strings_in_chars() {
+        assert_eq!(call(builtin_len, vec![str("añb")]), "3");
+        assert_eq!(call(builtin_slice, vec![str("añbc"), int(1), int(3)]), "ñb");
+    }
+
+    #[test]
+    fn clamps_slices() {
+        let xs = array(vec![int(1), int(2), int(3)]);
+        assert_eq!(call(builtin_slice, vec![xs.clone(), int(-5), int(2)]), "[1, 2]");
+        assert_eq!(call(builtin_slice, vec![xs.clone(), int(1), int(100)]), "[2, 3]");
+        assert_eq!(call(builtin_slice, vec![xs.clone(), int(2), int(1)]), "[]");
+        assert_eq!(call(builtin_slice, vec![str("abc"), int(2), int(1)]), "");
+    }
+
+    #[test]
+    fn builds_ranges() {
+        assert_eq!(call(builtin_range, vec![int(3)]), "[0, 1, 2]");
+        assert_eq!(call(builtin_range, vec![int(-3)]), "[]");
+        assert_eq!(call(builtin_range, vec![int(1), int(8), int(3)]), "[1, 4, 7]");
+        assert_eq!(call(builtin_range, vec![int(5), int(0), int(-2)]), "[5, 3, 1]");
+        assert_eq!(call(builtin_range, vec![int(0), int(1), int(0)]), "step of range can not be 0");
+        // the step does not overflow at the end of i64
+        assert_eq!(call(builtin_range, vec![int(i64::MAX - 1), int(i64::MAX), int(i64::MAX)]), format!("[{}]", i64::MAX - 1));
+        let limit = MAX_RANGE_LENGTH as i64;
+        assert_eq!(call(builtin_len, vec![builtin_range(vec![int(limit)]).unwrap()]), limit.to_string());
+        assert_eq!(call(builtin_range, vec![int(limit + 1)]), "range can have at most 10000000 elements");
+        assert_eq!(call(builtin_range, vec![int(i64::MIN), int(i64::MAX)]), "range can have at most 10000000 elements");
+    }
+
+    #[test]
+    fn splits_and_joins_strings() {
+        assert_eq!(call(builtin_split, vec![str("a,b,,c"), str(",")]), "[a, b, , c]");
+        assert_eq!(call(builtin_split, vec![str("añ"), str("")]), "[a, ñ]");
+        assert_eq!(call(builtin_join, vec![array(vec![int(1), str("a"), array(vec![int(2)])]), str("-")]), "1-a-[2]");
+        assert_eq!(call(builtin_join, vec![array(vec![]), str("-")]), "");
+    }
+
+    #[test]
+    fn converts_strings_to_integers() {
+        assert_eq!(call(builtin_int, vec![str(" -42 ")]), "-42");
+        assert_eq!(call(builtin_int, vec![str(&i64::MIN.to_string())]), i64::MIN.to_string());
+        assert_eq!(call(builtin_int, vec![str("4x")]), "could not convert \"4x\" to an integer");
+        assert_eq!(call(builtin_int, vec![str("9223372036854775808")]), "could not convert \"9223372036854775808\" to an integer");
+    }
+}
@@ -0,0 +1,349 @@
+use std::fmt;
+
+use crate::ast::Programm;
+use crate::lexer::{Lexer, TokenSource};
+use crate::parser::{ParseError, Parser};
+use crate::token::{Token, TokenType};
+
+// the concrete syntax tree keeps every byte of the source: each token knows its exact text and
+// the whitespace and comments in front of it. writing the tree out gives back the source unchanged,
+// so a tool can replace one node and leave the rest of the file as it was
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum SyntaxKind {
+    PROGRAM,
+
+    //statements
+    LET,
+    RETURN,
+    EXPRESSION,
+    IMPORT,
+    EXPORT,
+    BLOCK,
+
+    //expressions
+    NAME,
+    LITERAL,
+    PREFIX,
+    INFIX,
+    PAREN,
+    IF,
+    FUNCTION,
+    CALL,
+    MEMBER,
+    INDEX,
+    ARRAY,
+    HASH,
+
+    //types
+    TYPE,
+}
+impl SyntaxKind {
+    // the kind of expression that starts with the token
+    pub fn prefix(tok_type: &TokenType) -> SyntaxKind {
+        match tok_type {
+            TokenType::IDENT => SyntaxKind::NAME,
+            TokenType::BANG | TokenType::MINUS | TokenType::TILDE => SyntaxKind::PREFIX,
+            TokenType::LPAREN => SyntaxKind::PAREN,
+            TokenType::IF => SyntaxKind::IF,
+            TokenType::FUNCTION => SyntaxKind::FUNCTION,
+            TokenType::LBRACKET => SyntaxKind::ARRAY,
+            TokenType::LBRACE => SyntaxKind::HASH,
+            _ => SyntaxKind::LITERAL,
+        }
+    }
+    // the kind of expression the token continues the expression in front of it with
+    pub fn infix(tok_type: &TokenType) -> SyntaxKind {
+        match tok_type {
+            TokenType::LPAREN => SyntaxKind::CALL,
+            TokenType::DOT => SyntaxKind::MEMBER,
+            TokenType::LBRACKET => SyntaxKind::INDEX,
+            _ => SyntaxKind::INFIX,
+        }
+    }
+}
+
+// what the parser records while parsing with record_syntax
+#[derive(Debug, PartialEq, Clone)]
+pub enum SyntaxEvent {
+    START(SyntaxKind),
+    TOKEN(Token),
+    FINISH,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TriviaKind {
+    WHITESPACE,
+    LINECOMMENT,
+    DOCCOMMENT,
+    BLOCKCOMMENT,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+impl Trivia {
+    pub fn new(kind: TriviaKind, text: String) -> Trivia {
+        Trivia { kind, text }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxToken {
+    pub token: Token,
+    pub text: String,
+    pub leading_trivia: Vec<Trivia>,
+}
+impl SyntaxToken {
+    pub fn new(token: Token, text: String, leading_trivia: Vec<Trivia>) -> SyntaxToken {
+        SyntaxToken { token, text, leading_trivia }
+    }
+    // the "///" lines in front of the token, the same way the lexer collects them
+    pub fn doc(&self) -> Option<String> {
+        let lines: Vec<&str> = self.leading_trivia.iter()
+            .filter(|x| x.kind == TriviaKind::DOCCOMMENT)
+            .map(|x| {
+                let doc = &x.text[3..];
+                doc.strip_prefix(' ').unwrap_or(doc).trim_end_matches('\r')
+            })
+            .collect();
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SyntaxElement {
+    NODE(SyntaxNode),
+    TOKEN(SyntaxToken),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxNode {
+    pub kind: SyntaxKind,
+    pub children: Vec<SyntaxElement>,
+}
+impl SyntaxNode {
+    pub fn new(kind: SyntaxKind, children: Vec<SyntaxElement>) -> SyntaxNode {
+        SyntaxNode { kind, children }
+    }
+
+    // the child nodes, for the PROGRAM node these are the top level statements
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|x| match x {
+            SyntaxElement::NODE(node) => Some(node),
+            SyntaxElement::TOKEN(_) => None,
+        })
+    }
+
+    // all tokens of the node in source order
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens: Vec<&SyntaxToken> = Vec::new();
+        collect_tokens(self, &mut tokens);
+        tokens
+    }
+
+    pub fn first_token_mut(&mut self) -> Option<&mut SyntaxToken> {
+        for child in &mut self.children {
+            let first = match child {
+                SyntaxElement::TOKEN(x) => Some(x),
+                SyntaxElement::NODE(x) => x.first_token_mut(),
+            };
+            if first.is_some() {
+                return first
+            }
+        }
+        None
+    }
+
+    // the name bound by a let or export statement
+    pub fn binding_name(&self) -> Option<&str> {
+        match self.kind {
+            SyntaxKind::LET => self.children.iter().find_map(|x| match x {
+                SyntaxElement::TOKEN(tok) if tok.token.tokentype == TokenType::IDENT => Some(tok.text.as_str()),
+                _ => None,
+            }),
+            SyntaxKind::EXPORT => self.nodes().find(|x| x.kind == SyntaxKind::LET).and_then(|x| x.binding_name()),
+            _ => None,
+        }
+    }
+
+    // the position in children of the statement that binds name
+    pub fn find_binding(&self, name: &str) -> Option<usize> {
+        self.children.iter().position(|x| match x {
+            SyntaxElement::NODE(node) => node.binding_name() == Some(name),
+            SyntaxElement::TOKEN(_) => false,
+        })
+    }
+
+    // replaces a child node. the whitespace and comments in front of the old node stay where they are,
+    // so only the text of the node itself changes
+    pub fn replace_child(&mut self, index: usize, mut replacement: SyntaxNode) {
+        let old = match &mut self.children[index] {
+            SyntaxElement::NODE(x) => x.first_token_mut().map(|tok| std::mem::take(&mut tok.leading_trivia)),
+            SyntaxElement::TOKEN(x) => Some(std::mem::take(&mut x.leading_trivia)),
+        };
+        if let (Some(trivia), Some(first)) = (old, replacement.first_token_mut()) {
+            first.leading_trivia = trivia;
+        }
+        self.children[index] = SyntaxElement::NODE(replacement);
+    }
+
+    // turns the node into the ast the parser would have produced for its text.
+    // a node that is not a whole program becomes a program with just that node in it
+    pub fn lower(&self) -> Result<Programm, ParseError> {
+        let mut tokens: Vec<Token> = self.tokens().into_iter().map(|x| {
+            let mut tok = x.token.clone();
+            tok.doc = x.doc();
+            tok
+        }).collect();
+        if tokens.last().map(|x| &x.tokentype) != Some(&TokenType::EOF) {
+            tokens.push(Token::new(TokenType::EOF, ""));
+        }
+        let mut source = TokenList { tokens: tokens.into_iter() };
+        Parser::new(&mut source).parse_programm()
+    }
+}
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for tok in self.tokens() {
+            for trivia in &tok.leading_trivia {
+                write!(f, "{}", trivia.text)?;
+            }
+            write!(f, "{}", tok.text)?;
+        }
+        Ok(())
+    }
+}
+
+fn collect_tokens<'a>(node: &'a SyntaxNode, tokens: &mut Vec<&'a SyntaxToken>) {
+    for child in &node.children {
+        match child {
+            SyntaxElement::TOKEN(x) => tokens.push(x),
+            SyntaxElement::NODE(x) => collect_tokens(x, tokens),
+        }
+    }
+}
+
+struct TokenList {
+    tokens: std::vec::IntoIter<Token>,
+}
+impl TokenSource for TokenList {
+    fn next_token(&mut self) -> Token {
+        match self.tokens.next() {
+            Some(x) => x,
+            None => Token::new(TokenType::EOF, ""),
+        }
+    }
+}
+
+// parses source into a PROGRAM node
+pub fn parse(source: &str) -> Result<SyntaxNode, ParseError> {
+    let mut lexer = Lexer::new(String::from(source));
+    let mut parser = Parser::new(&mut lexer);
+    parser.record_syntax();
+    parser.parse_programm()?;
+    let events = parser.take_syntax_events();
+    Ok(build_tree(source, events))
+}
+
+// parses source that holds exactly one statement, for example to pass it to replace_child
+pub fn parse_statement(source: &str) -> Result<SyntaxNode, ParseError> {
+    let program = parse(source)?;
+    let mut statements: Vec<SyntaxNode> = program.nodes().cloned().collect();
+    match statements.len() {
+        1 => Ok(statements.remove(0)),
+        _ => Err(ParseError::new(format!("expected exactly one statement, found {}", statements.len()), Token::new(TokenType::EOF, ""))),
+    }
+}
+
+fn build_tree(source: &str, events: Vec<SyntaxEvent>) -> SyntaxNode {
+    let mut stack: Vec<SyntaxNode> = Vec::new();
+    let mut root = SyntaxNode::new(SyntaxKind::PROGRAM, Vec::new());
+    let mut previous_end = 0;
+    for event in events {
+        match event {
+            SyntaxEvent::START(kind) => stack.push(SyntaxNode::new(kind, Vec::new())),
+            SyntaxEvent::TOKEN(tok) => {
+                let trivia = split_trivia(&source[previous_end..tok.span.start]);
+                let text = String::from(&source[tok.span.start..tok.span.end]);
+                previous_end = tok.span.end;
+                if let Some(node) = stack.last_mut() {
+                    node.children.push(SyntaxElement::TOKEN(SyntaxToken::new(tok, text, trivia)));
+                }
+            },
+            SyntaxEvent::FINISH => {
+                let node = match stack.pop() {
+                    Some(x) => x,
+                    None => continue,
+                };
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(SyntaxElement::NODE(node)),
+                    None => root = node,
+                }
+            },
+        }
+    }
+    root
+}
+
+// splits the text between two tokens into whitespace, "//" and "///" line comments and "/* */" block comments
+pub fn split_trivia(text: &str) -> Vec<Trivia> {
+    let mut trivia: Vec<Trivia> = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let length = if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if rest.starts_with("/*") {
+            block_comment_length(rest)
+        } else {
+            match rest.find(|ch: char| !ch.is_whitespace()) {
+                Some(0) => rest.chars().next().map_or(1, |ch| ch.len_utf8()),
+                Some(x) => x,
+                None => rest.len(),
+            }
+        };
+        let piece = &rest[..length];
+        let kind = if piece.starts_with("///") && !piece.starts_with("////") {
+            TriviaKind::DOCCOMMENT
+        } else if piece.starts_with("//") {
+            TriviaKind::LINECOMMENT
+        } else if piece.starts_with("/*") {
+            TriviaKind::BLOCKCOMMENT
+        } else {
+            TriviaKind::WHITESPACE
+        };
+        trivia.push(Trivia::new(kind, String::from(piece)));
+        rest = &rest[length..];
+    }
+    trivia
+}
+
+// block comments nest, the comment ends at the "*/" that closes the first "/*"
+fn block_comment_length(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        match (bytes[i], bytes[i + 1]) {
+            (b'/', b'*') => {
+                depth += 1;
+                i += 2;
+            },
+            (b'*', b'/') => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i
+                }
+            },
+            _ => i += 1,
+        }
+    }
+    text.len()
+}
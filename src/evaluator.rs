@@ -1,75 +1,142 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::rc::Rc;
 use std::cell::RefCell;
 
 use crate::ast::*;
+use crate::gc;
+use crate::module;
 use crate::object::*;
 use crate::token::*;
 
-pub fn eval(program: Vec<Statement>, env: Rc<RefCell<Environment>>) -> Vec<MonkeyObject> {
+#[derive(Debug, PartialEq, Clone)]
+pub struct EvalError {
+    pub message: String,
+}
+impl EvalError {
+    pub fn new(message: String) -> EvalError {
+        EvalError { message }
+    }
+}
+impl From<&str> for EvalError {
+    fn from(message: &str) -> EvalError {
+        EvalError::new(String::from(message))
+    }
+}
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+pub fn eval(program: &[Statement], env: Rc<RefCell<Environment>>) -> Result<Vec<MonkeyObject>, EvalError> {
     let mut object: MonkeyObject;
     let mut results: Vec<MonkeyObject> = Vec::new();
     for node in program {
         object = match node {
-            Statement::LET(stmt) => eval_let_statement(stmt, Rc::clone(&env)),
-            Statement::RETURN(stmt) => eval_return_statement(stmt, Rc::clone(&env)),
-            Statement::EXPRESSION(stmt) => eval_expr_statement(stmt, Rc::clone(&env)),
-            Statement::BLOCK(stmt) => hanlde_block_result(eval(stmt.statements, Rc::clone(&env)))
+            Statement::LET(stmt) => eval_let_statement(stmt, Rc::clone(&env))?,
+            Statement::RETURN(stmt) => eval_return_statement(stmt, Rc::clone(&env))?,
+            Statement::EXPRESSION(stmt) => eval_expr_statement(stmt, Rc::clone(&env))?,
+            Statement::BLOCK(stmt) => hanlde_block_result(eval(&stmt.statements, Rc::clone(&env))?),
+            Statement::IMPORT(stmt) => eval_import_statement(stmt, Rc::clone(&env))?,
+            Statement::EXPORT(stmt) => eval_export_statement(stmt, Rc::clone(&env))?,
         };
         match object {
-            MonkeyObject::RETURN(x) => return vec![MonkeyObject::RETURN(x)],
+            MonkeyObject::RETURN(x) => return Ok(vec![MonkeyObject::RETURN(x)]),
             _ => {results.push(object);}
         }
     }
-    results
+    Ok(results)
 
 }
-fn eval_let_statement(node: LetStatement, env: Rc<RefCell<Environment>>) -> MonkeyObject {
-    let value = eval_expr(node.value, Rc::clone(&env)).unwrap();
-    env.borrow_mut().set(node.name.value, value);
-    MonkeyObject::NULL(Null {  })
+fn eval_let_statement(node: &LetStatement, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    let value = eval_expr(&node.value, Rc::clone(&env))?;
+    bind(&node.name, value, &env);
+    Ok(MonkeyObject::NULL(Null {  }))
 }
-fn eval_return_statement(node: ReturnStatement, env: Rc<RefCell<Environment>>) -> MonkeyObject {
-    MonkeyObject::RETURN(ReturnValue::new(Box::new(eval_expr(node.return_value, Rc::clone(&env)).unwrap())))
+// a name declared inside of a function goes into its slot, see resolver.rs
+fn bind(name: &Identifier, value: MonkeyObject, env: &Rc<RefCell<Environment>>) {
+    match name.resolved {
+        Some(x) => env.borrow_mut().set_slot(x.slot, value),
+        None => env.borrow_mut().set(name.value, value),
+    }
+}
+fn eval_return_statement(node: &ReturnStatement, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    Ok(MonkeyObject::RETURN(ReturnValue::new(Box::new(eval_expr(&node.return_value, Rc::clone(&env))?))))
 }
-fn hanlde_block_result(block_result: Vec<MonkeyObject>) -> MonkeyObject {
-    match block_result.get(0) {
-        Some(MonkeyObject::RETURN(x)) => MonkeyObject::RETURN(x.clone()),
-        Some(_) => MonkeyObject::BLOCK(Block{statements: block_result}),
-        None =>  panic!("Error: no result")
+// a block evaluates to the value of its last statement, a return inside of it is passed on
+fn hanlde_block_result(mut block_result: Vec<MonkeyObject>) -> MonkeyObject {
+    match block_result.pop() {
+        Some(x) => x,
+        None => MonkeyObject::NULL(Null {}),
     }
 }
-fn eval_expr_statement(node: ExpressionStatement, env: Rc<RefCell<Environment>>) -> MonkeyObject {
-    eval_expr(node.expression, env).unwrap()
+fn eval_expr_statement(node: &ExpressionStatement, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    eval_expr(&node.expression, env)
+}
+
+fn eval_import_statement(node: &ImportStatement, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    let context = match env.borrow().module_context() {
+        Some(x) => x,
+        None => return Err(EvalError::from("imports are not available in this environment")),
+    };
+    let imported = module::load_module(&context.loader, &node.path, context.path.as_deref())?;
+    match &node.alias {
+        Some(alias) => bind(alias, MonkeyObject::MODULE(imported), &env),
+        None => {
+            for name in &node.names {
+                let value = match imported.get(name.value) {
+                    Some(x) => x,
+                    None => return Err(EvalError::new(format!("module \"{}\" does not export {}", node.path, name.value))),
+                };
+                bind(name, value, &env);
+            }
+        },
+    }
+    Ok(MonkeyObject::NULL(Null {}))
 }
 
-fn eval_expr(expr: MonkeyExpression, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, &'static str> {
+fn eval_export_statement(node: &ExportStatement, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    let name = node.statement.name.value;
+    let result = eval_let_statement(&node.statement, Rc::clone(&env))?;
+    env.borrow_mut().export(name);
+    Ok(result)
+}
+
+pub fn eval_expr(expr: &MonkeyExpression, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
     match expr {
         MonkeyExpression::INTEGERLITERAL(x) => Ok(eval_integer_literal(x)),
+        MonkeyExpression::STRINGLITERAL(x) => Ok(MonkeyObject::STRING(Str::new(x.value.clone()))),
         MonkeyExpression::BOOLEAN(x) => Ok(eval_bool(x)),
-        MonkeyExpression::PREFIX(x) => eval_prefix_expr(&x.operator, *x.right, Rc::clone(&env)),
-        MonkeyExpression::INFIX(x) => eval_infix_expr(x.operator.as_str(), *x.left, *x.right, Rc::clone(&env)),
+        MonkeyExpression::PREFIX(x) => eval_prefix_expr(&x.operator, &x.right, Rc::clone(&env)),
+        MonkeyExpression::INFIX(x) => eval_infix_expr(x.operator.as_str(), &x.left, &x.right, Rc::clone(&env)),
         MonkeyExpression::IF(x) => eval_if_expr(x, Rc::clone(&env)),
         MonkeyExpression::IDENT(x) => eval_ident(x, Rc::clone(&env)),
         MonkeyExpression::FUNCTIONLITERAL(x) => eval_functionliteral(x, Rc::clone(&env)),
-        MonkeyExpression::CALL(x) => eval_function_call(*x.function, x.arguments, Rc::clone(&env)),
-        _ => panic!("Unknown Expression {:#?}", expr),
+        MonkeyExpression::CALL(x) => eval_function_call(&x.function, x.arguments.as_deref(), Rc::clone(&env)),
+        MonkeyExpression::MEMBER(x) => eval_member_expr(x, Rc::clone(&env)),
+        MonkeyExpression::ARRAY(x) => Ok(MonkeyObject::ARRAY(Array::new(eval_expr_list(Some(&x.elements), Rc::clone(&env))?))),
+        MonkeyExpression::HASH(x) => eval_hash_literal(x, Rc::clone(&env)),
+        MonkeyExpression::INDEX(x) => eval_index_expr(&x.left, &x.index, Rc::clone(&env)),
     }
 }
 
-fn eval_integer_literal(int_lit: IntegerLiteral) -> MonkeyObject {
+fn eval_integer_literal(int_lit: &IntegerLiteral) -> MonkeyObject {
     MonkeyObject::INTEGER(Integer::new(int_lit.value))
 }
 
-fn eval_bool(bool_lit: Boolean) -> MonkeyObject {
-    MonkeyObject::BOOLEAN(Bool::new(bool_lit.value)) 
+fn eval_bool(bool_lit: &Boolean) -> MonkeyObject {
+    MonkeyObject::BOOLEAN(Bool::new(bool_lit.value))
 }
 
-fn eval_prefix_expr(operator: &String, right_expr: MonkeyExpression, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, &'static str> {
-    let right = eval_expr(right_expr, Rc::clone(&env)).unwrap();
+fn eval_prefix_expr(operator: &String, right_expr: &MonkeyExpression, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    let right = eval_expr(right_expr, Rc::clone(&env))?;
     match operator.as_str() {
         "!" => Ok(eval_bang_operator_expr(right)),
         "-" => eval_minus_operator_expr(right),
-        _ => panic!("Illegal Prefix operator: {}", operator),
+        "~" => eval_tilde_operator_expr(right),
+        _ => Err(EvalError::new(format!("Illegal Prefix operator: {}", operator))),
     }
 }
 
@@ -80,109 +147,215 @@ fn eval_bang_operator_expr(right: MonkeyObject) -> MonkeyObject {
             false => MonkeyObject::BOOLEAN(Bool { value: true })
             }
         }
-        MonkeyObject::NULL(x) => MonkeyObject::BOOLEAN(Bool { value: true }),
+        MonkeyObject::NULL(_) => MonkeyObject::BOOLEAN(Bool { value: true }),
         _ => MonkeyObject::BOOLEAN(Bool { value: false })
     }
 }
-fn eval_minus_operator_expr(right: MonkeyObject) -> Result<MonkeyObject, &'static str> {
+fn eval_minus_operator_expr(right: MonkeyObject) -> Result<MonkeyObject, EvalError> {
     match right {
-        MonkeyObject::INTEGER(mut x) => {
-            x.value = -x.value; 
-            Ok(MonkeyObject::INTEGER(x))
-        },
-        _ => panic!("The minus operator can only be used for numeric values. you tried to use it on: {:#?}", right)
+        MonkeyObject::INTEGER(x) => Ok(MonkeyObject::INTEGER(-&x)),
+        _ => Err(EvalError::new(format!("The minus operator can only be used for numeric values. you tried to use it on: {}", right.type_name())))
     }
-    
+
 }
 
-fn eval_infix_expr(operator: &str, left_expr: MonkeyExpression, right_expr: MonkeyExpression, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, &'static str> {
-    let left = eval_expr(left_expr, Rc::clone(&env)).unwrap();
-    let right = eval_expr(right_expr, Rc::clone(&env)).unwrap();
+fn eval_tilde_operator_expr(right: MonkeyObject) -> Result<MonkeyObject, EvalError> {
+    match right {
+        MonkeyObject::INTEGER(x) => Ok(MonkeyObject::INTEGER(!&x)),
+        _ => Err(EvalError::new(format!("The ~ operator can only be used for numeric values. you tried to use it on: {}", right.type_name())))
+    }
+}
+
+fn eval_infix_expr(operator: &str, left_expr: &MonkeyExpression, right_expr: &MonkeyExpression, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    let left = eval_expr(left_expr, Rc::clone(&env))?;
+    let right = eval_expr(right_expr, Rc::clone(&env))?;
     match (left, right) {
         (MonkeyObject::INTEGER(l), MonkeyObject::INTEGER(r)) => eval_integer_infix_expr(operator, &l, &r),
+        (MonkeyObject::STRING(l), MonkeyObject::STRING(r)) => eval_string_infix_expr(operator, &l, &r),
         (MonkeyObject::BOOLEAN(l), MonkeyObject::BOOLEAN(r)) => eval_bool_infix_expr(operator, &l, &r),
-        _ => panic!("illegal infix expr")
+        (MonkeyObject::ARRAY(l), MonkeyObject::ARRAY(r)) if operator == "+" => {
+            let mut elements = l.into_elements();
+            elements.extend(r.elements.iter().cloned());
+            Ok(MonkeyObject::ARRAY(Array::new(elements)))
+        },
+        // values of different types are never equal
+        (l, r) if operator == "==" => Ok(MonkeyObject::BOOLEAN(Bool { value: l == r })),
+        (l, r) if operator == "!=" => Ok(MonkeyObject::BOOLEAN(Bool { value: l != r })),
+        (l, r) => Err(EvalError::new(format!("illegal infix expr: {} {} {}", l.type_name(), operator, r.type_name())))
     }
 }
-fn eval_integer_infix_expr(operator: &str, left: &Integer, right: &Integer) -> Result<MonkeyObject, &'static str> {
+fn eval_integer_infix_expr(operator: &str, left: &Integer, right: &Integer) -> Result<MonkeyObject, EvalError> {
+    let result = match operator {
+        "+" => MonkeyObject::INTEGER(left + right),
+        "-" => MonkeyObject::INTEGER(left - right),
+        "*" => MonkeyObject::INTEGER(left * right),
+        "/" => MonkeyObject::INTEGER(left.checked_div(right)?),
+        "^" => MonkeyObject::INTEGER(left.pow(right)?),
+        "&" => MonkeyObject::INTEGER(left & right),
+        "|" => MonkeyObject::INTEGER(left | right),
+        "<<" => MonkeyObject::INTEGER(left.checked_shl(right)?),
+        ">>" => MonkeyObject::INTEGER(left.checked_shr(right)?),
+        "<" => MonkeyObject::BOOLEAN(Bool { value: left < right }),
+        ">" => MonkeyObject::BOOLEAN(Bool { value: left > right }),
+        "<=" => MonkeyObject::BOOLEAN(Bool { value: left <= right }),
+        ">=" => MonkeyObject::BOOLEAN(Bool { value: left >= right }),
+        "!=" => MonkeyObject::BOOLEAN(Bool { value: left != right }),
+        "==" => MonkeyObject::BOOLEAN(Bool { value: left == right }),
+        _ => return Err(EvalError::new(format!("Illegal operator in integer Infixexpression: {}", operator)))
+    };
+    Ok(result)
+}
+
+fn eval_string_infix_expr(operator: &str, left: &Str, right: &Str) -> Result<MonkeyObject, EvalError> {
     let result = match operator {
-        "+" => MonkeyObject::INTEGER(Integer { value: left.value + right.value }),
-        "-" => MonkeyObject::INTEGER(Integer { value: left.value - right.value }),
-        "*" => MonkeyObject::INTEGER(Integer { value: left.value * right.value }),
-        "/" => MonkeyObject::INTEGER(Integer { value: left.value / right.value }),
-        "^" => MonkeyObject::INTEGER(Integer { value: left.value.pow(right.value.try_into().unwrap()) }),
+        "+" => MonkeyObject::STRING(Str::new(format!("{}{}", left.value, right.value))),
+        "==" => MonkeyObject::BOOLEAN(Bool { value: left.value == right.value }),
+        "!=" => MonkeyObject::BOOLEAN(Bool { value: left.value != right.value }),
         "<" => MonkeyObject::BOOLEAN(Bool { value: left.value < right.value }),
         ">" => MonkeyObject::BOOLEAN(Bool { value: left.value > right.value }),
         "<=" => MonkeyObject::BOOLEAN(Bool { value: left.value <= right.value }),
         ">=" => MonkeyObject::BOOLEAN(Bool { value: left.value >= right.value }),
-        "!=" => MonkeyObject::BOOLEAN(Bool { value: left.value != right.value }),
-        "==" => MonkeyObject::BOOLEAN(Bool { value: left.value == right.value }),
-        _ => panic!("Illegal operator in integer Infixexpression: {}", operator)
+        _ => return Err(EvalError::new(format!("illegal operator for strings: {}", operator))),
     };
     Ok(result)
 }
 
-fn eval_bool_infix_expr(operator: &str, left: &Bool, right: &Bool) -> Result<MonkeyObject, &'static str> {
+fn eval_bool_infix_expr(operator: &str, left: &Bool, right: &Bool) -> Result<MonkeyObject, EvalError> {
     let result = match operator {
         "==" => MonkeyObject::BOOLEAN(Bool { value: left.value == right.value }),
         "!=" => MonkeyObject::BOOLEAN(Bool { value: left.value != right.value }),
-        _ => panic!("illegal operator for boolean comparison: {}", operator),
+        _ => return Err(EvalError::new(format!("illegal operator for boolean comparison: {}", operator))),
     };
     Ok(result)
 }
 
-fn eval_if_expr(if_expr: IfExpression, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, &'static str> {
-    let condition = eval_expr(*if_expr.condition, Rc::clone(&env)).unwrap();
+fn eval_if_expr(if_expr: &IfExpression, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    let condition = eval_expr(&if_expr.condition, Rc::clone(&env))?;
     let result = match condition {
         MonkeyObject::BOOLEAN(x) => x,
-        _ => panic!("Could not evaluate condition: result of condition was no Bool"),
+        _ => return Err(EvalError::from("Could not evaluate condition: result of condition was no Bool")),
     };
     if result.value {
-        Ok(hanlde_block_result(eval(if_expr.consequence.statements, Rc::clone(&env))))
+        Ok(hanlde_block_result(eval(&if_expr.consequence.statements, Rc::clone(&env))?))
     } else {
-        match if_expr.alternative {
-            Some(x) => Ok(hanlde_block_result(eval(x.statements, Rc::clone(&env)))),
-            None => Ok(MonkeyObject::BLOCK(Block { statements:Vec::new()})),
+        match &if_expr.alternative {
+            Some(x) => Ok(hanlde_block_result(eval(&x.statements, Rc::clone(&env))?)),
+            None => Ok(MonkeyObject::NULL(Null {})),
         }
     }
 }
 
-fn eval_ident(ident: Identifier, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, &'static str> {
-    match env.borrow().get(&ident.value) {
+fn eval_ident(ident: &Identifier, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    let value = match ident.resolved {
+        // the value is moved out of the slot, so an array passed on to push is not shared and push does not copy it
+        Some(x) if x.last_use => {
+            let taken = env.borrow_mut().take_slot(x.slot);
+            taken.or_else(|| env.borrow().get_resolved(x, ident.value))
+        },
+        Some(x) => env.borrow().get_resolved(x, ident.value),
+        None => env.borrow().get(ident.value),
+    };
+    match value {
         Some(x) => Ok(x),
-        None => panic!("Undefined Identifier used: {:#?}", ident.value),
+        None => Err(EvalError::new(format!("Undefined Identifier used: {}", ident.value))),
     }
 }
 
-fn eval_functionliteral(func_lit: FunctionLiteral, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, &'static str> {
-    Ok(MonkeyObject::FUNCTION(Function::new(func_lit.parameters, func_lit.blockstatment, Rc::clone(&env))))
+fn eval_functionliteral(func_lit: &Rc<FunctionLiteral>, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    Ok(MonkeyObject::FUNCTION(Function::new(Rc::clone(func_lit), Rc::clone(&env))))
 }
 
-fn eval_function_call(function: MonkeyExpression, arguments: Option<Vec<Box<MonkeyExpression>>>, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, &'static str> {
-   let func =  match function {
-        MonkeyExpression::IDENT(x) => eval_ident(x, Rc::clone(&env)).unwrap(),
-        MonkeyExpression::FUNCTIONLITERAL(x) => eval_functionliteral(x, Rc::clone(&env)).unwrap(),
-        _ => panic!("Could not evaluate function call. Expression: {:#?}", function),
-    };
-    let args = eval_expr_list(arguments, Rc::clone(&env));
-    Err("dummy")
+fn eval_function_call(function: &MonkeyExpression, arguments: Option<&[MonkeyExpression]>, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    let func = eval_expr(function, Rc::clone(&env))?;
+    let args = eval_expr_list(arguments, Rc::clone(&env))?;
+    apply_function(func, args)
 }
 
-fn eval_expr_list(expression_list: Option<Vec<Box<MonkeyExpression>>>, env: Rc<RefCell<Environment>>) -> Option<Vec<MonkeyObject>> {
-    if expression_list.is_none() {
-        return None
+// calls nested deeper than this are an error instead of overflowing the stack. a call needs a few kB of stack
+// in a debug build, main.rs gives the interpreter a stack that is big enough for all of them
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+thread_local! {
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+pub fn apply_function(func: MonkeyObject, args: Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError> {
+    let func = match func {
+        MonkeyObject::FUNCTION(x) => x,
+        MonkeyObject::BUILTIN(x) => return (x.func)(args),
+        other => return Err(EvalError::new(format!("not a function: {}", other.type_name()))),
+    };
+    let literal = &func.literal;
+    if literal.parameters.len() != args.len() {
+        return Err(EvalError::new(format!("wrong number of arguments: expected {}, got {}", literal.parameters.len(), args.len())))
+    }
+    // the parameters are the first locals
+    let mut slots: Vec<Option<MonkeyObject>> = vec![None; literal.locals.len()];
+    for (slot, arg) in slots.iter_mut().zip(args) {
+        *slot = Some(arg);
     }
+    let depth = CALL_DEPTH.get();
+    if depth >= MAX_CALL_DEPTH {
+        return Err(EvalError::new(format!("maximum call depth of {} exceeded", MAX_CALL_DEPTH)))
+    }
+    let function_env = gc::track(Environment::new_call_environment(Rc::clone(&literal.locals), slots, Rc::clone(&func.env)));
+    CALL_DEPTH.set(depth + 1);
+    let result = eval(&literal.blockstatment.statements, function_env);
+    CALL_DEPTH.set(depth);
+    match hanlde_block_result(result?) {
+        MonkeyObject::RETURN(x) => Ok(*x.value),
+        x => Ok(x),
+    }
+}
+
+fn eval_expr_list(expression_list: Option<&[MonkeyExpression]>, env: Rc<RefCell<Environment>>) -> Result<Vec<MonkeyObject>, EvalError> {
     let mut result: Vec<MonkeyObject> = Vec::new();
-    for expr in expression_list.unwrap() {
-        match eval_expr(*expr, Rc::clone(&env)) {
-            Ok(x) => result.push(x),
-            Err(x) => panic!("{}",x),
-        }
+    for expr in expression_list.unwrap_or_default() {
+        result.push(eval_expr(expr, Rc::clone(&env))?);
     };
+    Ok(result)
+}
 
-    if result.len() > 0 {
-        return Some(result)
+fn eval_member_expr(member: &MemberExpression, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    match eval_expr(&member.object, Rc::clone(&env))? {
+        MonkeyObject::MODULE(x) => match x.get(member.property.value) {
+            Some(value) => Ok(value),
+            None => Err(EvalError::new(format!("module \"{}\" does not export {}", x.path.display(), member.property.value))),
+        },
+        other => Err(EvalError::new(format!("can not access {} on {}", member.property.value, other.type_name()))),
     }
+}
 
-    None
-}
\ No newline at end of file
+fn eval_hash_literal(hash_lit: &HashLiteral, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    let mut pairs: BTreeMap<HashKey, MonkeyObject> = BTreeMap::new();
+    for (key_expr, value_expr) in &hash_lit.pairs {
+        let key = HashKey::from_object(&eval_expr(key_expr, Rc::clone(&env))?)?;
+        let value = eval_expr(value_expr, Rc::clone(&env))?;
+        pairs.insert(key, value);
+    }
+    Ok(MonkeyObject::HASH(Hash::new(pairs)))
+}
+
+// indexing outside of an array or with a missing key results in NULL
+fn eval_index_expr(left_expr: &MonkeyExpression, index_expr: &MonkeyExpression, env: Rc<RefCell<Environment>>) -> Result<MonkeyObject, EvalError> {
+    let left = eval_expr(left_expr, Rc::clone(&env))?;
+    let index = eval_expr(index_expr, Rc::clone(&env))?;
+    match (left, index) {
+        (MonkeyObject::ARRAY(array), MonkeyObject::INTEGER(i)) => {
+            let element = i.as_i64().and_then(|i| usize::try_from(i).ok()).and_then(|i| array.elements.get(i).cloned());
+            Ok(element.unwrap_or(MonkeyObject::NULL(Null {})))
+        },
+        (MonkeyObject::STRING(string), MonkeyObject::INTEGER(i)) => {
+            let ch = i.as_i64().and_then(|i| usize::try_from(i).ok()).and_then(|i| string.value.chars().nth(i));
+            match ch {
+                Some(x) => Ok(MonkeyObject::STRING(Str::new(x.to_string()))),
+                None => Ok(MonkeyObject::NULL(Null {})),
+            }
+        },
+        (MonkeyObject::HASH(hash), index) => {
+            let key = HashKey::from_object(&index)?;
+            Ok(hash.pairs.get(&key).cloned().unwrap_or(MonkeyObject::NULL(Null {})))
+        },
+        (left, index) => Err(EvalError::new(format!("index operator not supported: {}[{}]", left.type_name(), index.type_name()))),
+    }
+}
@@ -0,0 +1,300 @@
+use std::fs;
+use std::io::{self, Read};
+
+use crate::ast::{walk_block_statement_mut, walk_let_statement_mut, VisitorMut};
+use crate::cst::{self, SyntaxKind, SyntaxNode, Trivia, TriviaKind};
+use crate::lexer::Lexer;
+use crate::parser::ParseError;
+use crate::token::{BlockStatement, LetStatement, Token, TokenType};
+
+// the canonical source of a program: four spaces of indentation, one statement per line,
+// single spaces around infix operators and only the parentheses that are needed.
+// the layout comes from printing the ast, the comments come from the concrete syntax tree:
+// every comment is put back in front of the token it was in front of in the source
+pub fn format_source(source: &str) -> Result<String, ParseError> {
+    let mut lexer = Lexer::new(String::from(source));
+    if lexer.next_token().tokentype == TokenType::EOF {
+        let mut formatted = String::new();
+        write_comments(&mut formatted, &comments(&cst::split_trivia(source), true), "");
+        return Ok(formatted)
+    }
+    let tree = cst::parse(source)?;
+    let mut program = tree.lower()?;
+    // doc comments are written like all other comments, from the trivia
+    ClearDocs.visit_program_mut(&mut program);
+    // a comment in a block that is written on one line would take the rest of the line with it
+    let mut commented: Vec<usize> = Vec::new();
+    blocks_with_comments(&tree, &mut commented);
+    MultilineBlocks { starts: commented }.visit_program_mut(&mut program);
+    let canonical = program.to_string();
+
+    let formatted_tokens = tokens_with_spans(&canonical);
+    let mut gaps: Vec<Vec<Comment>> = vec![Vec::new(); formatted_tokens.len() + 1];
+    let mut pending: Vec<Comment> = Vec::new();
+    let mut next = 0;
+    for (i, tok) in tree.tokens().into_iter().enumerate() {
+        pending.extend(comments(&tok.leading_trivia, i == 0));
+        if tok.token.tokentype == TokenType::EOF {
+            continue
+        }
+        // the formatter drops parentheses and semicolons that are not needed and adds missing semicolons,
+        // every other token is in the output in the same order
+        while next < formatted_tokens.len() {
+            if formatted_tokens[next] == tok.token {
+                gaps[next].append(&mut pending);
+                next += 1;
+                break
+            }
+            if is_optional(&tok.token) || !is_optional(&formatted_tokens[next]) {
+                break
+            }
+            next += 1;
+        }
+    }
+    gaps[formatted_tokens.len()].append(&mut pending);
+
+    let mut formatted = String::new();
+    let mut end = 0;
+    for (i, tok) in formatted_tokens.iter().enumerate() {
+        let line = &canonical[canonical[..tok.span.start].rfind('\n').map_or(0, |x| x + 1)..];
+        let indent = " ".repeat(line.len() - line.trim_start_matches(' ').len() + 4);
+        let closing = tok.tokentype == TokenType::RBRACE;
+        write_gap(&mut formatted, &canonical[end..tok.span.start], &gaps[i], i == 0, closing, &indent);
+        formatted.push_str(&canonical[tok.span.start..tok.span.end]);
+        end = tok.span.end;
+    }
+    // the comments behind the last token, the canonical source ends right after it
+    write_gap(&mut formatted, "\n", &gaps[formatted_tokens.len()], false, false, "");
+    Ok(formatted)
+}
+
+struct ClearDocs;
+impl VisitorMut for ClearDocs {
+    fn visit_let_statement_mut(&mut self, statement: &mut LetStatement) {
+        statement.token.doc = None;
+        walk_let_statement_mut(self, statement);
+    }
+}
+
+// the start of the "{" of every block that has a comment between its braces
+fn blocks_with_comments(node: &SyntaxNode, starts: &mut Vec<usize>) {
+    if node.kind == SyntaxKind::BLOCK {
+        let tokens = node.tokens();
+        let has_comment = tokens.iter().skip(1).any(|tok| tok.leading_trivia.iter().any(|x| x.kind != TriviaKind::WHITESPACE));
+        if let (true, Some(first)) = (has_comment, tokens.first()) {
+            starts.push(first.token.span.start);
+        }
+    }
+    for child in node.nodes() {
+        blocks_with_comments(child, starts);
+    }
+}
+
+struct MultilineBlocks {
+    starts: Vec<usize>,
+}
+impl VisitorMut for MultilineBlocks {
+    fn visit_block_statement_mut(&mut self, block: &mut BlockStatement) {
+        if self.starts.contains(&block.token.span.start) {
+            block.multiline = true;
+        }
+        walk_block_statement_mut(self, block);
+    }
+}
+
+fn is_optional(tok: &Token) -> bool {
+    matches!(tok.tokentype, TokenType::LPAREN | TokenType::RPAREN | TokenType::SEMICOLON)
+}
+
+fn tokens_with_spans(source: &str) -> Vec<Token> {
+    let mut lexer = Lexer::new(String::from(source));
+    let mut tokens = Vec::new();
+    loop {
+        let tok = lexer.next_token();
+        if tok.tokentype == TokenType::EOF {
+            return tokens
+        }
+        tokens.push(tok);
+    }
+}
+
+#[derive(Clone)]
+struct Comment {
+    text: String,
+    // the comment starts a line, otherwise it follows the token in front of it
+    own_line: bool,
+    blank_line_before: bool,
+    blank_line_after: bool,
+}
+
+// the comments in the whitespace and comments in front of a token
+fn comments(trivia: &[Trivia], at_start: bool) -> Vec<Comment> {
+    let mut comments: Vec<Comment> = Vec::new();
+    let mut newlines = 0;
+    for piece in trivia {
+        if piece.kind == TriviaKind::WHITESPACE {
+            newlines += piece.text.matches('\n').count();
+            continue
+        }
+        if let Some(previous) = comments.last_mut() {
+            previous.blank_line_after = newlines >= 2;
+        }
+        let text = match piece.kind {
+            TriviaKind::BLOCKCOMMENT => piece.text.clone(),
+            _ => String::from(piece.text.trim_end()),
+        };
+        let own_line = newlines > 0 || (at_start && comments.is_empty());
+        comments.push(Comment { text, own_line, blank_line_before: newlines >= 2, blank_line_after: false });
+        newlines = 0;
+    }
+    if let Some(last) = comments.last_mut() {
+        last.blank_line_after = newlines >= 2;
+    }
+    comments
+}
+
+// writes the whitespace between two tokens of the canonical source with the comments that belong there.
+// comments on the line of the token before stay behind it, the others get lines of their own
+// in front of the next token, or one level deeper if the next token closes a block and they are still inside of it.
+// inside of a line every comment goes behind the token before
+fn write_gap(formatted: &mut String, between: &str, comments: &[Comment], at_start: bool, closing: bool, indent: &str) {
+    // a comment behind a line comment starts a line, even if it was in front of another token in the source
+    let mut comments = comments.to_vec();
+    for i in 1..comments.len() {
+        if comments[i - 1].text.starts_with("//") {
+            comments[i].own_line = true;
+        }
+    }
+    let line_start = match between.rfind('\n') {
+        Some(x) => Some(x + 1),
+        None if at_start => Some(0),
+        None => None,
+    };
+    let line_start = match line_start {
+        Some(x) => x,
+        None => {
+            // the line goes on behind the comments with four more spaces of indentation
+            let mut at_line_start = false;
+            for comment in &comments {
+                if comment.own_line && !at_line_start {
+                    formatted.push('\n');
+                    formatted.push_str(indent);
+                }
+                write_trailing(formatted, comment);
+                at_line_start = !comment.text.starts_with("/*");
+                if at_line_start {
+                    formatted.push('\n');
+                    formatted.push_str(indent);
+                }
+            }
+            if !at_line_start && !comments.is_empty() {
+                formatted.push(' ');
+            }
+            formatted.push_str(if comments.is_empty() { between } else { between.trim_start() });
+            return
+        },
+    };
+    let own_line_from = comments.iter().position(|x| x.own_line || at_start).unwrap_or(comments.len());
+    for comment in &comments[..own_line_from] {
+        write_trailing(formatted, comment);
+    }
+    formatted.push_str(&between[..line_start]);
+    let own_line = &comments[own_line_from..];
+    if own_line.first().is_some_and(|x| x.blank_line_before) && !at_start && !formatted.ends_with("\n\n") {
+        formatted.push('\n');
+    }
+    write_comments(formatted, own_line, if closing { indent } else { &between[line_start..] });
+    formatted.push_str(&between[line_start..]);
+}
+
+fn write_trailing(formatted: &mut String, comment: &Comment) {
+    if !formatted.ends_with([' ', '\n']) && !formatted.is_empty() {
+        formatted.push(' ');
+    }
+    formatted.push_str(&comment.text);
+}
+
+// comments on lines of their own, with the blank lines between them that the source had
+fn write_comments(formatted: &mut String, comments: &[Comment], indent: &str) {
+    for (i, comment) in comments.iter().enumerate() {
+        if i > 0 && comment.blank_line_before {
+            formatted.push('\n');
+        }
+        formatted.push_str(indent);
+        formatted.push_str(&comment.text);
+        formatted.push('\n');
+        if i == comments.len() - 1 && comment.blank_line_after {
+            formatted.push('\n');
+        }
+    }
+}
+
+// monkey fmt [--check] [files...]
+// without files the source is read from stdin and written to stdout.
+// returns the exit code: 0 if everything is formatted, 1 if --check found files that are not, 2 on errors
+pub fn run(args: impl Iterator<Item = String>) -> i32 {
+    let mut check = false;
+    let mut files: Vec<String> = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--check" => check = true,
+            _ => files.push(arg),
+        }
+    }
+
+    if files.is_empty() {
+        let mut source = String::new();
+        if let Err(err) = io::stdin().read_to_string(&mut source) {
+            eprintln!("Error: could not read stdin: {}", err);
+            return 2
+        }
+        return match format_checked(&source, "<stdin>") {
+            Some(formatted) if check => if formatted == source { 0 } else { 1 },
+            Some(formatted) => {
+                print!("{}", formatted);
+                0
+            },
+            None => 2,
+        }
+    }
+
+    let mut exit_code = 0;
+    for file in files {
+        let source = match fs::read_to_string(&file) {
+            Ok(x) => x,
+            Err(err) => {
+                eprintln!("Error: could not read {}: {}", file, err);
+                exit_code = 2;
+                continue
+            }
+        };
+        let formatted = match format_checked(&source, &file) {
+            Some(x) => x,
+            None => {
+                exit_code = 2;
+                continue
+            }
+        };
+        if formatted == source {
+            continue
+        }
+        if check {
+            println!("would reformat {}", file);
+            exit_code = exit_code.max(1);
+        } else if let Err(err) = fs::write(&file, formatted) {
+            eprintln!("Error: could not write {}: {}", file, err);
+            exit_code = 2;
+        }
+    }
+    exit_code
+}
+
+fn format_checked(source: &str, name: &str) -> Option<String> {
+    match format_source(source) {
+        Ok(x) => Some(x),
+        Err(err) => {
+            eprintln!("Error: could not parse {}: {}", name, err);
+            None
+        }
+    }
+}
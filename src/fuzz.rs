@@ -0,0 +1,62 @@
+use crate::arena::Ast;
+use crate::ast::Programm;
+use crate::builtins::builtin_store;
+use crate::evaluator::{eval, EvalError};
+use crate::gc;
+use crate::lexer::Lexer;
+use crate::object::{Environment, MonkeyObject};
+use crate::parser::Parser;
+use crate::token::TokenType;
+
+// the checks behind the fuzz targets in fuzz/ and the random inputs of tests/fuzz.rs.
+// any input is fine as long as lexer, parser and evaluator give back tokens, a result or an error.
+// a panic, an overflowed stack or a lexer that does not get to the end of the input is a bug
+
+// reads every token of the input
+pub fn lex(input: &str) {
+    let mut lexer = Lexer::new(String::from(input));
+    // every token but EOF takes at least one char
+    for _ in 0..=input.chars().count() {
+        if lexer.next_token().tokentype == TokenType::EOF {
+            return
+        }
+    }
+    panic!("the lexer did not reach the end of {:?}", input);
+}
+
+// parses the input into the tree and into the arena, both have to end up with the same program or the same error.
+// a program that parses has to parse again after it was printed
+pub fn parse(input: &str) -> Option<Programm> {
+    let tree = Parser::new(&mut Lexer::new(String::from(input))).parse_programm();
+    let arena = Ast::parse(&mut Lexer::new(String::from(input)));
+    let program = match (tree, arena) {
+        (Ok(tree), Ok(arena)) => {
+            assert_eq!(arena.to_program().statements, tree.statements, "the arena parsed {:?} differently", input);
+            tree
+        },
+        (Err(tree), Err(arena)) => {
+            assert_eq!(tree, arena, "the arena reported another error for {:?}", input);
+            return None
+        },
+        (tree, arena) => panic!("only one of tree and arena parsed {:?}: {:?} {:?}", input, tree.err(), arena.err()),
+    };
+    let printed = program.to_string();
+    if let Err(err) = Parser::new(&mut Lexer::new(printed.clone())).parse_programm() {
+        panic!("{:?} was printed as {:?}, which does not parse: {}", input, printed, err);
+    }
+    Some(program)
+}
+
+// parses and evaluates the input with the builtins. there is no module loader, so imports are an error
+// and nothing is read from the file system
+pub fn run(input: &str) -> Option<Result<Vec<MonkeyObject>, EvalError>> {
+    let program = parse(input)?;
+    let env = gc::track(Environment::from_store(builtin_store()));
+    let result = eval(&program.statements, env);
+    if let Ok(results) = &result {
+        for x in results {
+            x.inspect();
+        }
+    }
+    Some(result)
+}
@@ -0,0 +1,221 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::{Rc, Weak};
+
+use crate::object::{Environment, HashKey, MonkeyObject};
+
+// a function keeps the environment it was created in alive and usually is stored in that same environment,
+// so every recursive let makes an Rc cycle that reference counting alone never frees.
+// the environments created through track are registered here, collect finds the ones that can only be
+// reached through cycles and clears them, which drops the functions in them and with that the cycle.
+//
+// an environment is in use if something besides the tracked environments holds an Rc to it: a variable of the
+// evaluator, a module loader, a value returned to the host or an environment that is not tracked.
+// those are found by comparing the strong count of every environment with the references the tracked
+// environments hold to it, everything reachable from them is kept. arrays and hashes share their elements
+// through an Rc as well, so they are counted the same way, but they are never cleared themselves
+
+// collections run once this many environments were tracked, after a collection the threshold is twice the live ones
+const MIN_THRESHOLD: usize = 10_000;
+
+struct Heap {
+    environments: Vec<Weak<RefCell<Environment>>>,
+    threshold: usize,
+}
+
+thread_local! {
+    static HEAP: RefCell<Heap> = const { RefCell::new(Heap { environments: Vec::new(), threshold: MIN_THRESHOLD }) };
+}
+
+// an Rc the collector follows, see MonkeyObject::each_reference
+pub enum HeapRef<'a> {
+    ENVIRONMENT(&'a Rc<RefCell<Environment>>),
+    ARRAY(&'a Rc<Vec<MonkeyObject>>),
+    HASH(&'a Rc<BTreeMap<HashKey, MonkeyObject>>),
+}
+impl HeapRef<'_> {
+    fn as_ptr(&self) -> *const () {
+        match self {
+            HeapRef::ENVIRONMENT(x) => Rc::as_ptr(x) as *const (),
+            HeapRef::ARRAY(x) => Rc::as_ptr(x) as *const (),
+            HeapRef::HASH(x) => Rc::as_ptr(x) as *const (),
+        }
+    }
+}
+
+// the tracked environments and the arrays and hashes reachable from them, an edge for every Rc between them
+struct Graph {
+    index: HashMap<*const (), usize>,
+    strong: Vec<usize>,
+    internal: Vec<usize>,
+    edges: Vec<Vec<usize>>,
+}
+impl Graph {
+    fn add(&mut self, ptr: *const (), strong: usize) -> usize {
+        let node = self.strong.len();
+        self.index.insert(ptr, node);
+        self.strong.push(strong);
+        self.internal.push(0);
+        self.edges.push(Vec::new());
+        node
+    }
+    fn link(&mut self, from: usize, to: usize) {
+        self.internal[to] += 1;
+        self.edges[from].push(to);
+    }
+    // environments that are not tracked are left out, an array or hash is looked into the first time it is seen
+    fn visit(&mut self, from: usize, reference: HeapRef) {
+        let ptr = reference.as_ptr();
+        if let Some(to) = self.index.get(&ptr) {
+            self.link(from, *to);
+            return
+        }
+        let to = match reference {
+            HeapRef::ENVIRONMENT(_) => return,
+            HeapRef::ARRAY(x) => {
+                let to = self.add(ptr, Rc::strong_count(x));
+                for element in x.iter() {
+                    element.each_reference(&mut |x| self.visit(to, x));
+                }
+                to
+            },
+            HeapRef::HASH(x) => {
+                let to = self.add(ptr, Rc::strong_count(x));
+                for value in x.values() {
+                    value.each_reference(&mut |x| self.visit(to, x));
+                }
+                to
+            },
+        };
+        self.link(from, to);
+    }
+}
+
+// puts the environment behind an Rc and registers it with the collector, this might run a collection
+pub fn track(env: Environment) -> Rc<RefCell<Environment>> {
+    let env = Rc::new(RefCell::new(env));
+    let full = HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.environments.push(Rc::downgrade(&env));
+        heap.environments.len() >= heap.threshold
+    });
+    if full {
+        collect();
+    }
+    env
+}
+
+// the number of tracked environments that are still alive
+pub fn tracked() -> usize {
+    HEAP.with(|heap| heap.borrow().environments.iter().filter(|x| x.strong_count() > 0).count())
+}
+
+// frees the tracked environments that are only kept alive by cycles and returns how many there were
+pub fn collect() -> usize {
+    // holding all of them keeps them alive while the garbage is cleared, that one is not counted below
+    let environments: Vec<Rc<RefCell<Environment>>> = HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.environments.retain(|x| x.strong_count() > 0);
+        heap.environments.iter().filter_map(|x| x.upgrade()).collect()
+    });
+    let mut graph = Graph { index: HashMap::new(), strong: Vec::new(), internal: Vec::new(), edges: Vec::new() };
+    for env in environments.iter() {
+        graph.add(Rc::as_ptr(env) as *const (), Rc::strong_count(env) - 1);
+    }
+
+    // an environment that is borrowed mutably right now can not be looked into, so it and everything it holds is kept
+    let mut roots: Vec<usize> = Vec::new();
+    for (i, env) in environments.iter().enumerate() {
+        match env.try_borrow() {
+            Ok(env) => env.each_reference(&mut |x| graph.visit(i, x)),
+            Err(_) => roots.push(i),
+        }
+    }
+    for i in 0..graph.strong.len() {
+        if graph.strong[i] > graph.internal[i] {
+            roots.push(i);
+        }
+    }
+
+    let mut reachable: Vec<bool> = vec![false; graph.strong.len()];
+    while let Some(i) = roots.pop() {
+        if reachable[i] {
+            continue
+        }
+        reachable[i] = true;
+        roots.extend(graph.edges[i].iter().filter(|x| !reachable[**x]));
+    }
+
+    let mut freed = 0;
+    for (i, env) in environments.iter().enumerate() {
+        if reachable[i] {
+            continue
+        }
+        if let Ok(mut env) = env.try_borrow_mut() {
+            env.clear();
+            freed += 1;
+        }
+    }
+    let live = environments.len() - freed;
+    drop(environments);
+    HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.environments.retain(|x| x.strong_count() > 0);
+        heap.threshold = MIN_THRESHOLD.max(live * 2);
+    });
+    freed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::eval;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    // the heap is thread local and every test runs on a thread of its own, so the counts start at zero
+    fn run(source: &str, env: Rc<RefCell<Environment>>) -> Vec<MonkeyObject> {
+        let program = match Parser::new(&mut Lexer::new(String::from(source))).parse_programm() {
+            Ok(x) => x,
+            Err(err) => panic!("could not parse {:?}: {}", source, err),
+        };
+        eval(&program.statements, env).unwrap()
+    }
+
+    #[test]
+    fn frees_a_function_stored_in_its_own_environment() {
+        run("let f = fn() { f };", track(Environment::new()));
+        assert_eq!(tracked(), 1);
+        assert_eq!(collect(), 1);
+        assert_eq!(tracked(), 0);
+    }
+
+    #[test]
+    fn frees_cycles_through_arrays_and_hashes() {
+        run("let fs = [fn() { fs }]; let h = {\"f\": fn() { h }};", track(Environment::new()));
+        assert_eq!(collect(), 1);
+        assert_eq!(tracked(), 0);
+    }
+
+    #[test]
+    fn keeps_environments_the_host_holds() {
+        let env = track(Environment::new());
+        run("let f = fn() { f }; let g = fn(x) { fn() { x } }; let h = g(1);", env.clone());
+        assert_eq!(collect(), 0);
+        assert_eq!(run("h();", env.clone()).last().map(|x| x.inspect()), Some(String::from("1")));
+        drop(env);
+        assert!(collect() > 0);
+        assert_eq!(tracked(), 0);
+    }
+
+    #[test]
+    fn keeps_what_a_returned_value_holds() {
+        let result = run("let keep = fn() { let n = 5; fn() { n } }; keep();", track(Environment::new()));
+        collect();
+        // the closure holds the environment of the call to keep, that one holds the global one
+        assert_eq!(tracked(), 2);
+        drop(result);
+        collect();
+        assert_eq!(tracked(), 0);
+    }
+}
@@ -0,0 +1,373 @@
+use std::fmt;
+
+// just enough json for the machine readable output of the tools, keys keep the order they were added in
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonValue {
+    NULL,
+    BOOL(bool),
+    NUMBER(i64),
+    STRING(String),
+    ARRAY(Vec<JsonValue>),
+    OBJECT(Vec<(String, JsonValue)>),
+}
+impl JsonValue {
+    pub fn string(value: &str) -> JsonValue {
+        JsonValue::STRING(String::from(value))
+    }
+
+    pub fn object(pairs: Vec<(&str, JsonValue)>) -> JsonValue {
+        JsonValue::OBJECT(pairs.into_iter().map(|(key, value)| (String::from(key), value)).collect())
+    }
+
+    // the value of a key of an object, None for other values
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::OBJECT(pairs) => pairs.iter().find(|(x, _)| x == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::STRING(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::NUMBER(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::ARRAY(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonValue::NULL => write!(f, "null"),
+            JsonValue::BOOL(x) => write!(f, "{}", x),
+            JsonValue::NUMBER(x) => write!(f, "{}", x),
+            JsonValue::STRING(x) => write_json_string(f, x),
+            JsonValue::ARRAY(elements) => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            },
+            JsonValue::OBJECT(pairs) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_json_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+fn write_json_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for ch in value.chars() {
+        match ch {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => write!(f, "{}", ch)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+// parses a json document. numbers with a fraction or an exponent are cut to their integer part
+pub fn parse(source: &str) -> Result<JsonValue, String> {
+    let mut parser = JsonParser { source: source.as_bytes(), position: 0 };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.position < parser.source.len() {
+        return Err(format!("unexpected character at {}", parser.position))
+    }
+    Ok(value)
+}
+
+struct JsonParser<'a> {
+    source: &'a [u8],
+    position: usize,
+}
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.position < self.source.len() && self.source[self.position].is_ascii_whitespace() {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.position).copied()
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(format!("expected '{}' at {}", expected as char, self.position))
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.parse_word("null", JsonValue::NULL),
+            Some(b't') => self.parse_word("true", JsonValue::BOOL(true)),
+            Some(b'f') => self.parse_word("false", JsonValue::BOOL(false)),
+            Some(b'"') => Ok(JsonValue::STRING(self.parse_string()?)),
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_object(),
+            Some(b'-') | Some(b'0'..=b'9') => self.parse_number(),
+            Some(_) => Err(format!("unexpected character at {}", self.position)),
+            None => Err(String::from("unexpected end of input")),
+        }
+    }
+
+    fn parse_word(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, String> {
+        if !self.source[self.position..].starts_with(word.as_bytes()) {
+            return Err(format!("unexpected character at {}", self.position))
+        }
+        self.position += word.len();
+        Ok(value)
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, String> {
+        let start = self.position;
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.position += 1;
+        }
+        let integer = std::str::from_utf8(&self.source[start..self.position]).unwrap_or_default();
+        let value = match integer.parse::<i64>() {
+            Ok(x) => x,
+            Err(_) => return Err(format!("invalid number at {}", start)),
+        };
+        while matches!(self.peek(), Some(b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-')) {
+            self.position += 1;
+        }
+        Ok(JsonValue::NUMBER(value))
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut bytes: Vec<u8> = Vec::new();
+        loop {
+            let byte = match self.peek() {
+                Some(x) => x,
+                None => return Err(String::from("unterminated string")),
+            };
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = match self.peek() {
+                        Some(x) => x,
+                        None => return Err(String::from("unterminated string")),
+                    };
+                    self.position += 1;
+                    let ch = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.parse_unicode_escape()?,
+                        _ => return Err(format!("invalid escape at {}", self.position - 1)),
+                    };
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(ch.encode_utf8(&mut buffer).as_bytes());
+                },
+                _ => bytes.push(byte),
+            }
+        }
+        match String::from_utf8(bytes) {
+            Ok(x) => Ok(x),
+            Err(_) => Err(String::from("string is not valid utf-8")),
+        }
+    }
+
+    // \uXXXX, characters outside of the basic plane are written as two escapes
+    fn parse_unicode_escape(&mut self) -> Result<char, String> {
+        let first = self.parse_hex4()?;
+        if (0xD800..0xDC00).contains(&first) && self.source[self.position..].starts_with(b"\\u") {
+            let start = self.position;
+            self.position += 2;
+            let second = self.parse_hex4()?;
+            if (0xDC00..0xE000).contains(&second) {
+                return Ok(char::from_u32(0x10000 + ((first - 0xD800) << 10) + (second - 0xDC00)).unwrap_or('\u{FFFD}'))
+            }
+            // not the second half of a pair, the escape after it is read on its own
+            self.position = start;
+        }
+        // a surrogate on its own is not a char
+        Ok(char::from_u32(first).unwrap_or('\u{FFFD}'))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let digits = match self.source.get(self.position..self.position + 4) {
+            Some(x) => x,
+            None => return Err(String::from("unterminated unicode escape")),
+        };
+        let digits = std::str::from_utf8(digits).unwrap_or_default();
+        match u32::from_str_radix(digits, 16) {
+            Ok(x) => {
+                self.position += 4;
+                Ok(x)
+            },
+            Err(_) => Err(format!("invalid unicode escape at {}", self.position)),
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, String> {
+        self.expect(b'[')?;
+        let mut elements: Vec<JsonValue> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(JsonValue::ARRAY(elements))
+        }
+        loop {
+            elements.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(JsonValue::ARRAY(elements))
+                },
+                _ => return Err(format!("expected ',' or ']' at {}", self.position)),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue, String> {
+        self.expect(b'{')?;
+        let mut pairs: Vec<(String, JsonValue)> = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(JsonValue::OBJECT(pairs))
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.expect(b':')?;
+            pairs.push((key, self.parse_value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(JsonValue::OBJECT(pairs))
+                },
+                _ => return Err(format!("expected ',' or '}}' at {}", self.position)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_kind_of_value() {
+        let value = parse(" { \"a\" : [1, -2, true, false, null], \"b\": {}, \"c\": [], \"d\": \"x\" } ").unwrap();
+        assert_eq!(value, JsonValue::object(vec![
+            ("a", JsonValue::ARRAY(vec![JsonValue::NUMBER(1), JsonValue::NUMBER(-2), JsonValue::BOOL(true), JsonValue::BOOL(false), JsonValue::NULL])),
+            ("b", JsonValue::OBJECT(Vec::new())),
+            ("c", JsonValue::ARRAY(Vec::new())),
+            ("d", JsonValue::string("x")),
+        ]));
+        assert_eq!(value.get("d").and_then(|x| x.as_str()), Some("x"));
+        assert_eq!(value.get("a").and_then(|x| x.as_array()).map(|x| x.len()), Some(5));
+        assert_eq!(value.get("missing"), None);
+        assert_eq!(JsonValue::NUMBER(1).get("a"), None);
+    }
+
+    #[test]
+    fn cuts_numbers_to_their_integer_part() {
+        let cases = [("0", 0), ("-0", 0), ("1.9", 1), ("-1.9", -1), ("2e3", 2), ("12.5E-1", 12), ("9223372036854775807", i64::MAX), ("-9223372036854775808", i64::MIN)];
+        for (text, expected) in cases {
+            assert_eq!(parse(text), Ok(JsonValue::NUMBER(expected)), "{}", text);
+        }
+        assert_eq!(parse("9223372036854775808"), Err(String::from("invalid number at 0")));
+        assert_eq!(parse("-"), Err(String::from("invalid number at 0")));
+    }
+
+    #[test]
+    fn reads_escapes() {
+        let cases = [
+            (r#""\"\\\/\b\f\n\r\t""#, "\"\\/\u{8}\u{c}\n\r\t"),
+            (r#""\u00e9\u0041""#, "éA"),
+            (r#""\ud83d\ude00""#, "😀"),
+            (r#""größe""#, "größe"),
+            // a surrogate that is not part of a pair
+            (r#""\ud83d\u0041""#, "\u{FFFD}A"),
+            (r#""\ude00""#, "\u{FFFD}"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse(text), Ok(JsonValue::string(expected)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn reports_the_position_of_errors() {
+        let cases = [
+            ("", "unexpected end of input"),
+            ("[1 2]", "expected ',' or ']' at 3"),
+            ("{\"a\": 1,}", "expected '\"' at 8"),
+            ("{\"a\" 1}", "expected ':' at 5"),
+            ("{\"a\": 1 \"b\": 2}", "expected ',' or '}' at 8"),
+            ("tru", "unexpected character at 0"),
+            ("1 2", "unexpected character at 2"),
+            ("\"\\x\"", "invalid escape at 2"),
+            ("\"\\u12zz\"", "invalid unicode escape at 3"),
+            ("\"\\u12", "unterminated unicode escape"),
+            ("\"abc", "unterminated string"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse(text), Err(String::from(expected)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn prints_what_it_parses() {
+        let value = JsonValue::object(vec![
+            ("text", JsonValue::string("quote \" backslash \\ newline \n tab \t bell \u{7} größe")),
+            ("list", JsonValue::ARRAY(vec![JsonValue::NULL, JsonValue::BOOL(true), JsonValue::NUMBER(-5)])),
+            ("empty", JsonValue::OBJECT(Vec::new())),
+        ]);
+        let text = value.to_string();
+        assert_eq!(text, r#"{"text":"quote \" backslash \\ newline \n tab \t bell \u0007 größe","list":[null,true,-5],"empty":{}}"#);
+        assert_eq!(parse(&text), Ok(value));
+    }
+}
@@ -1,145 +1,287 @@
+use crate::token::Span;
 use crate::token::Token;
 use crate::token::TokenType;
 
+// where the parser gets its tokens from. once the tokens run out it keeps handing out EOF tokens
+pub trait TokenSource {
+    fn next_token(&mut self) -> Token;
+}
+
+// position and read_position are byte offsets into input and always lie on a char boundary,
+// so every char is decoded exactly once and slicing the input by them is always valid utf-8
 #[derive(Debug)]
 pub struct Lexer {
     pub input: String,
     pub position: usize,
     pub read_position: usize,
     pub ch: char,
+    line: usize,
+    column: usize,
+    doc_comment: Vec<String>,
 }
 impl Lexer {
     pub fn new(input: String) -> Lexer {
         let mut l = Lexer {
-            input: input,
+            input,
             position: 0,
             read_position: 0,
-            ch: '0',
+            ch: '\0',
+            line: 1,
+            column: 0,
+            doc_comment: Vec::new(),
         };
         l.read_char();
-        return l
-        
+        l
+
     }
 
     fn read_char(&mut self) {
-        if !self.reached_eof() {
-            match self.input.chars().nth(self.read_position) {
-                Some(ch) => self.ch = ch,
-                None => ()
-            }
+        if self.ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
         }
-        
         self.position = self.read_position;
-        self.read_position += 1;
+        match self.input[self.read_position..].chars().next() {
+            Some(ch) => {
+                self.ch = ch;
+                self.read_position += ch.len_utf8();
+            },
+            None => self.ch = '\0',
+        }
     }
 
     fn peek_char(&self) -> Option<char> {
-        if self.reached_eof() {
-            None
+        self.input[self.read_position..].chars().next()
+    }
+
+    // the literal is kept as written (prefix and "_" separators included), the parser converts it
+    fn read_number(&mut self) -> &str  {
+        let start_pos: usize = self.position;
+        if self.ch == '0' && matches!(self.peek_char(), Some('x' | 'X' | 'o' | 'O' | 'b' | 'B')) {
+            self.read_char();
+            self.read_char();
+            while self.ch.is_ascii_alphanumeric() || self.ch == '_' {
+                self.read_char()
+            }
         } else {
-            Some(self.input.chars().nth(self.read_position).unwrap())
+            while is_digit(self.ch) || self.ch == '_' {
+                self.read_char()
+            }
         }
+        &self.input[start_pos..self.position]
     }
 
-    fn read_number(&mut self) -> String  {
+    fn read_identifier(&mut self) -> &str  {
         let start_pos: usize = self.position;
-        while is_digit(self.ch) && !self.reached_eof() {
-            self.read_char()
+        while is_identifier_char(self.ch) {
+            self.read_char();
         }
-        let result = match self.input.get(start_pos..self.position) {
-            Some(x) => String::from(x),
-            None => panic!("tried to read a number and failed")
-        };
-        result
+        &self.input[start_pos..self.position]
     }
 
-    fn read_identifier(&mut self) -> String  {
+    // the literal of a STRING token is the unescaped content without the quotes.
+    // a string that is not closed before the end of the input becomes an ILLEGAL token
+    fn read_string(&mut self) -> Token {
         let start_pos: usize = self.position;
-        while is_letter(self.ch) && !self.reached_eof() {
+        let mut value = String::new();
+        self.read_char();
+        loop {
+            if self.reached_eof() {
+                return Token::new(TokenType::ILLEGAL, &self.input[start_pos..])
+            }
+            match self.ch {
+                '"' => break,
+                '\\' => {
+                    self.read_char();
+                    match self.ch {
+                        'n' => value.push('\n'),
+                        't' => value.push('\t'),
+                        'r' => value.push('\r'),
+                        '"' => value.push('"'),
+                        '\\' => value.push('\\'),
+                        _ if self.reached_eof() => continue,
+                        other => {
+                            value.push('\\');
+                            value.push(other);
+                        },
+                    }
+                },
+                other => value.push(other),
+            }
             self.read_char();
         }
-        let result = match self.input.get(start_pos..self.position) {
-            Some(x) => String::from(x),
-            None => panic!("tried to read a identifier and failed")
-        };
-        result
+        self.read_char();
+        Token::new(TokenType::STRING, &value)
+    }
+
+    fn char_token(&self, tokentype: TokenType) -> Token {
+        Token::new(tokentype, self.ch.encode_utf8(&mut [0; 4]))
     }
 
     fn make_two_char_token(&mut self, one_char_tokentype: TokenType, two_char_tokentype: TokenType, second_char: char) -> Token {
 
-        if self.peek_char().unwrap_or(' ') == second_char {
+        if self.peek_char() == Some(second_char) {
             let mut literal = String::from(self.ch);
             self.read_char();
             literal.push(self.ch);
-            return Token::new(two_char_tokentype, literal)
+            Token::new(two_char_tokentype, &literal)
         } else {
-            return Token::new(one_char_tokentype, self.ch.to_string())
+            self.char_token(one_char_tokentype)
         }
     }
 
-    fn eat_whitespaces(&mut self) {
-        while self.ch == ' ' || self.ch == '\t' || self.ch == '\n' || self.ch == '\r' {
-            self.read_char()
+    // skips whitespace and comments. "//" comments run to the end of the line, "/* */" comments can be nested.
+    // "///" doc comments are collected and handed to the next token.
+    // returns start, line and column of a block comment that is never closed
+    fn eat_whitespaces(&mut self) -> Option<(usize, usize, usize)> {
+        loop {
+            match (self.ch, self.peek_char()) {
+                (' ' | '\t' | '\n' | '\r', _) => self.read_char(),
+                ('/', Some('/')) => self.read_line_comment(),
+                ('/', Some('*')) => {
+                    let start = (self.position, self.line, self.column);
+                    if !self.read_block_comment() {
+                        return Some(start)
+                    }
+                },
+                _ => return None,
+            }
         }
     }
 
-    fn reached_eof(&self) -> bool {
-        if self.read_position > self.input.len() {
-            true
-        } else {
-            false
+    fn read_line_comment(&mut self) {
+        let start_pos: usize = self.position;
+        while self.ch != '\n' && !self.reached_eof() {
+            self.read_char();
+        }
+        let comment = &self.input[start_pos..self.position];
+        if let Some(doc) = comment.strip_prefix("///") {
+            if !doc.starts_with('/') {
+                let doc = doc.strip_prefix(' ').unwrap_or(doc).trim_end_matches('\r');
+                self.doc_comment.push(String::from(doc));
+            }
+        }
+    }
+
+    // returns false if the input ends before the comment is closed
+    fn read_block_comment(&mut self) -> bool {
+        let mut depth = 0;
+        loop {
+            match (self.ch, self.peek_char()) {
+                ('/', Some('*')) => {
+                    depth += 1;
+                    self.read_char();
+                },
+                ('*', Some('/')) => {
+                    depth -= 1;
+                    self.read_char();
+                    if depth == 0 {
+                        self.read_char();
+                        return true
+                    }
+                },
+                _ if self.reached_eof() => return false,
+                _ => (),
+            }
+            self.read_char();
         }
     }
 
+    fn reached_eof(&self) -> bool {
+        self.position >= self.input.len()
+    }
+
     pub fn next_token(&mut self) -> Token {
 
-        self.eat_whitespaces();
+        if let Some((start, line, column)) = self.eat_whitespaces() {
+            let mut tok = Token::new(TokenType::ILLEGAL, &self.input[start..]);
+            tok.span = Span::new(start, self.position, line, column);
+            return tok
+        }
+
+        let start = self.position;
+        let (line, column) = (self.line, self.column);
+        let mut tok = self.read_token();
+        tok.span = Span::new(start, self.position, line, column);
+        if !self.doc_comment.is_empty() {
+            tok.doc = Some(self.doc_comment.join("\n"));
+            self.doc_comment.clear();
+        }
+        tok
+    }
+
+    fn read_token(&mut self) -> Token {
+        if self.reached_eof() {
+            return Token::new(TokenType::EOF, "")
+        }
 
         let tok = match self.ch {
-            //hm book uses strings in general here but maybe chars are better for performance, vec<chars> instead of string 
-            //maybe not, the vector of chars would definitely use more memory. One char uses four bytes and a element of a string is just 1 byte
-            //alternative: vec<u8> ???
             '=' => self.make_two_char_token(TokenType::ASSIGN, TokenType::EQ, '='),
-            ';' => Token::new(TokenType::SEMICOLON, self.ch.to_string()),
-            '(' => Token::new(TokenType::LPAREN, self.ch.to_string()),
-            ')' => Token::new(TokenType::RPAREN, self.ch.to_string()),
-            ',' => Token::new(TokenType::COMMA, self.ch.to_string()),
+            ';' => self.char_token(TokenType::SEMICOLON),
+            '(' => self.char_token(TokenType::LPAREN),
+            ')' => self.char_token(TokenType::RPAREN),
+            ',' => self.char_token(TokenType::COMMA),
+            '.' => self.char_token(TokenType::DOT),
             '+' => self.make_two_char_token(TokenType::PLUS, TokenType::PLUSSELF, '='),
-            '-' => self.make_two_char_token(TokenType::MINUS, TokenType::MINUSSELF, '='),
+            '-' => match self.peek_char() {
+                Some('>') => self.make_two_char_token(TokenType::MINUS, TokenType::ARROW, '>'),
+                _ => self.make_two_char_token(TokenType::MINUS, TokenType::MINUSSELF, '='),
+            },
             '!' => self.make_two_char_token(TokenType::BANG, TokenType::NOTEQ, '='),
-            '/' => Token::new(TokenType::SLASH, self.ch.to_string()),
-            '*' => Token::new(TokenType::ASTERISK, self.ch.to_string()),
-            '^' => Token::new(TokenType::POWER, self.ch.to_string()),
-            '<' => self.make_two_char_token(TokenType::LT, TokenType::LTEQ, '='),
-            '>' => self.make_two_char_token(TokenType::GT, TokenType::GTEQ, '='),
-            '{' => Token::new(TokenType::LBRACE, self.ch.to_string()),
-            '}' => Token::new(TokenType::RBRACE, self.ch.to_string()),
+            '/' => self.char_token(TokenType::SLASH),
+            '*' => self.char_token(TokenType::ASTERISK),
+            '^' => self.char_token(TokenType::POWER),
+            '<' => match self.peek_char() {
+                Some('<') => self.make_two_char_token(TokenType::LT, TokenType::SHIFTLEFT, '<'),
+                _ => self.make_two_char_token(TokenType::LT, TokenType::LTEQ, '='),
+            },
+            '>' => match self.peek_char() {
+                Some('>') => self.make_two_char_token(TokenType::GT, TokenType::SHIFTRIGHT, '>'),
+                _ => self.make_two_char_token(TokenType::GT, TokenType::GTEQ, '='),
+            },
+            '&' => self.char_token(TokenType::AMPERSAND),
+            '|' => self.char_token(TokenType::PIPE),
+            '~' => self.char_token(TokenType::TILDE),
+            '{' => self.char_token(TokenType::LBRACE),
+            '}' => self.char_token(TokenType::RBRACE),
+            '[' => self.char_token(TokenType::LBRACKET),
+            ']' => self.char_token(TokenType::RBRACKET),
+            ':' => self.char_token(TokenType::COLON),
+            '"' => return self.read_string(),
             _ => {
-                if is_letter(self.ch) && !self.reached_eof() {
-                    let literal: String = self.read_identifier();
-                    return Token::new(TokenType::lookup_keyword(&literal), literal)
-                    
-                } else if is_digit(self.ch) && !self.reached_eof() {
-                    let literal: String = self.read_number();
+                if is_letter(self.ch) {
+                    let literal = self.read_identifier();
+                    return Token::new(TokenType::lookup_keyword(literal), literal)
+
+                } else if is_digit(self.ch) {
+                    let literal = self.read_number();
                     return Token::new(TokenType::INT, literal)
-                } else if self.reached_eof() {
-                    return Token::new(TokenType::EOF, String::new())
                 } else {
-                    Token::new(TokenType::ILLEGAL, self.ch.to_string())
+                    self.char_token(TokenType::ILLEGAL)
                 }
             }
         };
-        if self.reached_eof() {
-            return Token::new(TokenType::EOF, String::new())
-        }
         self.read_char();
         tok
     }
 }
+impl TokenSource for Lexer {
+    fn next_token(&mut self) -> Token {
+        Lexer::next_token(self)
+    }
+}
+// identifiers start with a letter or "_" and continue with letters, "_" or the digits 0-9.
+// letters are all unicode alphabetic chars, so "größe" or "x1" are single identifiers
 fn is_letter(byte: char) -> bool {
-    'a' <= byte && byte <= 'z' || 'A' <= byte && byte <= 'Z' || byte == '_'
+    byte.is_alphabetic() || byte == '_'
+}
+
+fn is_identifier_char(byte: char) -> bool {
+    is_letter(byte) || is_digit(byte)
 }
 
 fn is_digit(byte:char) -> bool {
-    '0' <= byte && byte <= '9'
-}
\ No newline at end of file
+    byte.is_ascii_digit()
+}
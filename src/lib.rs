@@ -4,4 +4,23 @@ pub mod repl;
 pub mod ast;
 pub mod parser;
 pub mod object;
-pub mod evaluator;
\ No newline at end of file
+pub mod evaluator;
+pub mod bigint;
+pub mod module;
+pub mod builtins;
+pub mod stdlib;
+pub mod fmt;
+pub mod cst;
+
+pub mod json;
+pub mod lint;
+pub mod lsp;
+pub mod optimizer;
+pub mod typecheck;
+pub mod ast_json;
+pub mod gc;
+pub mod resolver;
+pub mod symbol;
+pub mod arena;
+pub mod fuzz;
+pub mod scope;
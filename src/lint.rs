@@ -0,0 +1,233 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+
+use crate::ast::*;
+use crate::builtins::builtin_names;
+use crate::json::JsonValue;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::scope::{DefinitionKind, Scopes};
+use crate::stdlib::prelude_names;
+use crate::symbol::Symbol;
+use crate::token::*;
+use crate::typecheck;
+
+// the linter finds names the evaluator can not look up, see scope.rs for the scoping rules,
+// and code that never runs or conditions that never change
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LintKind {
+    UNDEFINEDNAME,
+    USEDBEFOREDEFINITION,
+    UNUSEDVARIABLE,
+    UNUSEDPARAMETER,
+    UNUSEDIMPORT,
+    UNREACHABLECODE,
+    CONSTANTCONDITION,
+    SHADOWING,
+}
+impl LintKind {
+    // the name of the warning in the output of monkey check
+    pub fn code(&self) -> &'static str {
+        match self {
+            LintKind::UNDEFINEDNAME => "undefined-name",
+            LintKind::USEDBEFOREDEFINITION => "used-before-definition",
+            LintKind::UNUSEDVARIABLE => "unused-variable",
+            LintKind::UNUSEDPARAMETER => "unused-parameter",
+            LintKind::UNUSEDIMPORT => "unused-import",
+            LintKind::UNREACHABLECODE => "unreachable-code",
+            LintKind::CONSTANTCONDITION => "constant-condition",
+            LintKind::SHADOWING => "shadowing",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Warning {
+    pub kind: LintKind,
+    pub message: String,
+    pub span: Span,
+}
+impl Warning {
+    pub fn new(kind: LintKind, message: String, span: Span) -> Warning {
+        Warning { kind, message, span }
+    }
+}
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: warning[{}]: {}", self.span.line, self.span.column, self.kind.code(), self.message)
+    }
+}
+
+struct Linter {
+    warnings: Vec<Warning>,
+}
+
+// lints a program that runs with the builtins and the bundled prelude
+pub fn lint(program: &Programm) -> Vec<Warning> {
+    let mut globals: Vec<String> = builtin_names().into_iter().map(String::from).collect();
+    globals.extend(prelude_names());
+    lint_with_globals(program, globals)
+}
+
+// lints a program that can use the given names without defining them
+pub fn lint_with_globals(program: &Programm, globals: Vec<String>) -> Vec<Warning> {
+    let globals: HashSet<Symbol> = globals.iter().map(|x| Symbol::intern(x)).collect();
+    let mut linter = Linter { warnings: Vec::new() };
+    linter.visit_program(program);
+
+    let scopes = Scopes::new(program);
+    let mut used = vec![false; scopes.definitions.len()];
+    for reference in &scopes.references {
+        let name = reference.name;
+        match reference.target {
+            Some(x) => used[x] = true,
+            None if reference.before_definition => {
+                linter.warn(LintKind::USEDBEFOREDEFINITION, format!("{} is used before its definition", name.value), name.token.span);
+            },
+            None if !globals.contains(&name.value) => {
+                linter.warn(LintKind::UNDEFINEDNAME, format!("{} is not defined", name.value), name.token.span);
+            },
+            None => (),
+        }
+    }
+    for shadowing in &scopes.shadowing {
+        let name = scopes.definitions[shadowing.definition].name;
+        let message = if shadowing.same_scope {
+            format!("{} is already defined in this scope", name.value)
+        } else {
+            format!("{} shadows a variable of an outer scope", name.value)
+        };
+        linter.warn(LintKind::SHADOWING, message, name.token.span);
+    }
+    for (definition, used) in scopes.definitions.iter().zip(used) {
+        let name = definition.name;
+        if used || name.value.text().starts_with('_') {
+            continue
+        }
+        let (kind, what) = match definition.kind {
+            DefinitionKind::PARAMETER => (LintKind::UNUSEDPARAMETER, "parameter"),
+            DefinitionKind::LET(_) => (LintKind::UNUSEDVARIABLE, "variable"),
+            DefinitionKind::IMPORT(_) => (LintKind::UNUSEDIMPORT, "import"),
+            // exported names are used by the modules importing them
+            DefinitionKind::EXPORT(_) => continue,
+        };
+        linter.warn(kind, format!("unused {} {}", what, name.value), name.token.span);
+    }
+    linter.warnings.sort_by_key(|x| x.span.start);
+    linter.warnings
+}
+
+impl Linter {
+    fn warn(&mut self, kind: LintKind, message: String, span: Span) {
+        self.warnings.push(Warning::new(kind, message, span));
+    }
+
+    // the first statement after a return in a list of statements
+    fn check_unreachable(&mut self, statements: &[Statement]) {
+        if let Some(i) = statements.iter().position(|x| matches!(x, Statement::RETURN(_))) {
+            if let Some(unreachable) = statements.get(i + 1) {
+                self.warn(LintKind::UNREACHABLECODE, String::from("unreachable code after return"), statement_span(unreachable));
+            }
+        }
+    }
+}
+impl Visitor<'_> for Linter {
+    fn visit_program(&mut self, program: &Programm) {
+        self.check_unreachable(&program.statements);
+        walk_program(self, program);
+    }
+
+    fn visit_block_statement(&mut self, block: &BlockStatement) {
+        self.check_unreachable(&block.statements);
+        walk_block_statement(self, block);
+    }
+
+    fn visit_if_expression(&mut self, expression: &IfExpression) {
+        if let Some(value) = constant_condition(&expression.condition) {
+            self.warn(LintKind::CONSTANTCONDITION, format!("condition is always {}", value), expression.tok.span);
+        }
+        walk_if_expression(self, expression);
+    }
+}
+
+// true and false, negated with ! any number of times
+fn constant_condition(condition: &MonkeyExpression) -> Option<bool> {
+    match condition {
+        MonkeyExpression::BOOLEAN(x) => Some(x.value),
+        MonkeyExpression::PREFIX(x) if x.operator == "!" => constant_condition(&x.right).map(|value| !value),
+        _ => None,
+    }
+}
+
+fn statement_span(statement: &Statement) -> Span {
+    match statement {
+        Statement::LET(x) => x.token.span,
+        Statement::RETURN(x) => x.token.span,
+        Statement::EXPRESSION(x) => x.token.span,
+        Statement::BLOCK(x) => x.token.span,
+        Statement::IMPORT(x) => x.token.span,
+        Statement::EXPORT(x) => x.token.span,
+    }
+}
+
+fn diagnostic(file: &str, severity: &str, code: &str, message: &str, span: Span) -> JsonValue {
+    JsonValue::object(vec![
+        ("file", JsonValue::string(file)),
+        ("severity", JsonValue::string(severity)),
+        ("code", JsonValue::string(code)),
+        ("message", JsonValue::string(message)),
+        ("line", JsonValue::NUMBER(span.line as i64)),
+        ("column", JsonValue::NUMBER(span.column as i64)),
+        ("start", JsonValue::NUMBER(span.start as i64)),
+        ("end", JsonValue::NUMBER(span.end as i64)),
+    ])
+}
+
+// monkey check [--types] [files...]
+// prints a json array with one object per warning or error to stdout, --types adds the errors of the type checker.
+// returns the exit code: 0 if nothing was found, 1 if there are warnings, 2 on errors
+pub fn run(args: impl Iterator<Item = String>) -> i32 {
+    let mut diagnostics: Vec<JsonValue> = Vec::new();
+    let mut exit_code = 0;
+    let mut types = false;
+    let mut files: Vec<String> = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--types" => types = true,
+            _ => files.push(arg),
+        }
+    }
+    for file in files {
+        let source = match fs::read_to_string(&file) {
+            Ok(x) => x,
+            Err(err) => {
+                eprintln!("Error: could not read {}: {}", file, err);
+                exit_code = 2;
+                continue
+            }
+        };
+        let mut lexer = Lexer::new(source);
+        let program = match Parser::new(&mut lexer).parse_programm() {
+            Ok(x) => x,
+            Err(err) => {
+                diagnostics.push(diagnostic(&file, "error", "parse-error", &err.message, err.token.span));
+                exit_code = 2;
+                continue
+            }
+        };
+        for warning in lint(&program) {
+            diagnostics.push(diagnostic(&file, "warning", warning.kind.code(), &warning.message, warning.span));
+            exit_code = exit_code.max(1);
+        }
+        if types {
+            for error in typecheck::check(&program) {
+                diagnostics.push(diagnostic(&file, "error", "type-error", &error.message, error.span));
+                exit_code = 2;
+            }
+        }
+    }
+    println!("{}", JsonValue::ARRAY(diagnostics));
+    exit_code
+}
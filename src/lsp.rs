@@ -0,0 +1,546 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use crate::ast::*;
+use crate::builtins::builtin_names;
+use crate::fmt::format_source;
+use crate::json::{self, JsonValue};
+use crate::lexer::Lexer;
+use crate::lint::lint;
+use crate::parser::Parser;
+use crate::scope::{self, DefinitionKind, Scopes};
+use crate::stdlib::PRELUDE;
+use crate::token::*;
+
+// a language server for editors, speaking json-rpc over stdin and stdout.
+// the editor sends the whole text of a document on every change and every request parses it again
+
+const KEYWORDS: [&str; 11] = ["fn", "let", "true", "false", "if", "else", "return", "import", "export", "as", "from"];
+
+// error codes of json-rpc and the language server protocol
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const REQUEST_FAILED: i64 = -32803;
+
+// kinds of completion items and severities of diagnostics as numbered by the protocol
+const COMPLETION_FUNCTION: i64 = 3;
+const COMPLETION_VARIABLE: i64 = 6;
+const COMPLETION_KEYWORD: i64 = 14;
+const SEVERITY_ERROR: i64 = 1;
+const SEVERITY_WARNING: i64 = 2;
+
+type RequestResult = Result<JsonValue, (i64, String)>;
+
+// monkey lsp
+// returns the exit code: 0 if the editor sent shutdown before exit, 1 otherwise
+pub fn run() -> i32 {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    serve(&mut stdin.lock(), &mut stdout.lock())
+}
+
+pub fn serve(input: &mut impl BufRead, output: &mut impl Write) -> i32 {
+    let mut server = Server::new();
+    loop {
+        let body = match read_message(input) {
+            Ok(Some(x)) => x,
+            Ok(None) => return 1,
+            Err(err) => {
+                eprintln!("Error: could not read message: {}", err);
+                return 1
+            }
+        };
+        let outgoing = match json::parse(&body) {
+            Ok(message) if message.get("method").and_then(|x| x.as_str()) == Some("exit") => {
+                return if server.shutdown { 0 } else { 1 }
+            },
+            Ok(message) => server.handle(&message),
+            Err(err) => vec![response(JsonValue::NULL, Err((PARSE_ERROR, err)))],
+        };
+        for message in outgoing {
+            if let Err(err) = write_message(output, &message) {
+                eprintln!("Error: could not write message: {}", err);
+                return 1
+            }
+        }
+    }
+}
+
+// every message is a "Content-Length: n" header, an empty line and n bytes of json
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length: Option<usize> = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None)
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if length.is_some() {
+                break
+            }
+            continue
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+    let mut body = vec![0; length.unwrap_or(0)];
+    input.read_exact(&mut body)?;
+    Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+fn write_message(output: &mut impl Write, message: &JsonValue) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+fn response(id: JsonValue, result: RequestResult) -> JsonValue {
+    match result {
+        Ok(x) => JsonValue::object(vec![("jsonrpc", JsonValue::string("2.0")), ("id", id), ("result", x)]),
+        Err((code, message)) => JsonValue::object(vec![
+            ("jsonrpc", JsonValue::string("2.0")),
+            ("id", id),
+            ("error", JsonValue::object(vec![("code", JsonValue::NUMBER(code)), ("message", JsonValue::STRING(message))])),
+        ]),
+    }
+}
+
+fn notification(method: &str, params: JsonValue) -> JsonValue {
+    JsonValue::object(vec![("jsonrpc", JsonValue::string("2.0")), ("method", JsonValue::string(method)), ("params", params)])
+}
+
+struct Server {
+    documents: HashMap<String, String>,
+    prelude: Option<Analysis>,
+    shutdown: bool,
+}
+impl Server {
+    fn new() -> Server {
+        let mut lexer = Lexer::new(String::from(PRELUDE));
+        let prelude = Parser::new(&mut lexer).parse_programm().ok().map(|x| analyze(PRELUDE, &x));
+        Server { documents: HashMap::new(), prelude, shutdown: false }
+    }
+
+    fn handle(&mut self, message: &JsonValue) -> Vec<JsonValue> {
+        // a message without a method is the answer to a request of the server, it never sends any
+        let method = match message.get("method").and_then(|x| x.as_str()) {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+        let params = message.get("params").unwrap_or(&JsonValue::NULL);
+        let id = match message.get("id") {
+            Some(x) => x.clone(),
+            None => return self.notification(method, params),
+        };
+        let result = if self.shutdown {
+            Err((INVALID_REQUEST, String::from("the server is shutting down")))
+        } else {
+            self.request(method, params)
+        };
+        vec![response(id, result)]
+    }
+
+    fn request(&mut self, method: &str, params: &JsonValue) -> RequestResult {
+        match method {
+            "initialize" => Ok(capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(JsonValue::NULL)
+            },
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/completion" => self.completion(params),
+            "textDocument/formatting" => self.formatting(params),
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method {}", method))),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &JsonValue) -> Vec<JsonValue> {
+        let document = params.get("textDocument");
+        let uri = match document.and_then(|x| x.get("uri")).and_then(|x| x.as_str()) {
+            Some(x) => String::from(x),
+            None => return Vec::new(),
+        };
+        match method {
+            "textDocument/didOpen" => {
+                let text = document.and_then(|x| x.get("text")).and_then(|x| x.as_str()).unwrap_or_default();
+                self.documents.insert(uri.clone(), String::from(text));
+            },
+            // only full documents are synced, the last change holds the whole text
+            "textDocument/didChange" => {
+                let changes = params.get("contentChanges").and_then(|x| x.as_array());
+                let text = match changes.and_then(|x| x.last()).and_then(|x| x.get("text")).and_then(|x| x.as_str()) {
+                    Some(x) => x,
+                    None => return Vec::new(),
+                };
+                self.documents.insert(uri.clone(), String::from(text));
+            },
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return vec![publish_diagnostics(&uri, Vec::new())]
+            },
+            _ => return Vec::new(),
+        }
+        let diagnostics = diagnostics(&self.documents[&uri]);
+        vec![publish_diagnostics(&uri, diagnostics)]
+    }
+
+    fn document(&self, params: &JsonValue) -> Result<(String, &str), (i64, String)> {
+        let uri = match params.get("textDocument").and_then(|x| x.get("uri")).and_then(|x| x.as_str()) {
+            Some(x) => x,
+            None => return Err((INVALID_PARAMS, String::from("missing textDocument.uri"))),
+        };
+        match self.documents.get(uri) {
+            Some(text) => Ok((String::from(uri), text)),
+            None => Err((INVALID_PARAMS, format!("unknown document {}", uri))),
+        }
+    }
+
+    // the document and the byte offset of params.position in it
+    fn document_position(&self, params: &JsonValue) -> Result<(String, &str, usize), (i64, String)> {
+        let (uri, text) = self.document(params)?;
+        let position = params.get("position");
+        let line = position.and_then(|x| x.get("line")).and_then(|x| x.as_i64());
+        let character = position.and_then(|x| x.get("character")).and_then(|x| x.as_i64());
+        match (line, character) {
+            (Some(line), Some(character)) => Ok((uri, text, offset_of(text, line.max(0) as usize, character.max(0) as usize))),
+            _ => Err((INVALID_PARAMS, String::from("missing position"))),
+        }
+    }
+
+    fn hover(&self, params: &JsonValue) -> RequestResult {
+        let (_, text, offset) = self.document_position(params)?;
+        let analysis = match parse_and_analyze(text) {
+            Some(x) => x,
+            None => return Ok(JsonValue::NULL),
+        };
+        let (span, target) = match analysis.reference_at(offset) {
+            Some(x) => x,
+            None => return Ok(JsonValue::NULL),
+        };
+        let definition = match target {
+            Target::LOCAL(x) => Some(&analysis.definitions[*x]),
+            Target::GLOBAL(name) => self.prelude.as_ref().and_then(|x| x.top_level(name)),
+        };
+        let markdown = match (definition, target) {
+            (Some(definition), _) => definition.markdown(),
+            (None, Target::GLOBAL(name)) if builtin_names().contains(&name.as_str()) => format!("```monkey\nbuiltin {}\n```", name),
+            _ => return Ok(JsonValue::NULL),
+        };
+        Ok(JsonValue::object(vec![
+            ("contents", JsonValue::object(vec![("kind", JsonValue::string("markdown")), ("value", JsonValue::STRING(markdown))])),
+            ("range", range(text, *span)),
+        ]))
+    }
+
+    fn definition(&self, params: &JsonValue) -> RequestResult {
+        let (uri, text, offset) = self.document_position(params)?;
+        let analysis = match parse_and_analyze(text) {
+            Some(x) => x,
+            None => return Ok(JsonValue::NULL),
+        };
+        match analysis.reference_at(offset) {
+            Some((_, Target::LOCAL(x))) => Ok(JsonValue::object(vec![
+                ("uri", JsonValue::STRING(uri)),
+                ("range", range(text, analysis.definitions[*x].span)),
+            ])),
+            // builtins and the prelude have no file to jump to
+            _ => Ok(JsonValue::NULL),
+        }
+    }
+
+    // keywords, builtins, the prelude and the names defined in the scopes around the cursor
+    fn completion(&self, params: &JsonValue) -> RequestResult {
+        let (_, text, offset) = self.document_position(params)?;
+        let mut items: Vec<(String, i64, String)> = Vec::new();
+        match parse_and_analyze(text) {
+            Some(analysis) => {
+                for definition in analysis.visible_at(offset) {
+                    let kind = if definition.is_function { COMPLETION_FUNCTION } else { COMPLETION_VARIABLE };
+                    items.push((definition.name.clone(), kind, definition.detail.clone()));
+                }
+            },
+            // while the document does not parse every name after a let is offered
+            None => {
+                let mut lexer = Lexer::new(String::from(text));
+                let mut previous = lexer.next_token();
+                while previous.tokentype != TokenType::EOF && previous.tokentype != TokenType::ILLEGAL {
+                    let tok = lexer.next_token();
+                    if previous.tokentype == TokenType::LET && tok.tokentype == TokenType::IDENT {
+                        items.push((tok.literal.to_string(), COMPLETION_VARIABLE, format!("let {}", tok.literal)));
+                    }
+                    previous = tok;
+                }
+            },
+        }
+        if let Some(prelude) = &self.prelude {
+            for definition in prelude.visible_at(PRELUDE.len()) {
+                items.push((definition.name.clone(), COMPLETION_FUNCTION, definition.detail.clone()));
+            }
+        }
+        for name in builtin_names() {
+            items.push((String::from(name), COMPLETION_FUNCTION, format!("builtin {}", name)));
+        }
+        for keyword in KEYWORDS {
+            items.push((String::from(keyword), COMPLETION_KEYWORD, String::from("keyword")));
+        }
+
+        let mut seen: Vec<String> = Vec::new();
+        let mut completions: Vec<JsonValue> = Vec::new();
+        for (label, kind, detail) in items {
+            if seen.contains(&label) {
+                continue
+            }
+            seen.push(label.clone());
+            completions.push(JsonValue::object(vec![
+                ("label", JsonValue::STRING(label)),
+                ("kind", JsonValue::NUMBER(kind)),
+                ("detail", JsonValue::STRING(detail)),
+            ]));
+        }
+        Ok(JsonValue::ARRAY(completions))
+    }
+
+    // the whole document is replaced by its formatted source
+    fn formatting(&self, params: &JsonValue) -> RequestResult {
+        let (_, text) = self.document(params)?;
+        let formatted = match format_source(text) {
+            Ok(x) => x,
+            Err(err) => return Err((REQUEST_FAILED, format!("could not parse the document: {}", err.message))),
+        };
+        if formatted == text {
+            return Ok(JsonValue::ARRAY(Vec::new()))
+        }
+        let whole = JsonValue::object(vec![("start", position(text, 0)), ("end", position(text, text.len()))]);
+        Ok(JsonValue::ARRAY(vec![JsonValue::object(vec![("range", whole), ("newText", JsonValue::STRING(formatted))])]))
+    }
+}
+
+fn capabilities() -> JsonValue {
+    JsonValue::object(vec![
+        ("capabilities", JsonValue::object(vec![
+            // 1: the editor sends the full text on every change
+            ("textDocumentSync", JsonValue::NUMBER(1)),
+            ("hoverProvider", JsonValue::BOOL(true)),
+            ("definitionProvider", JsonValue::BOOL(true)),
+            ("completionProvider", JsonValue::object(vec![("triggerCharacters", JsonValue::ARRAY(Vec::new()))])),
+            ("documentFormattingProvider", JsonValue::BOOL(true)),
+        ])),
+        ("serverInfo", JsonValue::object(vec![("name", JsonValue::string("monkey")), ("version", JsonValue::string(env!("CARGO_PKG_VERSION")))])),
+    ])
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<JsonValue>) -> JsonValue {
+    notification("textDocument/publishDiagnostics", JsonValue::object(vec![
+        ("uri", JsonValue::string(uri)),
+        ("diagnostics", JsonValue::ARRAY(diagnostics)),
+    ]))
+}
+
+// the parse errors of the document or, if it parses, the warnings of the linter
+fn diagnostics(text: &str) -> Vec<JsonValue> {
+    let diagnostic = |span: Span, severity: i64, code: &str, message: &str| JsonValue::object(vec![
+        ("range", range(text, span)),
+        ("severity", JsonValue::NUMBER(severity)),
+        ("code", JsonValue::string(code)),
+        ("source", JsonValue::string("monkey")),
+        ("message", JsonValue::string(message)),
+    ]);
+    let mut lexer = Lexer::new(String::from(text));
+    match Parser::new(&mut lexer).parse_programm_recovering() {
+        Ok(program) => lint(&program).iter()
+            .map(|x| diagnostic(x.span, SEVERITY_WARNING, x.kind.code(), &x.message))
+            .collect(),
+        Err(errors) => errors.iter()
+            .map(|x| diagnostic(x.token.span, SEVERITY_ERROR, "parse-error", &x.message))
+            .collect(),
+    }
+}
+
+// lsp positions are a line and a column counted in utf-16 code units, both starting at 0
+fn position(text: &str, offset: usize) -> JsonValue {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+    let line = before.matches('\n').count();
+    let character: usize = before[line_start..].chars().map(|ch| ch.len_utf16()).sum();
+    JsonValue::object(vec![("line", JsonValue::NUMBER(line as i64)), ("character", JsonValue::NUMBER(character as i64))])
+}
+
+fn range(text: &str, span: Span) -> JsonValue {
+    JsonValue::object(vec![("start", position(text, span.start)), ("end", position(text, span.end))])
+}
+
+fn offset_of(text: &str, line: usize, character: usize) -> usize {
+    let mut line_start = 0;
+    for _ in 0..line {
+        match text[line_start..].find('\n') {
+            Some(x) => line_start += x + 1,
+            None => return text.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, ch) in text[line_start..].char_indices() {
+        if units >= character || ch == '\n' {
+            return line_start + i
+        }
+        units += ch.len_utf16();
+    }
+    text.len()
+}
+
+// a name defined by a let statement, a parameter or an import
+struct Definition {
+    name: String,
+    span: Span,
+    detail: String,
+    doc: Option<String>,
+    is_function: bool,
+}
+impl Definition {
+    fn new(name: &Identifier, detail: String, doc: Option<String>, is_function: bool) -> Definition {
+        Definition { name: name.value.to_string(), span: name.token.span, detail, doc, is_function }
+    }
+
+    fn markdown(&self) -> String {
+        match &self.doc {
+            Some(doc) => format!("```monkey\n{}\n```\n\n{}", self.detail, doc),
+            None => format!("```monkey\n{}\n```", self.detail),
+        }
+    }
+}
+
+// what an identifier refers to: a definition in the document or a builtin or prelude function
+#[allow(clippy::upper_case_acronyms)]
+enum Target {
+    LOCAL(usize),
+    GLOBAL(String),
+}
+
+// the source range of the module or of a function with the definitions made inside of it
+struct ScopeRange {
+    start: usize,
+    end: usize,
+    definitions: Vec<usize>,
+}
+
+struct Analysis {
+    definitions: Vec<Definition>,
+    references: Vec<(Span, Target)>,
+    scopes: Vec<ScopeRange>,
+}
+impl Analysis {
+    fn reference_at(&self, offset: usize) -> Option<&(Span, Target)> {
+        self.references.iter().find(|(span, _)| span.start <= offset && offset <= span.end)
+    }
+
+    fn top_level(&self, name: &str) -> Option<&Definition> {
+        self.scopes.first()?.definitions.iter().map(|x| &self.definitions[*x]).find(|x| x.name == name)
+    }
+
+    // inside of the innermost scope only names defined above the offset can be used,
+    // the names of the outer scopes can all be used by a function that is called later
+    fn visible_at(&self, offset: usize) -> Vec<&Definition> {
+        let enclosing: Vec<&ScopeRange> = self.scopes.iter().filter(|x| x.start <= offset && offset <= x.end).collect();
+        let mut visible: Vec<&Definition> = Vec::new();
+        for (i, scope) in enclosing.iter().enumerate().rev() {
+            let innermost = i == enclosing.len() - 1;
+            for definition in scope.definitions.iter().map(|x| &self.definitions[*x]) {
+                if !innermost || definition.span.start < offset {
+                    visible.push(definition);
+                }
+            }
+        }
+        visible
+    }
+}
+
+fn parse_and_analyze(text: &str) -> Option<Analysis> {
+    let mut lexer = Lexer::new(String::from(text));
+    let program = Parser::new(&mut lexer).parse_programm().ok()?;
+    Some(analyze(text, &program))
+}
+
+// every definition and every identifier that refers to one, resolved by the rules of scope.rs.
+// a definition is a reference to itself, so the name of a let shows its hover as well
+fn analyze(text: &str, program: &Programm) -> Analysis {
+    let mut lexer = Lexer::new(String::from(text));
+    let mut tokens: Vec<Token> = Vec::new();
+    loop {
+        let tok = lexer.next_token();
+        if tok.tokentype == TokenType::EOF || tok.tokentype == TokenType::ILLEGAL {
+            break
+        }
+        tokens.push(tok);
+    }
+    let resolved = Scopes::new(program);
+    let mut references: Vec<(Span, Target)> = Vec::new();
+    for (i, definition) in resolved.definitions.iter().enumerate() {
+        references.push((definition.name.token.span, Target::LOCAL(i)));
+    }
+    for reference in &resolved.references {
+        let target = match reference.target {
+            Some(x) => Target::LOCAL(x),
+            None => Target::GLOBAL(reference.name.value.to_string()),
+        };
+        references.push((reference.name.token.span, target));
+    }
+    let scopes = resolved.scopes.iter()
+        .map(|scope| match scope.function {
+            Some(function) => ScopeRange {
+                start: function.token.span.start,
+                end: closing_brace(&tokens, function.blockstatment.token.span.start),
+                definitions: scope.definitions.clone(),
+            },
+            None => ScopeRange { start: 0, end: text.len(), definitions: scope.definitions.clone() },
+        })
+        .collect();
+    Analysis { definitions: resolved.definitions.iter().map(describe).collect(), references, scopes }
+}
+
+// the end of the "}" that closes the "{" starting at open
+fn closing_brace(tokens: &[Token], open: usize) -> usize {
+    let mut depth = 0;
+    for tok in tokens.iter().skip_while(|x| x.span.start < open) {
+        match tok.tokentype {
+            TokenType::LBRACE => depth += 1,
+            TokenType::RBRACE => depth -= 1,
+            _ => (),
+        }
+        if depth == 0 {
+            return tok.span.end
+        }
+    }
+    tokens.last().map_or(open, |x| x.span.end)
+}
+
+// the line shown for a definition on hover and completion
+fn describe(definition: &scope::Definition) -> Definition {
+    let name = definition.name;
+    let (prefix, statement) = match definition.kind {
+        DefinitionKind::PARAMETER => return Definition::new(name, format!("parameter {}", name.value), None, false),
+        DefinitionKind::IMPORT(statement) => {
+            let detail = match &statement.alias {
+                Some(_) => format!("import \"{}\" as {}", statement.path, name.value),
+                None => format!("import {{ {} }} from \"{}\"", name.value, statement.path),
+            };
+            return Definition::new(name, detail, None, false)
+        },
+        DefinitionKind::LET(x) => ("", x),
+        DefinitionKind::EXPORT(x) => ("export ", x),
+    };
+    let doc = statement.doc().map(String::from);
+    match &statement.value {
+        MonkeyExpression::FUNCTIONLITERAL(func) => {
+            let params: Vec<String> = func.parameters.iter().map(|x| x.value.to_string()).collect();
+            Definition::new(name, format!("{}let {} = fn({})", prefix, name.value, params.join(", ")), doc, true)
+        },
+        _ => Definition::new(name, format!("{}let {}", prefix, name.value), doc, false),
+    }
+}
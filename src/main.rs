@@ -1,7 +1,22 @@
-use monkey::repl;
+use monkey::{fmt, lint, lsp, repl};
+
+// deep recursion in a monkey program needs more stack than the main thread has, see evaluator::MAX_CALL_DEPTH
+const STACK_SIZE: usize = 64 * 1024 * 1024;
 
 fn main() {
+    let interpreter = std::thread::Builder::new().stack_size(STACK_SIZE).spawn(run).expect("could not start the interpreter");
+    if interpreter.join().is_err() {
+        std::process::exit(101);
+    }
+}
+
+fn run() {
     let args = std::env::args();
-    repl::start(args);
+    match std::env::args().nth(1).as_deref() {
+        Some("fmt") => std::process::exit(fmt::run(args.skip(2))),
+        Some("check") => std::process::exit(lint::run(args.skip(2))),
+        Some("lsp") => std::process::exit(lsp::run()),
+        _ => std::process::exit(repl::start(args)),
+    }
     // repl::start_interactive();
 }
@@ -0,0 +1,284 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::builtins::builtin_store;
+use crate::ast::Programm;
+use crate::evaluator::{eval, EvalError};
+use crate::gc;
+use crate::lexer::Lexer;
+use crate::object::{Environment, Module, MonkeyObject};
+use crate::optimizer::optimize;
+use crate::parser::Parser;
+use crate::stdlib::PRELUDE;
+
+// modules are looked up relative to the importing file first and then in every directory of the search path.
+// every module is evaluated once, later imports get the cached module.
+// the prelude is evaluated on first use and shared by all modules
+pub struct ModuleLoader {
+    search_path: Vec<PathBuf>,
+    modules: HashMap<PathBuf, Module>,
+    loading: Vec<PathBuf>,
+    prelude: Option<String>,
+    prelude_env: Option<Rc<RefCell<Environment>>>,
+    optimize: bool,
+}
+impl ModuleLoader {
+    pub fn new(search_path: Vec<PathBuf>) -> ModuleLoader {
+        ModuleLoader {
+            search_path,
+            modules: HashMap::new(),
+            loading: Vec::new(),
+            prelude: Some(String::from(PRELUDE)),
+            prelude_env: None,
+            optimize: false,
+        }
+    }
+
+    // reads the search path from the MONKEY_PATH environment variable
+    pub fn from_env() -> ModuleLoader {
+        let search_path = match std::env::var_os("MONKEY_PATH") {
+            Some(x) => std::env::split_paths(&x).collect(),
+            None => Vec::new(),
+        };
+        ModuleLoader::new(search_path)
+    }
+
+    pub fn add_search_path(&mut self, dir: PathBuf) {
+        self.search_path.push(dir);
+    }
+
+    pub fn search_path(&self) -> &[PathBuf] {
+        &self.search_path
+    }
+
+    // replaces the bundled prelude with another monkey source. None leaves only the builtins
+    pub fn set_prelude(&mut self, source: Option<String>) {
+        self.prelude = source;
+        self.prelude_env = None;
+    }
+
+    pub fn disable_prelude(&mut self) {
+        self.set_prelude(None);
+    }
+
+    // runs every module and the prelude through the optimizer before evaluating them
+    pub fn set_optimize(&mut self, optimize: bool) {
+        self.optimize = optimize;
+        self.prelude_env = None;
+    }
+
+    fn prepare(&self, program: Programm) -> Programm {
+        if self.optimize {
+            optimize(program)
+        } else {
+            program
+        }
+    }
+
+    fn resolve(&self, import_path: &str, importing_file: Option<&Path>) -> Result<PathBuf, EvalError> {
+        let importing_dir = match importing_file.and_then(|x| x.parent()) {
+            Some(x) => x.to_path_buf(),
+            None => PathBuf::from("."),
+        };
+        let mut candidates: Vec<PathBuf> = vec![importing_dir.join(import_path)];
+        for dir in &self.search_path {
+            candidates.push(dir.join(import_path));
+        }
+        for candidate in candidates {
+            if candidate.is_file() {
+                return canonical_path(&candidate)
+            }
+            let with_extension = candidate.with_extension("mk");
+            if candidate.extension().is_none() && with_extension.is_file() {
+                return canonical_path(&with_extension)
+            }
+        }
+        Err(EvalError::new(format!("could not find module \"{}\"", import_path)))
+    }
+
+    fn enter(&mut self, path: &Path) -> Result<(), EvalError> {
+        if let Some(start) = self.loading.iter().position(|x| x == path) {
+            let mut cycle: Vec<String> = self.loading[start..].iter().map(|x| x.display().to_string()).collect();
+            cycle.push(path.display().to_string());
+            return Err(EvalError::new(format!("import cycle detected: {}", cycle.join(" -> "))))
+        }
+        self.loading.push(path.to_path_buf());
+        Ok(())
+    }
+}
+impl fmt::Debug for ModuleLoader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ModuleLoader")
+            .field("search_path", &self.search_path)
+            .field("modules", &self.modules.keys().collect::<Vec<_>>())
+            .field("prelude", &self.prelude.is_some())
+            .field("optimize", &self.optimize)
+            .finish()
+    }
+}
+
+fn canonical_path(path: &Path) -> Result<PathBuf, EvalError> {
+    match fs::canonicalize(path) {
+        Ok(x) => Ok(x),
+        Err(err) => Err(EvalError::new(format!("could not open module {}: {}", path.display(), err))),
+    }
+}
+
+pub fn load_module(loader: &Rc<RefCell<ModuleLoader>>, import_path: &str, importing_file: Option<&Path>) -> Result<Module, EvalError> {
+    let path = loader.borrow().resolve(import_path, importing_file)?;
+    if let Some(module) = loader.borrow().modules.get(&path) {
+        return Ok(module.clone())
+    }
+    let (env, _) = eval_file(loader, &path)?;
+    let module = Module::new(path.clone(), env);
+    loader.borrow_mut().modules.insert(path, module.clone());
+    Ok(module)
+}
+
+// evaluates a file as the entry point of a program, its imports are resolved relative to it
+pub fn run_file(loader: &Rc<RefCell<ModuleLoader>>, path: &Path) -> Result<Vec<MonkeyObject>, EvalError> {
+    let path = canonical_path(path)?;
+    let (_, results) = eval_file(loader, &path)?;
+    Ok(results)
+}
+
+fn eval_file(loader: &Rc<RefCell<ModuleLoader>>, path: &Path) -> Result<(Rc<RefCell<Environment>>, Vec<MonkeyObject>), EvalError> {
+    loader.borrow_mut().enter(path)?;
+    let result = eval_source(loader, path);
+    loader.borrow_mut().loading.pop();
+    result
+}
+
+fn eval_source(loader: &Rc<RefCell<ModuleLoader>>, path: &Path) -> Result<(Rc<RefCell<Environment>>, Vec<MonkeyObject>), EvalError> {
+    let input = match fs::read_to_string(path) {
+        Ok(x) => x,
+        Err(err) => return Err(EvalError::new(format!("could not read {}: {}", path.display(), err))),
+    };
+    let mut lexer = Lexer::new(input);
+    let program = match Parser::new(&mut lexer).parse_programm() {
+        Ok(x) => x,
+        Err(err) => return Err(EvalError::new(format!("could not parse {}: {}", path.display(), err))),
+    };
+    let program = loader.borrow().prepare(program);
+    let prelude = prelude_env(loader)?;
+    let env = gc::track(Environment::new_module(Some(path.to_path_buf()), Rc::clone(loader), Some(prelude)));
+    let results = eval(&program.statements, Rc::clone(&env))?;
+    Ok((env, results))
+}
+
+// the environment holding the builtins and the prelude, it is the outer environment of every module
+pub fn prelude_env(loader: &Rc<RefCell<ModuleLoader>>) -> Result<Rc<RefCell<Environment>>, EvalError> {
+    if let Some(env) = &loader.borrow().prelude_env {
+        return Ok(Rc::clone(env))
+    }
+    let env = gc::track(Environment::from_store(builtin_store()));
+    let source = loader.borrow().prelude.clone();
+    if let Some(source) = source {
+        let mut lexer = Lexer::new(source);
+        let program = match Parser::new(&mut lexer).parse_programm() {
+            Ok(x) => x,
+            Err(err) => return Err(EvalError::new(format!("could not parse prelude: {}", err))),
+        };
+        let program = loader.borrow().prepare(program);
+        if let Err(err) = eval(&program.statements, Rc::clone(&env)) {
+            return Err(EvalError::new(format!("error in prelude: {}", err)))
+        }
+    }
+    loader.borrow_mut().prelude_env = Some(Rc::clone(&env));
+    Ok(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::Symbol;
+
+    // a directory of its own for every test, the tests run in parallel
+    fn temp_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("monkey-module-{}-{}", name, std::process::id()));
+        for (file, source) in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+        fs::canonicalize(dir).unwrap()
+    }
+
+    fn loader(search_path: Vec<PathBuf>) -> Rc<RefCell<ModuleLoader>> {
+        Rc::new(RefCell::new(ModuleLoader::new(search_path)))
+    }
+
+    #[test]
+    fn resolves_relative_to_the_importing_file_first() {
+        let dir = temp_dir("resolve", &[("main.mk", ""), ("a.mk", ""), ("lib/a.mk", ""), ("lib/b.mk", ""), ("lib/c.txt", "")]);
+        let loader = ModuleLoader::new(vec![dir.join("lib")]);
+        let main = dir.join("main.mk");
+        assert_eq!(loader.resolve("a", Some(&main)).unwrap(), dir.join("a.mk"));
+        assert_eq!(loader.resolve("./a.mk", Some(&main)).unwrap(), dir.join("a.mk"));
+        assert_eq!(loader.resolve("b", Some(&main)).unwrap(), dir.join("lib").join("b.mk"));
+        assert_eq!(loader.resolve("lib/c.txt", Some(&main)).unwrap(), dir.join("lib").join("c.txt"));
+        assert_eq!(loader.resolve("c", Some(&main)).unwrap_err().message, "could not find module \"c\"");
+        // a file outside of the search path only finds what is next to it
+        assert!(ModuleLoader::new(Vec::new()).resolve("b", Some(&main)).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn evaluates_a_module_once() {
+        let dir = temp_dir("once", &[("counter.mk", "export let value = len([1, 2, 3]);\n")]);
+        let loader = loader(vec![dir.clone()]);
+        let first = load_module(&loader, "counter", None).unwrap();
+        let second = load_module(&loader, "counter.mk", None).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.path, dir.join("counter.mk"));
+        assert_eq!(first.get(Symbol::intern("value")).map(|x| x.inspect()), Some(String::from("3")));
+        // a new loader evaluates it again
+        assert_ne!(load_module(&self::loader(vec![dir.clone()]), "counter", None).unwrap(), first);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reports_import_cycles() {
+        let dir = temp_dir("cycle", &[("a.mk", "import \"b\" as b;\n"), ("b.mk", "import \"a\" as a;\n")]);
+        let err = run_file(&loader(Vec::new()), &dir.join("a.mk")).unwrap_err();
+        let (a, b) = (dir.join("a.mk").display().to_string(), dir.join("b.mk").display().to_string());
+        assert!(err.message.contains(&format!("import cycle detected: {} -> {} -> {}", a, b, a)), "{}", err.message);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn replaces_the_prelude() {
+        let loader = loader(Vec::new());
+        let env = prelude_env(&loader).unwrap();
+        assert!(env.borrow().get(Symbol::intern("map")).is_some());
+        assert!(Rc::ptr_eq(&env, &prelude_env(&loader).unwrap()));
+
+        loader.borrow_mut().set_prelude(Some(String::from("let answer = 42;")));
+        let env = prelude_env(&loader).unwrap();
+        assert_eq!(env.borrow().get(Symbol::intern("answer")).map(|x| x.inspect()), Some(String::from("42")));
+        assert!(env.borrow().get(Symbol::intern("map")).is_none());
+        assert!(env.borrow().get(Symbol::intern("len")).is_some());
+
+        loader.borrow_mut().disable_prelude();
+        assert!(prelude_env(&loader).unwrap().borrow().get(Symbol::intern("answer")).is_none());
+
+        loader.borrow_mut().set_prelude(Some(String::from("let broken = ;")));
+        assert!(prelude_env(&loader).unwrap_err().message.starts_with("could not parse prelude"));
+        loader.borrow_mut().set_prelude(Some(String::from("missing")));
+        assert_eq!(prelude_env(&loader).unwrap_err().message, "error in prelude: Undefined Identifier used: missing");
+    }
+
+    #[test]
+    fn optimizes_modules_when_asked_to() {
+        let loader = loader(Vec::new());
+        let program = Parser::new(&mut Lexer::new(String::from("1 + 2;"))).parse_programm().unwrap();
+        assert_eq!(loader.borrow().prepare(program).to_string(), "1 + 2;");
+        loader.borrow_mut().set_optimize(true);
+        let program = Parser::new(&mut Lexer::new(String::from("1 + 2;"))).parse_programm().unwrap();
+        assert_eq!(loader.borrow().prepare(program).to_string(), "3;");
+    }
+}
@@ -1,26 +1,80 @@
-use std::{cell::{Ref, RefCell}, collections::HashMap, fmt::Debug, rc::Rc};
+use std::{cell::RefCell, cmp::Ordering, collections::{BTreeMap, HashMap, HashSet}, fmt, fmt::Debug, ops::{Add, BitAnd, BitOr, Mul, Neg, Not, Sub}, path::PathBuf, rc::Rc};
 
-use crate::token::{BlockStatement, Identifier};
+use crate::bigint::BigInt;
+use crate::evaluator::EvalError;
+use crate::gc::HeapRef;
+use crate::module::ModuleLoader;
+use crate::symbol::Symbol;
+use crate::token::{FunctionLiteral, Resolved};
 
+// cloning an object is cheap: integers, booleans and null are stored inline, strings, arrays, hashes and
+// function bodies are shared through an Rc. arrays and hashes are copied on write, see Array::elements_mut
 #[derive(Debug, PartialEq, Clone)]
 pub enum MonkeyObject {
     INTEGER(Integer),
+    STRING(Str),
     BOOLEAN(Bool),
     NULL(Null),
-    BLOCK(Block),
     RETURN(ReturnValue),
     FUNCTION(Function),
+    BUILTIN(Builtin),
+    ARRAY(Array),
+    HASH(Hash),
+    MODULE(Module),
 }
 
 impl MonkeyObject {
     pub fn into_obj(self) -> Box<dyn Object> {
         match self {
             Self::INTEGER(x) => Box::new(x),
+            Self::STRING(x) => Box::new(x),
             Self::BOOLEAN(x) => Box::new(x),
             Self::NULL(x) => Box::new(x),
-            Self::BLOCK(x) => Box::new(x),
             Self::RETURN(x) => Box::new(x),
             Self::FUNCTION(x) => Box::new(x),
+            Self::BUILTIN(x) => Box::new(x),
+            Self::ARRAY(x) => Box::new(x),
+            Self::HASH(x) => Box::new(x),
+            Self::MODULE(x) => Box::new(x),
+        }
+    }
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::INTEGER(_) => "INTEGER",
+            Self::STRING(_) => "STRING",
+            Self::BOOLEAN(_) => "BOOLEAN",
+            Self::NULL(_) => "NULL",
+            Self::RETURN(_) => "RETURN",
+            Self::FUNCTION(_) => "FUNCTION",
+            Self::BUILTIN(_) => "BUILTIN",
+            Self::ARRAY(_) => "ARRAY",
+            Self::HASH(_) => "HASH",
+            Self::MODULE(_) => "MODULE",
+        }
+    }
+    pub fn inspect(&self) -> String {
+        match self {
+            Self::INTEGER(x) => x.inspect(),
+            Self::STRING(x) => x.inspect(),
+            Self::BOOLEAN(x) => x.inspect(),
+            Self::NULL(x) => x.inspect(),
+            Self::RETURN(x) => x.inspect(),
+            Self::FUNCTION(x) => x.inspect(),
+            Self::BUILTIN(x) => x.inspect(),
+            Self::ARRAY(x) => x.inspect(),
+            Self::HASH(x) => x.inspect(),
+            Self::MODULE(x) => x.inspect(),
+        }
+    }
+    // calls visit with every environment, array and hash the value holds an Rc to, see gc.rs
+    pub fn each_reference(&self, visit: &mut dyn FnMut(HeapRef)) {
+        match self {
+            Self::RETURN(x) => x.value.each_reference(visit),
+            Self::FUNCTION(x) => visit(HeapRef::ENVIRONMENT(&x.env)),
+            Self::ARRAY(x) => visit(HeapRef::ARRAY(&x.elements)),
+            Self::HASH(x) => visit(HeapRef::HASH(&x.pairs)),
+            Self::MODULE(x) => visit(HeapRef::ENVIRONMENT(&x.env)),
+            _ => (),
         }
     }
 }
@@ -30,18 +84,242 @@ pub trait Object: ObjectInterface + Debug {}
 pub trait ObjectInterface {
     fn inspect(&self) -> String;
 }
-#[derive(Debug, PartialEq,Clone)]
+// integers are stored inline as i64 and promoted to a BigInt once a result no longer fits.
+// a BIG value is always outside the i64 range, so equal values always have the same representation
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum IntegerValue {
+    SMALL(i64),
+    BIG(BigInt),
+}
+
+// ^ and << can make a huge number out of two small ones, results with more bits than this are an error
+// instead of taking forever
+pub const MAX_RESULT_BITS: u64 = 1 << 16;
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct Integer {
-    pub value: i64
+    value: IntegerValue,
 }
 impl Integer {
     pub fn new(val: i64) -> Integer {
-        Integer { value: val }
+        Integer { value: IntegerValue::SMALL(val) }
+    }
+    pub fn from_big(val: BigInt) -> Integer {
+        match val.to_i64() {
+            Some(x) => Integer::new(x),
+            None => Integer { value: IntegerValue::BIG(val) },
+        }
+    }
+    pub fn value(&self) -> &IntegerValue {
+        &self.value
+    }
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.value {
+            IntegerValue::SMALL(x) => Some(x),
+            IntegerValue::BIG(_) => None,
+        }
+    }
+    pub fn to_big(&self) -> BigInt {
+        match &self.value {
+            IntegerValue::SMALL(x) => BigInt::from_i64(*x),
+            IntegerValue::BIG(x) => x.clone(),
+        }
+    }
+    pub fn is_negative(&self) -> bool {
+        match &self.value {
+            IntegerValue::SMALL(x) => *x < 0,
+            IntegerValue::BIG(x) => x.is_negative(),
+        }
+    }
+    // truncating division like i64, fails on division by zero
+    pub fn checked_div(&self, other: &Integer) -> Result<Integer, &'static str> {
+        if let (Some(l), Some(r)) = (self.as_i64(), other.as_i64()) {
+            if r == 0 {
+                return Err("division by zero")
+            }
+            if let Some(x) = l.checked_div(r) {
+                return Ok(Integer::new(x))
+            }
+        }
+        match self.to_big().div_rem(&other.to_big()) {
+            Some((quotient, _)) => Ok(Integer::from_big(quotient)),
+            None => Err("division by zero"),
+        }
+    }
+    pub fn pow(&self, exponent: &Integer) -> Result<Integer, &'static str> {
+        if exponent.is_negative() {
+            return Err("negative exponents are not supported for integers")
+        }
+        let exp: u32 = match exponent.as_i64().map(u32::try_from) {
+            Some(Ok(x)) => x,
+            _ => match self.as_i64() {
+                Some(0) | Some(1) => return Ok(self.clone()),
+                _ => return Err("exponent is too large"),
+            },
+        };
+        if let Some(x) = self.as_i64().and_then(|base| base.checked_pow(exp)) {
+            return Ok(Integer::new(x))
+        }
+        let base = self.to_big();
+        // 0, 1 and -1 fit into an i64 with every exponent, so every other base has at least 2 bits
+        if (base.bits() - 1) * exp as u64 > MAX_RESULT_BITS {
+            return Err("exponent is too large")
+        }
+        Ok(Integer::from_big(base.pow(exp)))
+    }
+    pub fn checked_shl(&self, amount: &Integer) -> Result<Integer, &'static str> {
+        if amount.is_negative() {
+            return Err("negative shift amount")
+        }
+        let bits = match amount.as_i64().map(u32::try_from) {
+            Some(Ok(x)) => x,
+            _ => match self.as_i64() {
+                Some(0) => return Ok(self.clone()),
+                _ => return Err("shift amount is too large"),
+            },
+        };
+        match self.as_i64() {
+            Some(x) if bits < 64 => {
+                let wide = (x as i128) << bits;
+                match i64::try_from(wide) {
+                    Ok(x) => Ok(Integer::new(x)),
+                    Err(_) => Ok(Integer::from_big(BigInt::from_i128(wide))),
+                }
+            },
+            _ => {
+                let value = self.to_big();
+                if !value.is_zero() && value.bits() + bits as u64 > MAX_RESULT_BITS {
+                    return Err("shift amount is too large")
+                }
+                Ok(Integer::from_big(&value << bits))
+            },
+        }
+    }
+    pub fn checked_shr(&self, amount: &Integer) -> Result<Integer, &'static str> {
+        if amount.is_negative() {
+            return Err("negative shift amount")
+        }
+        let bits = match amount.as_i64().map(u32::try_from) {
+            Some(Ok(x)) => x,
+            _ => u32::MAX,
+        };
+        match self.as_i64() {
+            Some(x) => Ok(Integer::new(x >> bits.min(63))),
+            None => Ok(Integer::from_big(&self.to_big() >> bits)),
+        }
+    }
+}
+impl Add for &Integer {
+    type Output = Integer;
+    fn add(self, other: &Integer) -> Integer {
+        match (self.as_i64(), other.as_i64()) {
+            (Some(l), Some(r)) => match l.checked_add(r) {
+                Some(x) => Integer::new(x),
+                None => Integer::from_big(BigInt::from_i128(l as i128 + r as i128)),
+            },
+            _ => Integer::from_big(&self.to_big() + &other.to_big()),
+        }
+    }
+}
+impl Sub for &Integer {
+    type Output = Integer;
+    fn sub(self, other: &Integer) -> Integer {
+        match (self.as_i64(), other.as_i64()) {
+            (Some(l), Some(r)) => match l.checked_sub(r) {
+                Some(x) => Integer::new(x),
+                None => Integer::from_big(BigInt::from_i128(l as i128 - r as i128)),
+            },
+            _ => Integer::from_big(&self.to_big() - &other.to_big()),
+        }
+    }
+}
+impl Mul for &Integer {
+    type Output = Integer;
+    fn mul(self, other: &Integer) -> Integer {
+        match (self.as_i64(), other.as_i64()) {
+            (Some(l), Some(r)) => match l.checked_mul(r) {
+                Some(x) => Integer::new(x),
+                None => Integer::from_big(BigInt::from_i128(l as i128 * r as i128)),
+            },
+            _ => Integer::from_big(&self.to_big() * &other.to_big()),
+        }
+    }
+}
+impl Neg for &Integer {
+    type Output = Integer;
+    fn neg(self) -> Integer {
+        match &self.value {
+            IntegerValue::SMALL(x) => match x.checked_neg() {
+                Some(x) => Integer::new(x),
+                None => Integer::from_big(-&BigInt::from_i64(*x)),
+            },
+            IntegerValue::BIG(x) => Integer::from_big(-x),
+        }
+    }
+}
+impl BitAnd for &Integer {
+    type Output = Integer;
+    fn bitand(self, other: &Integer) -> Integer {
+        match (self.as_i64(), other.as_i64()) {
+            (Some(l), Some(r)) => Integer::new(l & r),
+            _ => Integer::from_big(&self.to_big() & &other.to_big()),
+        }
+    }
+}
+impl BitOr for &Integer {
+    type Output = Integer;
+    fn bitor(self, other: &Integer) -> Integer {
+        match (self.as_i64(), other.as_i64()) {
+            (Some(l), Some(r)) => Integer::new(l | r),
+            _ => Integer::from_big(&self.to_big() | &other.to_big()),
+        }
+    }
+}
+impl Not for &Integer {
+    type Output = Integer;
+    fn not(self) -> Integer {
+        match &self.value {
+            IntegerValue::SMALL(x) => Integer::new(!x),
+            IntegerValue::BIG(x) => Integer::from_big(!x),
+        }
+    }
+}
+impl Ord for Integer {
+    fn cmp(&self, other: &Integer) -> Ordering {
+        match (&self.value, &other.value) {
+            (IntegerValue::SMALL(l), IntegerValue::SMALL(r)) => l.cmp(r),
+            _ => self.to_big().cmp(&other.to_big()),
+        }
+    }
+}
+impl PartialOrd for Integer {
+    fn partial_cmp(&self, other: &Integer) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 impl Object for Integer {}
 
 impl ObjectInterface for Integer {
+    fn inspect(&self) -> String {
+        match &self.value {
+            IntegerValue::SMALL(x) => x.to_string(),
+            IntegerValue::BIG(x) => x.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Str {
+    pub value: Rc<str>
+}
+impl Str {
+    pub fn new(val: String) -> Str {
+        Str { value: Rc::from(val) }
+    }
+}
+impl Object for Str {}
+
+impl ObjectInterface for Str {
     fn inspect(&self) -> String {
         self.value.to_string()
     }
@@ -72,80 +350,297 @@ impl ObjectInterface for Null {
         String::from("Null")
     }
 }
-#[derive(Debug, PartialEq, Clone)]
-pub struct Block {
-    pub statements: Vec<MonkeyObject>,
-}
-impl Object for Block {}
-impl ObjectInterface for Block {
-    fn inspect(&self) -> String {
-        String::from("Number of Statements in block:{self.statements.len()}")
-    }
-}
-
 #[derive(Debug, PartialEq,Clone)]
 pub struct ReturnValue {
     pub value: Box<MonkeyObject>
 }
 impl ReturnValue {
     pub fn new(value: Box<MonkeyObject>) -> ReturnValue {
-        ReturnValue { value: value }
+        ReturnValue { value }
     }
 }
 impl Object for ReturnValue {}
 impl ObjectInterface for ReturnValue {
     fn inspect(&self) -> String {
-        String::from("dummy")
+        self.value.inspect()
     }
 }
-#[derive(Debug, PartialEq,Clone)]
+#[derive(Clone)]
 pub struct Function  {
-    pub params: Vec<Identifier>,
-    pub body: BlockStatement,
+    pub literal: Rc<FunctionLiteral>,
     pub env: Rc<RefCell<Environment>>
 }
 impl Function {
-    pub fn new(params: Vec<Identifier>, body: BlockStatement, env: Rc<RefCell<Environment>>) -> Function {
-        Function { params: params, body: body, env: env }
+    pub fn new(literal: Rc<FunctionLiteral>, env: Rc<RefCell<Environment>>) -> Function {
+        Function { literal, env }
+    }
+}
+// a function usually lives in the environment it captured, so the environment is compared by identity
+// and left out of the debug output to not run in circles
+impl PartialEq for Function {
+    fn eq(&self, other: &Function) -> bool {
+        self.literal == other.literal && Rc::ptr_eq(&self.env, &other.env)
+    }
+}
+impl Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Function").field("params", &self.literal.parameters).field("body", &self.literal.blockstatment).finish_non_exhaustive()
     }
 }
 impl Object for Function {}
 impl ObjectInterface for Function {
     fn inspect(&self) -> String {
-        String::from("dummy")
+        let params: Vec<String> = self.literal.parameters.iter().map(|x| x.value.to_string()).collect();
+        format!("fn({}) {{...}}", params.join(", "))
     }
 }
 
-#[derive(Debug, PartialEq,Clone)]
+pub type BuiltinFunction = fn(Vec<MonkeyObject>) -> Result<MonkeyObject, EvalError>;
+
+// a function implemented in rust, see builtins.rs
+#[derive(Debug, Clone)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub func: BuiltinFunction,
+}
+impl Builtin {
+    pub fn new(name: &'static str, func: BuiltinFunction) -> Builtin {
+        Builtin { name, func }
+    }
+}
+// builtin names are unique, comparing the function pointers would not be reliable
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Builtin) -> bool {
+        self.name == other.name
+    }
+}
+impl Object for Builtin {}
+impl ObjectInterface for Builtin {
+    fn inspect(&self) -> String {
+        format!("builtin {}", self.name)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Array {
+    pub elements: Rc<Vec<MonkeyObject>>,
+}
+impl Array {
+    pub fn new(elements: Vec<MonkeyObject>) -> Array {
+        Array { elements: Rc::new(elements) }
+    }
+    // the elements are only copied if another value still shares them
+    pub fn elements_mut(&mut self) -> &mut Vec<MonkeyObject> {
+        Rc::make_mut(&mut self.elements)
+    }
+    // takes the elements out without copying them if this is the only array that uses them
+    pub fn into_elements(self) -> Vec<MonkeyObject> {
+        Rc::try_unwrap(self.elements).unwrap_or_else(|x| x.as_ref().clone())
+    }
+}
+impl Object for Array {}
+impl ObjectInterface for Array {
+    fn inspect(&self) -> String {
+        let elements: Vec<String> = self.elements.iter().map(|x| x.inspect()).collect();
+        format!("[{}]", elements.join(", "))
+    }
+}
+
+// only integers, strings and booleans can be used as keys of a hash
+#[derive(Debug, PartialEq, Eq, Clone, Hash, PartialOrd, Ord)]
+pub enum HashKey {
+    INTEGER(Integer),
+    STRING(Rc<str>),
+    BOOLEAN(bool),
+}
+impl HashKey {
+    pub fn from_object(obj: &MonkeyObject) -> Result<HashKey, EvalError> {
+        match obj {
+            MonkeyObject::INTEGER(x) => Ok(HashKey::INTEGER(x.clone())),
+            MonkeyObject::STRING(x) => Ok(HashKey::STRING(x.value.clone())),
+            MonkeyObject::BOOLEAN(x) => Ok(HashKey::BOOLEAN(x.value)),
+            other => Err(EvalError::new(format!("unusable as hash key: {}", other.type_name()))),
+        }
+    }
+    pub fn to_object(&self) -> MonkeyObject {
+        match self {
+            HashKey::INTEGER(x) => MonkeyObject::INTEGER(x.clone()),
+            HashKey::STRING(x) => MonkeyObject::STRING(Str { value: Rc::clone(x) }),
+            HashKey::BOOLEAN(x) => MonkeyObject::BOOLEAN(Bool::new(*x)),
+        }
+    }
+}
+
+// the pairs are sorted by key, so keys() and values() always return them in the same order
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Hash {
+    pub pairs: Rc<BTreeMap<HashKey, MonkeyObject>>,
+}
+impl Hash {
+    pub fn new(pairs: BTreeMap<HashKey, MonkeyObject>) -> Hash {
+        Hash { pairs: Rc::new(pairs) }
+    }
+    // the pairs are only copied if another value still shares them
+    pub fn pairs_mut(&mut self) -> &mut BTreeMap<HashKey, MonkeyObject> {
+        Rc::make_mut(&mut self.pairs)
+    }
+}
+impl Object for Hash {}
+impl ObjectInterface for Hash {
+    fn inspect(&self) -> String {
+        let pairs: Vec<String> = self.pairs.iter().map(|(key, value)| format!("{}: {}", key.to_object().inspect(), value.inspect())).collect();
+        format!("{{{}}}", pairs.join(", "))
+    }
+}
+
+// the root environment of a module, only the exported bindings are visible from the outside
+#[derive(Clone)]
+pub struct Module {
+    pub path: PathBuf,
+    pub env: Rc<RefCell<Environment>>,
+}
+impl Module {
+    pub fn new(path: PathBuf, env: Rc<RefCell<Environment>>) -> Module {
+        Module { path, env }
+    }
+    pub fn get(&self, key: Symbol) -> Option<MonkeyObject> {
+        self.env.borrow().get_export(key)
+    }
+}
+impl PartialEq for Module {
+    fn eq(&self, other: &Module) -> bool {
+        Rc::ptr_eq(&self.env, &other.env)
+    }
+}
+impl Debug for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Module").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+impl Object for Module {}
+impl ObjectInterface for Module {
+    fn inspect(&self) -> String {
+        format!("module({})", self.path.display())
+    }
+}
+
+// the file a module was loaded from (None if it did not come from a file) and the loader shared by all modules
+#[derive(Debug, Clone)]
+pub struct ModuleContext {
+    pub path: Option<PathBuf>,
+    pub loader: Rc<RefCell<ModuleLoader>>,
+}
+
+#[derive(Debug, Clone)]
 pub struct Environment {
-    store: HashMap<String, MonkeyObject>,
+    store: HashMap<Symbol, MonkeyObject>,
+    // the locals of a function call, see resolver.rs. names are the names of the slots
+    slots: Vec<Option<MonkeyObject>>,
+    names: Rc<Vec<Symbol>>,
     pub outer: Option<Rc<RefCell<Environment>>>,
+    exports: HashSet<Symbol>,
+    module: Option<ModuleContext>,
+}
+impl Default for Environment {
+    fn default() -> Self {
+        Environment::new()
+    }
 }
 impl Environment {
+    // a root environment with its own module loader, imports are resolved from the working directory
     pub fn new() -> Environment {
-        Environment { store: HashMap::new(), outer: None }
+        let context = ModuleContext { path: None, loader: Rc::new(RefCell::new(ModuleLoader::new(Vec::new()))) };
+        Environment { store: HashMap::new(), slots: Vec::new(), names: Rc::default(), outer: None, exports: HashSet::new(), module: Some(context) }
+    }
+    // the root environment of a module. the bindings of the prelude are visible from it, but can be shadowed
+    pub fn new_module(path: Option<PathBuf>, loader: Rc<RefCell<ModuleLoader>>, prelude: Option<Rc<RefCell<Environment>>>) -> Environment {
+        let context = ModuleContext { path, loader };
+        Environment { store: HashMap::new(), slots: Vec::new(), names: Rc::default(), outer: prelude, exports: HashSet::new(), module: Some(context) }
+    }
+    // an environment that is not part of any module and can not import, used for the prelude
+    pub fn from_store(store: HashMap<Symbol, MonkeyObject>) -> Environment {
+        Environment { store, slots: Vec::new(), names: Rc::default(), outer: None, exports: HashSet::new(), module: None }
+    }
+    pub fn new_enclosed_evironment(hashmap: HashMap<Symbol, MonkeyObject>, env: Rc<RefCell<Environment>>) -> Environment {
+        Environment{ store: hashmap, slots: Vec::new(), names: Rc::default(), outer: Some(Rc::clone(&env)), exports: HashSet::new(), module: None }
     }
-    pub fn new_enclosed_evironment(hashmap: HashMap<String, MonkeyObject>, env: Rc<RefCell<Environment>>) -> Environment {
-        Environment{ store: hashmap, outer: Some(Rc::clone(&env))}
+    // the environment of a function call, there is one slot for every name in names
+    pub fn new_call_environment(names: Rc<Vec<Symbol>>, slots: Vec<Option<MonkeyObject>>, env: Rc<RefCell<Environment>>) -> Environment {
+        Environment{ store: HashMap::new(), slots, names, outer: Some(env), exports: HashSet::new(), module: None }
     }
-    pub fn get(&self, key: &String) -> Option<MonkeyObject> {
+    pub fn get(&self, key: Symbol) -> Option<MonkeyObject> {
+        if let Some(x) = self.store.get(&key) {
+            return Some(x.clone())
+        }
+        if let Some(Some(x)) = self.names.iter().rposition(|x| *x == key).map(|slot| &self.slots[slot]) {
+            return Some(x.clone())
+        }
         match &self.outer {
-            Some(x) => {
-                match x.borrow().get(key) {
-                    Some(monkeyobj) => Some(monkeyobj.clone()),
-                    None => match self.store.get(key) {
-                        Some(x) => Some(x.clone()),
-                        None => None,
-                    }
-                }
-            },
-            None => match self.store.get(key) {
-                Some(x) => Some(x.clone()),
+            Some(x) => x.borrow().get(key),
+            None => None,
+        }
+    }
+    // reads a slot the resolver found, if it was not set yet the name is looked up outside of its function
+    pub fn get_resolved(&self, resolved: Resolved, key: Symbol) -> Option<MonkeyObject> {
+        if resolved.depth > 0 {
+            return match &self.outer {
+                Some(x) => x.borrow().get_resolved(Resolved { depth: resolved.depth - 1, ..resolved }, key),
                 None => None,
             }
         }
+        match self.slots.get(resolved.slot) {
+            Some(Some(x)) => Some(x.clone()),
+            _ => match &self.outer {
+                Some(x) => x.borrow().get(key),
+                None => None,
+            },
+        }
     }
-    pub fn set(&mut self, key: String, value: MonkeyObject) {
+    pub fn set(&mut self, key: Symbol, value: MonkeyObject) {
         self.store.insert(key, value);
     }
+    pub fn set_slot(&mut self, slot: usize, value: MonkeyObject) {
+        self.slots[slot] = Some(value);
+    }
+    // takes the value out of a slot that is not read again, see Resolved::last_use
+    pub fn take_slot(&mut self, slot: usize) -> Option<MonkeyObject> {
+        self.slots.get_mut(slot).and_then(Option::take)
+    }
+    pub fn export(&mut self, key: Symbol) {
+        self.exports.insert(key);
+    }
+    pub fn get_export(&self, key: Symbol) -> Option<MonkeyObject> {
+        if self.exports.contains(&key) {
+            self.store.get(&key).cloned()
+        } else {
+            None
+        }
+    }
+    // calls visit with the outer environment and everything the bindings hold an Rc to
+    pub fn each_reference(&self, visit: &mut dyn FnMut(HeapRef)) {
+        if let Some(outer) = &self.outer {
+            visit(HeapRef::ENVIRONMENT(outer));
+        }
+        for value in self.store.values() {
+            value.each_reference(visit);
+        }
+        for value in self.slots.iter().flatten() {
+            value.each_reference(visit);
+        }
+    }
+    // drops all bindings and the outer environment, the garbage collector uses it to break cycles
+    pub fn clear(&mut self) {
+        self.store.clear();
+        self.slots.clear();
+        self.exports.clear();
+        self.outer = None;
+    }
+    // the module context of the closest enclosing module environment
+    pub fn module_context(&self) -> Option<ModuleContext> {
+        match (&self.module, &self.outer) {
+            (Some(x), _) => Some(x.clone()),
+            (None, Some(outer)) => outer.borrow().module_context(),
+            (None, None) => None,
+        }
+    }
 }
\ No newline at end of file
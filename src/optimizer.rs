@@ -0,0 +1,188 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::*;
+use crate::evaluator::eval_expr;
+use crate::object::{Environment, MonkeyObject};
+use crate::scope::{self, DefinitionKind};
+use crate::symbol::Symbol;
+use crate::token::*;
+
+// rewrites a program into one that evaluates to the same results with less work:
+// operators on literals are computed once, ifs with a constant condition are replaced by the branch
+// that is taken and names that a let binds to a literal exactly once are replaced by the literal.
+// an expression that fails, like a division by zero, is left as it is so the error still happens at runtime
+pub fn optimize(mut program: Programm) -> Programm {
+    let mut optimizer = Optimizer { scopes: Vec::new() };
+    optimizer.push_scope(&[], &program.statements);
+    optimizer.visit_program_mut(&mut program);
+    program
+}
+
+struct Scope {
+    // the names defined in the scope, true if the only definition is a let that always runs
+    definitions: HashMap<Symbol, bool>,
+    constants: HashMap<Symbol, MonkeyExpression>,
+}
+
+struct Optimizer {
+    scopes: Vec<Scope>,
+}
+impl Optimizer {
+    fn push_scope(&mut self, parameters: &[Identifier], statements: &[Statement]) {
+        let mut definitions: HashMap<Symbol, bool> = HashMap::new();
+        for definition in scope::definitions(parameters, statements) {
+            let is_let = matches!(definition.kind, DefinitionKind::LET(_) | DefinitionKind::EXPORT(_));
+            let only = is_let && !definition.conditional && !definitions.contains_key(&definition.name.value);
+            definitions.insert(definition.name.value, only);
+        }
+        self.scopes.push(Scope { definitions, constants: HashMap::new() });
+    }
+
+    // the literal of a name if the closest scope defining the name has it as a constant.
+    // a constant is only known after its let was visited, so uses above the let keep the name
+    // an if statement evaluates the taken block in the environment it is in, so the statements of the block
+    // take the place of the if. only an empty block at the end stays an if, there it makes the statements give null
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        let count = statements.len();
+        for (i, mut statement) in std::mem::take(statements).into_iter().enumerate() {
+            self.visit_statement_mut(&mut statement);
+            if let Statement::EXPRESSION(x) = &mut statement {
+                if let MonkeyExpression::IF(if_expr) = &mut x.expression {
+                    if let Some(branch) = take_branch(if_expr) {
+                        if !branch.statements.is_empty() || i + 1 < count {
+                            statements.extend(branch.statements);
+                            continue
+                        }
+                        if_expr.consequence = branch;
+                        if_expr.alternative = None;
+                    }
+                }
+            }
+            statements.push(statement);
+        }
+    }
+
+    fn constant(&self, name: Symbol) -> Option<MonkeyExpression> {
+        for scope in self.scopes.iter().rev() {
+            if scope.definitions.contains_key(&name) {
+                return scope.constants.get(&name).cloned()
+            }
+        }
+        None
+    }
+}
+impl VisitorMut for Optimizer {
+    fn visit_program_mut(&mut self, program: &mut Programm) {
+        self.visit_statements(&mut program.statements);
+    }
+
+    fn visit_block_statement_mut(&mut self, block: &mut BlockStatement) {
+        self.visit_statements(&mut block.statements);
+    }
+
+    fn visit_let_statement_mut(&mut self, statement: &mut LetStatement) {
+        self.visit_expression_mut(&mut statement.value);
+        if !is_literal(&statement.value) {
+            return
+        }
+        if let Some(scope) = self.scopes.last_mut() {
+            if scope.definitions.get(&statement.name.value) == Some(&true) {
+                scope.constants.insert(statement.name.value, statement.value.clone());
+            }
+        }
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut MonkeyExpression) {
+        if let MonkeyExpression::IDENT(x) = expression {
+            if let Some(constant) = self.constant(x.value) {
+                *expression = constant;
+            }
+            return
+        }
+        walk_expression_mut(self, expression);
+        if let Some(folded) = fold(expression) {
+            *expression = folded;
+        }
+    }
+
+    fn visit_function_literal_mut(&mut self, expression: &mut FunctionLiteral) {
+        self.push_scope(&expression.parameters, &expression.blockstatment.statements);
+        self.visit_statements(&mut expression.blockstatment.statements);
+        self.scopes.pop();
+    }
+}
+
+// the expressions that are already as simple as they get, negative numbers are written as -x
+fn is_literal(expression: &MonkeyExpression) -> bool {
+    match expression {
+        MonkeyExpression::INTEGERLITERAL(_) | MonkeyExpression::BOOLEAN(_) | MonkeyExpression::STRINGLITERAL(_) => true,
+        MonkeyExpression::PREFIX(x) => x.operator == "-" && matches!(*x.right, MonkeyExpression::INTEGERLITERAL(_)),
+        _ => false,
+    }
+}
+
+fn fold(expression: &MonkeyExpression) -> Option<MonkeyExpression> {
+    let span = match expression {
+        MonkeyExpression::PREFIX(x) if is_literal(&x.right) && !is_literal(expression) => x.token.span,
+        MonkeyExpression::INFIX(x) if is_literal(&x.left) && is_literal(&x.right) => x.token.span,
+        MonkeyExpression::IF(x) => return taken_expression(x),
+        _ => return None,
+    };
+    // the evaluator computes the value, so the optimized program can not disagree with it
+    let env = Rc::new(RefCell::new(Environment::from_store(HashMap::new())));
+    match eval_expr(expression, env) {
+        Ok(value) => literal(value, span),
+        Err(_) => None,
+    }
+}
+
+// integers are only written back if they fit into a literal, strings are not folded so they do not grow
+fn literal(value: MonkeyObject, span: Span) -> Option<MonkeyExpression> {
+    let token = |tok_type: TokenType, literal: &str| {
+        let mut tok = Token::new(tok_type, literal);
+        tok.span = span;
+        tok
+    };
+    match value {
+        MonkeyObject::INTEGER(x) => {
+            let value = x.as_i64()?;
+            let literal = MonkeyExpression::INTEGERLITERAL(IntegerLiteral::new(token(TokenType::INT, &value.checked_abs()?.to_string()), value.abs()));
+            if value < 0 {
+                Some(MonkeyExpression::PREFIX(PrefixExpression::new(token(TokenType::MINUS, "-"), String::from("-"), literal)))
+            } else {
+                Some(literal)
+            }
+        },
+        MonkeyObject::BOOLEAN(x) => {
+            let tok_type = if x.value { TokenType::TRUE } else { TokenType::FALSE };
+            Some(MonkeyExpression::BOOLEAN(Boolean::new(token(tok_type, &x.value.to_string()), x.value)))
+        },
+        _ => None,
+    }
+}
+
+// the block an if with a constant condition evaluates, taken out of the if
+fn take_branch(expression: &mut IfExpression) -> Option<BlockStatement> {
+    let empty = BlockStatement::new(expression.consequence.token.clone(), Vec::new());
+    match expression.condition.as_ref() {
+        MonkeyExpression::BOOLEAN(x) if x.value => Some(std::mem::replace(&mut expression.consequence, empty)),
+        MonkeyExpression::BOOLEAN(_) => Some(expression.alternative.take().unwrap_or(empty)),
+        _ => None,
+    }
+}
+
+// inside of an expression an if can only be replaced if the taken block is a single expression,
+// a let in the block would end up in the environment around it
+fn taken_expression(expression: &IfExpression) -> Option<MonkeyExpression> {
+    let branch = match expression.condition.as_ref() {
+        MonkeyExpression::BOOLEAN(x) if x.value => &expression.consequence,
+        MonkeyExpression::BOOLEAN(_) => expression.alternative.as_ref()?,
+        _ => return None,
+    };
+    match branch.statements.as_slice() {
+        [Statement::EXPRESSION(x)] => Some(x.expression.clone()),
+        _ => None,
+    }
+}
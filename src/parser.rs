@@ -1,38 +1,205 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::num::IntErrorKind;
+use std::rc::Rc;
+
+use crate::cst::{SyntaxEvent, SyntaxKind};
+use crate::lexer::TokenSource;
+use crate::resolver;
+use crate::ast::{MonkeyExpression, Programm, Statement, TypeAnnotation};
+use crate::token::{ArrayLiteral, BlockStatement, Boolean, CallExpression, ExportStatement, ExpressionStatement, FunctionLiteral, HashLiteral, Identifier, IfExpression, ImportStatement, IndexExpression, InfixExpression, IntegerLiteral, LetStatement, MemberExpression, PrefixExpression, ReturnStatement, StringLiteral, Token, TokenType};
+
+type PrefixParseFn<'a, B> = fn(&mut Parser<'a, B>) -> Result<<B as AstBuilder>::Expression, ParseError>;
+type InfixParseFn<'a, B> = fn(&mut Parser<'a, B>, <B as AstBuilder>::Expression) -> Result<<B as AstBuilder>::Expression, ParseError>;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    pub message: String,
+    pub token: Token,
+}
+impl ParseError {
+    pub fn new(message: String, token: Token) -> ParseError {
+        ParseError { message, token }
+    }
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at line {}, column {} (token: {:?} \"{}\")", self.message, self.token.span.line, self.token.span.column, self.token.tokentype, self.token.literal)
+    }
+}
+
+// the parser hands every node it finished to a builder, which decides how the nodes are stored.
+// TreeBuilder puts them together into the ast of ast.rs, arena::Ast keeps them in vectors and hands out ids
+pub trait AstBuilder {
+    type Expression;
+    type Statement;
+    type Let;
+    type Block;
+    type Program;
+
+    fn identifier(&mut self, identifier: Identifier) -> Self::Expression;
+    fn integer_literal(&mut self, literal: IntegerLiteral) -> Self::Expression;
+    fn string_literal(&mut self, literal: StringLiteral) -> Self::Expression;
+    fn boolean(&mut self, boolean: Boolean) -> Self::Expression;
+    // the operator is the literal of the token
+    fn prefix_expression(&mut self, tok: Token, right: Self::Expression) -> Self::Expression;
+    fn infix_expression(&mut self, tok: Token, left: Self::Expression, right: Self::Expression) -> Self::Expression;
+    fn if_expression(&mut self, tok: Token, condition: Self::Expression, consequence: Self::Block, alternative: Option<Self::Block>) -> Self::Expression;
+    fn function_literal(&mut self, tok: Token, params: Vec<Identifier>, parameter_types: Vec<Option<TypeAnnotation>>, return_type: Option<TypeAnnotation>, body: Self::Block) -> Self::Expression;
+    fn call_expression(&mut self, tok: Token, function: Self::Expression, arguments: Option<Vec<Self::Expression>>) -> Self::Expression;
+    fn member_expression(&mut self, tok: Token, object: Self::Expression, property: Identifier) -> Self::Expression;
+    fn array_literal(&mut self, tok: Token, elements: Vec<Self::Expression>) -> Self::Expression;
+    fn hash_literal(&mut self, tok: Token, pairs: Vec<(Self::Expression, Self::Expression)>) -> Self::Expression;
+    fn index_expression(&mut self, tok: Token, left: Self::Expression, index: Self::Expression) -> Self::Expression;
+
+    // a let is a statement of its own or the statement inside of an export
+    fn let_binding(&mut self, tok: Token, name: Identifier, annotation: Option<TypeAnnotation>, value: Self::Expression) -> Self::Let;
+    fn let_statement(&mut self, statement: Self::Let) -> Self::Statement;
+    fn export_statement(&mut self, tok: Token, statement: Self::Let) -> Self::Statement;
+    fn return_statement(&mut self, tok: Token, value: Self::Expression) -> Self::Statement;
+    fn expression_statement(&mut self, tok: Token, expression: Self::Expression) -> Self::Statement;
+    fn import_statement(&mut self, statement: ImportStatement) -> Self::Statement;
+    fn block_statement(&mut self, tok: Token, statements: Vec<Self::Statement>) -> Self::Block;
+    // called once at the end with the top level statements, there is at least one
+    fn program(&mut self, statements: Vec<Self::Statement>) -> Self::Program;
+}
+
+// builds the ast of ast.rs, every child expression gets its own box
+pub struct TreeBuilder;
 
-use crate::lexer::Lexer;
-use crate::ast::{MonkeyExpr, MonkeyExpression, Programm, Statement};
-use crate::token::{BlockStatement, Boolean, CallExpression, ExpressionStatement, FunctionLiteral, Identifier, IfExpression, InfixExpression, IntegerLiteral, LetStatement, PrefixExpression, ReturnStatement, Token, TokenType};
+impl AstBuilder for TreeBuilder {
+    type Expression = MonkeyExpression;
+    type Statement = Statement;
+    type Let = LetStatement;
+    type Block = BlockStatement;
+    type Program = Programm;
 
-pub struct Parser<'a> {
-    lexer: &'a mut Lexer,
+    fn identifier(&mut self, identifier: Identifier) -> MonkeyExpression {
+        MonkeyExpression::IDENT(identifier)
+    }
+    fn integer_literal(&mut self, literal: IntegerLiteral) -> MonkeyExpression {
+        MonkeyExpression::INTEGERLITERAL(literal)
+    }
+    fn string_literal(&mut self, literal: StringLiteral) -> MonkeyExpression {
+        MonkeyExpression::STRINGLITERAL(literal)
+    }
+    fn boolean(&mut self, boolean: Boolean) -> MonkeyExpression {
+        MonkeyExpression::BOOLEAN(boolean)
+    }
+    fn prefix_expression(&mut self, tok: Token, right: MonkeyExpression) -> MonkeyExpression {
+        MonkeyExpression::PREFIX(PrefixExpression::new(tok.clone(), tok.literal.to_string(), right))
+    }
+    fn infix_expression(&mut self, tok: Token, left: MonkeyExpression, right: MonkeyExpression) -> MonkeyExpression {
+        MonkeyExpression::INFIX(InfixExpression::new(tok.literal.to_string(), tok, left, right))
+    }
+    fn if_expression(&mut self, tok: Token, condition: MonkeyExpression, consequence: BlockStatement, alternative: Option<BlockStatement>) -> MonkeyExpression {
+        MonkeyExpression::IF(IfExpression::new(tok, Box::new(condition), consequence, alternative))
+    }
+    fn function_literal(&mut self, tok: Token, params: Vec<Identifier>, parameter_types: Vec<Option<TypeAnnotation>>, return_type: Option<TypeAnnotation>, body: BlockStatement) -> MonkeyExpression {
+        let mut function = FunctionLiteral::new(tok, params, body);
+        function.parameter_types = parameter_types;
+        function.return_type = return_type;
+        MonkeyExpression::FUNCTIONLITERAL(Rc::new(function))
+    }
+    fn call_expression(&mut self, tok: Token, function: MonkeyExpression, arguments: Option<Vec<MonkeyExpression>>) -> MonkeyExpression {
+        MonkeyExpression::CALL(CallExpression::new(tok, function, arguments))
+    }
+    fn member_expression(&mut self, tok: Token, object: MonkeyExpression, property: Identifier) -> MonkeyExpression {
+        MonkeyExpression::MEMBER(MemberExpression::new(tok, object, property))
+    }
+    fn array_literal(&mut self, tok: Token, elements: Vec<MonkeyExpression>) -> MonkeyExpression {
+        MonkeyExpression::ARRAY(ArrayLiteral::new(tok, elements))
+    }
+    fn hash_literal(&mut self, tok: Token, pairs: Vec<(MonkeyExpression, MonkeyExpression)>) -> MonkeyExpression {
+        MonkeyExpression::HASH(HashLiteral::new(tok, pairs))
+    }
+    fn index_expression(&mut self, tok: Token, left: MonkeyExpression, index: MonkeyExpression) -> MonkeyExpression {
+        MonkeyExpression::INDEX(IndexExpression::new(tok, left, index))
+    }
+    fn let_binding(&mut self, tok: Token, name: Identifier, annotation: Option<TypeAnnotation>, value: MonkeyExpression) -> LetStatement {
+        let mut statement = LetStatement::new(tok, name, value);
+        statement.annotation = annotation;
+        statement
+    }
+    fn let_statement(&mut self, statement: LetStatement) -> Statement {
+        Statement::LET(statement)
+    }
+    fn export_statement(&mut self, tok: Token, statement: LetStatement) -> Statement {
+        Statement::EXPORT(ExportStatement::new(tok, statement))
+    }
+    fn return_statement(&mut self, tok: Token, value: MonkeyExpression) -> Statement {
+        Statement::RETURN(ReturnStatement::new(tok, value))
+    }
+    fn expression_statement(&mut self, tok: Token, expression: MonkeyExpression) -> Statement {
+        Statement::EXPRESSION(ExpressionStatement::new(tok, expression))
+    }
+    fn import_statement(&mut self, statement: ImportStatement) -> Statement {
+        Statement::IMPORT(statement)
+    }
+    fn block_statement(&mut self, tok: Token, statements: Vec<Statement>) -> BlockStatement {
+        BlockStatement::new(tok, statements)
+    }
+    fn program(&mut self, statements: Vec<Statement>) -> Programm {
+        let mut programm = Programm { statements };
+        resolver::resolve(&mut programm);
+        programm
+    }
+}
+
+// expressions and types nested deeper than this are an error, so a deeply nested input can not overflow the stack
+pub const MAX_NESTING: usize = 256;
+
+// with record_syntax the parser also writes down where every node of the concrete syntax tree
+// starts and ends, see cst.rs. curr_recorded tells if curr_token already ended up in the events.
+// nesting counts the expressions and types the parser is inside of
+pub struct Parser<'a, B: AstBuilder = TreeBuilder> {
+    lexer: &'a mut dyn TokenSource,
+    builder: B,
     curr_token: Token,
     peek_token: Token,
-    prefix_parse_fns: HashMap<TokenType, fn(&mut Parser<'a>) -> Result<MonkeyExpression, &'static str>>,
-    infix_parse_fns: HashMap<TokenType, fn(&mut Parser<'a>, left : MonkeyExpression) -> Result<MonkeyExpression, &'static str>>
+    prefix_parse_fns: HashMap<TokenType, PrefixParseFn<'a, B>>,
+    infix_parse_fns: HashMap<TokenType, InfixParseFn<'a, B>>,
+    events: Option<Vec<SyntaxEvent>>,
+    curr_recorded: bool,
+    nesting: usize,
 }
-impl<'a> Parser <'a> {
-    pub fn new(lexer: &'a mut Lexer) -> Parser<'a> {
+impl<'a> Parser<'a> {
+    pub fn new(lexer: &'a mut dyn TokenSource) -> Parser<'a> {
+        Parser::with_builder(lexer, TreeBuilder)
+    }
+}
+impl<'a, B: AstBuilder> Parser<'a, B> {
+    pub fn with_builder(lexer: &'a mut dyn TokenSource, builder: B) -> Parser<'a, B> {
         let token_1 = lexer.next_token();
         let token_2 = lexer.next_token();
         let mut p = Parser{
-            lexer: lexer,
+            lexer,
+            builder,
             curr_token: token_1,
             peek_token: token_2,
             prefix_parse_fns: HashMap::new(),
             infix_parse_fns: HashMap::new(),
+            events: None,
+            curr_recorded: false,
+            nesting: 0,
         };
         p.register_prefix_fn(TokenType::IDENT, Parser::parse_identifier);
         p.register_prefix_fn(TokenType::INT, Parser::parse_integer_literal);
+        p.register_prefix_fn(TokenType::STRING, Parser::parse_string_literal);
         p.register_prefix_fn(TokenType::BANG, Parser::parse_prefix_expression);
         p.register_prefix_fn(TokenType::MINUS, Parser::parse_prefix_expression);
+        p.register_prefix_fn(TokenType::TILDE, Parser::parse_prefix_expression);
         p.register_prefix_fn(TokenType::FALSE, Parser::parse_boolean);
         p.register_prefix_fn(TokenType::TRUE, Parser::parse_boolean);
         p.register_prefix_fn(TokenType::LPAREN, Parser::parse_grouped_expression);
         p.register_prefix_fn(TokenType::IF, Parser::parse_if_expression);
         p.register_prefix_fn(TokenType::FUNCTION, Parser::parse_function_literal);
+        p.register_prefix_fn(TokenType::LBRACKET, Parser::parse_array_literal);
+        p.register_prefix_fn(TokenType::LBRACE, Parser::parse_hash_literal);
 
         p.register_infix_fn(TokenType::LPAREN, Parser::parse_call_expression);
+        p.register_infix_fn(TokenType::DOT, Parser::parse_member_expression);
+        p.register_infix_fn(TokenType::LBRACKET, Parser::parse_index_expression);
         p.register_infix_fn(TokenType::EQ, Parser::parse_infix_expression);
         p.register_infix_fn(TokenType::NOTEQ, Parser::parse_infix_expression);
         p.register_infix_fn(TokenType::LT, Parser::parse_infix_expression);
@@ -44,353 +211,693 @@ impl<'a> Parser <'a> {
         p.register_infix_fn(TokenType::ASTERISK, Parser::parse_infix_expression);
         p.register_infix_fn(TokenType::SLASH, Parser::parse_infix_expression);
         p.register_infix_fn(TokenType::POWER, Parser::parse_infix_expression);
+        p.register_infix_fn(TokenType::AMPERSAND, Parser::parse_infix_expression);
+        p.register_infix_fn(TokenType::PIPE, Parser::parse_infix_expression);
+        p.register_infix_fn(TokenType::SHIFTLEFT, Parser::parse_infix_expression);
+        p.register_infix_fn(TokenType::SHIFTRIGHT, Parser::parse_infix_expression);
 
         p
     }
-    fn register_prefix_fn(&mut self, tok_type: TokenType, parse_func: fn(&mut Parser<'a>) -> Result<MonkeyExpression, &'static str>) {
+    fn register_prefix_fn(&mut self, tok_type: TokenType, parse_func: PrefixParseFn<'a, B>) {
         self.prefix_parse_fns.insert(tok_type, parse_func);
     }
 
-    fn register_infix_fn(&mut self, tok_type: TokenType, parse_func: fn(&mut Parser<'a>, expr: MonkeyExpression) -> Result<MonkeyExpression, &'static str>) {
+    fn register_infix_fn(&mut self, tok_type: TokenType, parse_func: InfixParseFn<'a, B>) {
         self.infix_parse_fns.insert(tok_type, parse_func);
     }
-    //alot of cloning going on here :/ -> needs to be fixed
-    pub fn parse_programm(&mut self) -> Result<Programm, &'static str> {
-        let mut programm = Programm {
-            statements: Vec::new()
-        };
-        let mut parsed_statement: Statement;
+
+    pub fn record_syntax(&mut self) {
+        self.events = Some(Vec::new());
+    }
+
+    pub fn take_syntax_events(&mut self) -> Vec<SyntaxEvent> {
+        self.events.take().unwrap_or_default()
+    }
+
+    fn start_node(&mut self, kind: SyntaxKind) {
+        if let Some(events) = &mut self.events {
+            events.push(SyntaxEvent::START(kind));
+        }
+    }
+
+    fn checkpoint(&self) -> usize {
+        match &self.events {
+            Some(events) => events.len(),
+            None => 0,
+        }
+    }
+
+    // starts a node that encloses everything recorded since the checkpoint, used for the left side of infix expressions
+    fn start_node_at(&mut self, checkpoint: usize, kind: SyntaxKind) {
+        if let Some(events) = &mut self.events {
+            events.insert(checkpoint, SyntaxEvent::START(kind));
+        }
+    }
+
+    // every parse function stops on the last token of its node, so that token still belongs to the node
+    fn finish_node(&mut self) {
+        self.record_curr_token();
+        if let Some(events) = &mut self.events {
+            events.push(SyntaxEvent::FINISH);
+        }
+    }
+
+    fn record_curr_token(&mut self) {
+        if let (Some(events), false) = (&mut self.events, self.curr_recorded) {
+            events.push(SyntaxEvent::TOKEN(self.curr_token.clone()));
+        }
+        self.curr_recorded = true;
+    }
+    // stops at the first error, the parser still reads the rest to look for more but they are dropped
+    pub fn parse_programm(&mut self) -> Result<B::Program, ParseError> {
+        self.parse_programm_recovering().map_err(|mut errors| errors.remove(0))
+    }
+
+    // goes on after an error and returns all of them, for editors that show every error of a file at once
+    pub fn parse_programm_recovering(&mut self) -> Result<B::Program, Vec<ParseError>> {
+        let mut statements: Vec<B::Statement> = Vec::new();
+        let mut errors: Vec<ParseError> = Vec::new();
+        self.start_node(SyntaxKind::PROGRAM);
         loop {
-            
-            parsed_statement = match self.parse_statement() {
-                Some(x) => x,
-                None => break,
-            };
+            match self.parse_statement() {
+                Ok(Some(x)) => {
+                    statements.push(x);
+                    self.next_token();
+                },
+                Ok(None) => break,
+                Err(err) => {
+                    errors.push(err);
+                    self.skip_to_next_statement();
+                },
+            }
+        }
+        self.finish_node();
+        if !errors.is_empty() {
+            return Err(errors)
+        }
+        if statements.is_empty() {
+            return Err(vec![self.error("failed to parse any statements")])
+        }
+        Ok(self.builder.program(statements))
+    }
 
-            // println!("{:#?}", parsed_statement);
-            programm.statements.push(parsed_statement);
-            let _ = &self.next_token();
+    // skips the rest of a statement that did not parse, up to the next one that starts at the beginning of a line.
+    // the statements in a block are indented, so an error in a function does not make its "}" another error
+    fn skip_to_next_statement(&mut self) {
+        self.nesting = 0;
+        while self.curr_token.tokentype != TokenType::EOF {
+            self.next_token();
+            let starts_statement = matches!(self.curr_token.tokentype, TokenType::LET | TokenType::RETURN | TokenType::IMPORT | TokenType::EXPORT | TokenType::IDENT);
+            if starts_statement && self.curr_token.span.column == 1 {
+                return
+            }
         }
-        if programm.statements.len() > 0 {
-            Ok(programm)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError::new(String::from(message), self.curr_token.clone())
+    }
+
+    fn peek_error(&self, message: &str) -> ParseError {
+        ParseError::new(String::from(message), self.peek_token.clone())
+    }
+
+    // the lexer hands out everything it could not read as an ILLEGAL token
+    fn illegal_token_error(&self) -> ParseError {
+        if self.curr_token.literal.as_str().starts_with('"') {
+            self.error("unterminated string literal")
+        } else if self.curr_token.literal.as_str().starts_with("/*") {
+            self.error("unterminated block comment")
         } else {
-            Err("failed to parse any statements")
+            self.error("illegal character")
         }
     }
 
     fn next_token(&mut self) {
+        self.record_curr_token();
         self.curr_token = self.peek_token.clone();
         self.peek_token = self.lexer.next_token();
+        self.curr_recorded = false;
     }
 
-    fn parse_statement(&mut self) -> Option<Statement> {
-        match self.curr_token.tokentype {
+    fn parse_statement(&mut self) -> Result<Option<B::Statement>, ParseError> {
+        let kind = match self.curr_token.tokentype {
+            TokenType::LET => SyntaxKind::LET,
+            TokenType::RETURN => SyntaxKind::RETURN,
+            TokenType::IMPORT => SyntaxKind::IMPORT,
+            TokenType::EXPORT => SyntaxKind::EXPORT,
+            TokenType::EOF => return Ok(None),
+            _ => SyntaxKind::EXPRESSION,
+        };
+        self.start_node(kind);
+        let statement = match self.curr_token.tokentype {
             TokenType::LET => {
-                match self.parse_let_statement() {
-                    Ok(x) => Some(x),
-                    Err(err) => panic!("Error parsing let statement: {}",err),
-                }
-            },
-            TokenType::RETURN => {
-                match self.parse_return_statement() {
-                    Ok(x) => Some(x),
-                    Err(err) => panic!("Error parsing return statment: {}", err),
-                }
-            }
-            TokenType::EOF => None,
-            _ => {
-                match self.parse_expression_statement() {
-                    Ok(x) => Some(x),
-                    Err(err) => panic!("Error parsing return statment: {}", err)
-                }
+                let statement = self.parse_let_statement()?;
+                self.builder.let_statement(statement)
             },
-        }
+            TokenType::RETURN => self.parse_return_statement()?,
+            TokenType::IMPORT => self.parse_import_statement()?,
+            TokenType::EXPORT => self.parse_export_statement()?,
+            _ => self.parse_expression_statement()?,
+        };
+        self.finish_node();
+        Ok(Some(statement))
     }
 
-    fn parse_let_statement(&mut self) -> Result<Statement, &'static str> {
+    fn parse_let_statement(&mut self) -> Result<B::Let, ParseError> {
         let statement_token = self.curr_token.clone();
-        let statement_name: Identifier;
-        match self.peek_token.tokentype {
+        let statement_name: Identifier = match self.peek_token.tokentype {
             TokenType::IDENT => {
                 self.next_token();
-                statement_name = Identifier::new(self.curr_token.clone(), self.curr_token.literal.clone());
+                Identifier::new(self.curr_token.clone(), self.curr_token.literal.symbol())
             },
-            _ => return Err("the name of the variable contains illegal characters or keywords"),
+            TokenType::RESERVED => return Err(self.peek_error("reserved word can not be used as a variable name")),
+            _ => return Err(self.peek_error("the name of the variable contains illegal characters or keywords")),
+        };
+
+        // let name: type = value;
+        let mut annotation: Option<TypeAnnotation> = None;
+        if self.peektoken_is(TokenType::COLON) {
+            self.next_token();
+            self.next_token();
+            annotation = Some(self.parse_type_annotation()?);
         }
 
         match self.peek_token.tokentype {
             TokenType::ASSIGN => {self.next_token(); self.next_token();},
-            _ => return Err("for assigning values to a variable a \"=\" is required "),
+            _ => return Err(self.peek_error("for assigning values to a variable a \"=\" is required")),
             
         }
-        let value = self.parse_expression(Precedence::LOWEST.into_i32());
+        let value = self.parse_expression(Precedence::LOWEST.into_i32())?;
+        self.finish_statement("let statements need to end with \";\"")?;
 
-        self.next_token();
-        
-        while self.curr_token.tokentype != TokenType::SEMICOLON {
+        Ok(self.builder.let_binding(statement_token, statement_name, annotation, value))
+    }
+
+    // int, bool, string, any, [type], {type: type} or fn(types) -> type
+    fn parse_type_annotation(&mut self) -> Result<TypeAnnotation, ParseError> {
+        self.enter_nesting()?;
+        let annotation = self.parse_type_annotation_inner();
+        self.nesting -= 1;
+        annotation
+    }
+
+    fn parse_type_annotation_inner(&mut self) -> Result<TypeAnnotation, ParseError> {
+        self.start_node(SyntaxKind::TYPE);
+        let type_token = self.curr_token.clone();
+        let annotation = match self.curr_token.tokentype {
+            TokenType::IDENT if TypeAnnotation::NAMES.contains(&self.curr_token.literal.as_str()) => {
+                TypeAnnotation::NAME(Identifier::new(type_token, self.curr_token.literal.symbol()))
+            },
+            TokenType::LBRACKET => {
+                self.next_token();
+                let element = self.parse_type_annotation()?;
+                if !self.expect_peek(TokenType::RBRACKET) {
+                    return Err(self.peek_error("Error parsing array type: missing closing ]"))
+                }
+                TypeAnnotation::ARRAY(type_token, Box::new(element))
+            },
+            TokenType::LBRACE => {
+                self.next_token();
+                let key = self.parse_type_annotation()?;
+                if !self.expect_peek(TokenType::COLON) {
+                    return Err(self.peek_error("a hash type needs a key and a value type => {<key>: <value>}"))
+                }
+                self.next_token();
+                let value = self.parse_type_annotation()?;
+                if !self.expect_peek(TokenType::RBRACE) {
+                    return Err(self.peek_error("Error parsing hash type: missing closing }"))
+                }
+                TypeAnnotation::HASH(type_token, Box::new(key), Box::new(value))
+            },
+            TokenType::FUNCTION => {
+                if !self.expect_peek(TokenType::LPAREN) {
+                    return Err(self.peek_error("a function type needs its parameter types in () => fn(<types>) -> <type>"))
+                }
+                let mut params: Vec<TypeAnnotation> = Vec::new();
+                if self.peektoken_is(TokenType::RPAREN) {
+                    self.next_token();
+                } else {
+                    self.next_token();
+                    params.push(self.parse_type_annotation()?);
+                    while self.peektoken_is(TokenType::COMMA) {
+                        self.next_token();
+                        self.next_token();
+                        params.push(self.parse_type_annotation()?);
+                    }
+                    if !self.expect_peek(TokenType::RPAREN) {
+                        return Err(self.peek_error("Error parsing function type: missing )"))
+                    }
+                }
+                if !self.expect_peek(TokenType::ARROW) {
+                    return Err(self.peek_error("a function type needs a return type => fn(<types>) -> <type>"))
+                }
+                self.next_token();
+                let result = self.parse_type_annotation()?;
+                TypeAnnotation::FUNCTION(type_token, params, Box::new(result))
+            },
+            _ => return Err(self.error("unknown type, types are int, bool, string, any, [<type>], {<type>: <type>} and fn(<types>) -> <type>")),
+        };
+        self.finish_node();
+        Ok(annotation)
+    }
+
+    // import "path" as name;
+    // import { a, b } from "path";
+    fn parse_import_statement(&mut self) -> Result<B::Statement, ParseError> {
+        let statement_token = self.curr_token.clone();
+        let mut alias: Option<Identifier> = None;
+        let mut names: Vec<Identifier> = Vec::new();
+        let path: String;
+
+        if self.expect_peek(TokenType::STRING) {
+            path = self.curr_token.literal.to_string();
+            if !self.expect_peek(TokenType::AS) {
+                return Err(self.peek_error("a module import needs a name => import \"<path>\" as <name>;"))
+            }
+            self.next_token();
+            alias = Some(self.parse_import_name()?);
+        } else if self.expect_peek(TokenType::LBRACE) {
             self.next_token();
+            names.push(self.parse_import_name()?);
+            while self.peektoken_is(TokenType::COMMA) {
+                self.next_token();
+                self.next_token();
+                names.push(self.parse_import_name()?);
+            }
+            if !self.expect_peek(TokenType::RBRACE) {
+                return Err(self.peek_error("Error parsing import: missing closing }"))
+            }
+            if !self.expect_peek(TokenType::FROM) {
+                return Err(self.peek_error("selective imports need a module => import { <names> } from \"<path>\";"))
+            }
+            if !self.expect_peek(TokenType::STRING) {
+                return Err(self.peek_error("the module path of an import needs to be a string"))
+            }
+            path = self.curr_token.literal.to_string();
+        } else {
+            return Err(self.peek_error("import needs to be followed by a module path or a list of names in {}"))
         }
 
-        Ok(Statement::LET(
-            LetStatement::new(
-                Token::new(statement_token.tokentype, statement_token.literal), 
-                statement_name,
-                value.unwrap())
-            )
-        )
+        if !self.expect_peek(TokenType::SEMICOLON) {
+            return Err(self.peek_error("import statements need to end with \";\""))
+        }
+        Ok(self.builder.import_statement(ImportStatement::new(statement_token, path, alias, names)))
+    }
+
+    fn parse_import_name(&mut self) -> Result<Identifier, ParseError> {
+        match self.curr_token.tokentype {
+            TokenType::IDENT => Ok(Identifier::new(self.curr_token.clone(), self.curr_token.literal.symbol())),
+            TokenType::RESERVED => Err(self.error("reserved word can not be used as an import name")),
+            _ => Err(self.error("imported names need to be identifiers")),
+        }
+    }
+
+    // export let name = value;
+    fn parse_export_statement(&mut self) -> Result<B::Statement, ParseError> {
+        let statement_token = self.curr_token.clone();
+        if !self.expect_peek(TokenType::LET) {
+            return Err(self.peek_error("only let statements can be exported => export let <name> = <value>;"))
+        }
+        // the doc comment is written above "export", it belongs to the let statement
+        if self.curr_token.doc.is_none() {
+            self.curr_token.doc = statement_token.doc.clone();
+        }
+        self.start_node(SyntaxKind::LET);
+        let statement = self.parse_let_statement()?;
+        self.finish_node();
+        Ok(self.builder.export_statement(statement_token, statement))
     }
 
-    fn parse_return_statement(&mut self) -> Result<Statement, &'static str> {
+    fn parse_return_statement(&mut self) -> Result<B::Statement, ParseError> {
         let statement_token = self.curr_token.clone();
         self.next_token();
-        let expression = match self.parse_expression(Precedence::LOWEST.into_i32()) {
-            Ok(x) =>x,
-            Err(err) => panic!("was not able to parse expression of return statement. Token: {:?}, Error: {}", self.curr_token, err),
-        };
-        
-        while self.curr_token.tokentype != TokenType::SEMICOLON {
-            self.next_token();
+        let expression = self.parse_expression(Precedence::LOWEST.into_i32())?;
+        self.finish_statement("return statements need to end with \";\"")?;
+        Ok(self.builder.return_statement(statement_token, expression))
+    }
+
+    // the value of a let or return ends at a ";", at the end of the input or at the "}" of its block.
+    // any other token after it is an error, skipping it would silently drop code
+    fn finish_statement(&mut self, message: &str) -> Result<(), ParseError> {
+        match self.peek_token.tokentype {
+            TokenType::SEMICOLON => {
+                self.next_token();
+                Ok(())
+            },
+            TokenType::EOF | TokenType::RBRACE => Ok(()),
+            TokenType::ILLEGAL => {
+                self.next_token();
+                Err(self.illegal_token_error())
+            },
+            _ => Err(self.peek_error(message)),
         }
-        return Ok(Statement::RETURN(ReturnStatement::new(statement_token, expression)))
     }
 
-    fn parse_expression_statement(&mut self) -> Result<Statement, &'static str> {
-        let expression = match self.parse_expression(Precedence::LOWEST.into_i32()) {
-            Ok(x) => x,
-            Err(err) => panic!("was not able to parse expression of expression statement. Token: {:?}, Error: {}", self.curr_token, err),
-        };
+    // the statement keeps the first token of the expression, so it is the same with or without the ";"
+    fn parse_expression_statement(&mut self) -> Result<B::Statement, ParseError> {
+        let statement_token = self.curr_token.clone();
+        let expression = self.parse_expression(Precedence::LOWEST.into_i32())?;
         if self.peektoken_is(TokenType::SEMICOLON) {
             self.next_token();
         }
-        Ok(Statement::EXPRESSION(ExpressionStatement::new(self.curr_token.clone(), expression)))
+        Ok(self.builder.expression_statement(statement_token, expression))
     }
 
-    fn parse_block_statement(&mut self) -> Result<BlockStatement, &'static str> {
-        let mut block_statement = BlockStatement::new(self.curr_token.clone(), Vec::new());
+    fn parse_block_statement(&mut self) -> Result<B::Block, ParseError> {
+        self.start_node(SyntaxKind::BLOCK);
+        let block_token = self.curr_token.clone();
+        let mut statements: Vec<B::Statement> = Vec::new();
         self.next_token();
         while !self.currtoken_is(TokenType::RBRACE) && !self.currtoken_is(TokenType::EOF) {
-            let statement = match self.parse_statement() {
+            match self.curr_token.tokentype {
+                TokenType::IMPORT => return Err(self.error("imports are only allowed at the top level of a file")),
+                TokenType::EXPORT => return Err(self.error("exports are only allowed at the top level of a file")),
+                _ => (),
+            }
+            let statement = match self.parse_statement()? {
                 Some(x) => x,
                 None => break,
             };
-            block_statement.statements.push(statement);
+            statements.push(statement);
             self.next_token();
         }
-        Ok(block_statement)
+        if self.currtoken_is(TokenType::EOF) {
+            return Err(self.error("Error parsing block: missing closing }"))
+        }
+        self.finish_node();
+        Ok(self.builder.block_statement(block_token, statements))
     }
 
-    fn parse_expression(&mut self, precedence: i32) -> Result<MonkeyExpression, &'static str> {
-        let mut left_expr: Result<MonkeyExpression, &'static str>;
-        
-        //eeh sketchy, im not checking if curr_token.tokentype is a key in hashmap
-        let prefix = self.prefix_parse_fns[&self.curr_token.tokentype];
+    fn parse_expression(&mut self, precedence: i32) -> Result<B::Expression, ParseError> {
+        self.enter_nesting()?;
+        let expression = self.parse_expression_inner(precedence);
+        self.nesting -= 1;
+        expression
+    }
 
-        left_expr = prefix(self);
+    fn enter_nesting(&mut self) -> Result<(), ParseError> {
+        if self.nesting >= MAX_NESTING {
+            return Err(ParseError::new(format!("nested too deeply, at most {} levels are allowed", MAX_NESTING), self.curr_token.clone()))
+        }
+        self.nesting += 1;
+        Ok(())
+    }
+
+    fn parse_expression_inner(&mut self, precedence: i32) -> Result<B::Expression, ParseError> {
+        let prefix = match self.prefix_parse_fns.get(&self.curr_token.tokentype) {
+            Some(x) => *x,
+            None if self.currtoken_is(TokenType::ILLEGAL) => return Err(self.illegal_token_error()),
+            None if self.currtoken_is(TokenType::RESERVED) => return Err(self.error("reserved word can not be used as an identifier")),
+            None => return Err(self.error("no expression can start with this token")),
+        };
+
+        let checkpoint = self.checkpoint();
+        self.start_node(SyntaxKind::prefix(&self.curr_token.tokentype));
+        let mut left_expr = prefix(self)?;
+        self.finish_node();
 
         while self.peek_token.tokentype != TokenType::SEMICOLON && precedence < self.get_precedence(true).into_i32() {
-            let infix = self.infix_parse_fns[&self.peek_token.tokentype];
+            let infix = match self.infix_parse_fns.get(&self.peek_token.tokentype) {
+                Some(x) => *x,
+                None => return Ok(left_expr),
+            };
+            self.start_node_at(checkpoint, SyntaxKind::infix(&self.peek_token.tokentype));
             self.next_token();
-            left_expr = infix(self, left_expr.unwrap());
+            left_expr = infix(self, left_expr)?;
+            self.finish_node();
         }
-        left_expr
+        Ok(left_expr)
     
     }
 
-    fn parse_grouped_expression(&mut self) -> Result<MonkeyExpression, &'static str> {
+    fn parse_grouped_expression(&mut self) -> Result<B::Expression, ParseError> {
         self.next_token();
-        let expression = self.parse_expression(Precedence::LOWEST.into_i32());
+        let expression = self.parse_expression(Precedence::LOWEST.into_i32())?;
         if !self.expect_peek(TokenType::RPAREN) {
-            return Err("Error during parsing grouped expression. Did not find closing )")
+            return Err(self.peek_error("Error during parsing grouped expression. Did not find closing )"))
         }
 
-        expression
+        Ok(expression)
 
     }
 
-    fn parse_function_literal(&mut self) -> Result<MonkeyExpression, &'static str> {
+    fn parse_function_literal(&mut self) -> Result<B::Expression, ParseError> {
         let func_tok = self.curr_token.clone();
 
         if !self.expect_peek(TokenType::LPAREN) {
-            panic!("Error parsing function: fn needs to be followed up with (). curr_token: {:#?}", self.curr_token)
+            return Err(self.peek_error("Error parsing function: fn needs to be followed up with ()"))
         }
 
-        let params = self.parse_function_parameters().unwrap();
+        let (params, parameter_types) = self.parse_function_parameters()?;
+
+        let mut return_type: Option<TypeAnnotation> = None;
+        if self.peektoken_is(TokenType::ARROW) {
+            self.next_token();
+            self.next_token();
+            return_type = Some(self.parse_type_annotation()?);
+        }
     
         if !self.expect_peek(TokenType::LBRACE) {
-            panic!("Error parsing function: missing opening bracket \"{{\" for blockstatement curr_tocken: {:#?}", self.curr_token)
+            return Err(self.peek_error("Error parsing function: missing opening bracket \"{\" for blockstatement"))
         }
 
-        let blockstatement = self.parse_block_statement().unwrap();
+        let blockstatement = self.parse_block_statement()?;
 
-        Ok(MonkeyExpression::FUNCTIONLITERAL(
-            FunctionLiteral::new(func_tok, params, blockstatement)
-        ))
+        Ok(self.builder.function_literal(func_tok, params, parameter_types, return_type, blockstatement))
     }
 
-    fn parse_function_parameters(&mut self) -> Result<Vec<Identifier>, &'static str> {
+    // the parameters and their annotations, a parameter without one gets None
+    fn parse_function_parameters(&mut self) -> Result<(Vec<Identifier>, Vec<Option<TypeAnnotation>>), ParseError> {
         let mut identifiers: Vec<Identifier> = Vec::new();
-        if self.peektoken_is(TokenType::LPAREN) {
+        let mut annotations: Vec<Option<TypeAnnotation>> = Vec::new();
+        if self.peektoken_is(TokenType::RPAREN) {
             self.next_token();
-            return Ok(identifiers)
+            return Ok((identifiers, annotations))
         }
         self.next_token();
 
-        let mut ident = Identifier::new(self.curr_token.clone(), self.curr_token.literal.clone());
-        identifiers.push(ident);
+        identifiers.push(self.parse_parameter()?);
+        annotations.push(self.parse_parameter_type()?);
 
         while self.peektoken_is(TokenType::COMMA) {
             self.next_token();
             self.next_token();
 
-            ident = Identifier::new(self.curr_token.clone(), self.curr_token.literal.clone());
-            identifiers.push(ident);
+            identifiers.push(self.parse_parameter()?);
+            annotations.push(self.parse_parameter_type()?);
         }
 
         if !self.expect_peek(TokenType::RPAREN) {
-            panic!("Error parsing function parameters: missing ). curr_token: {:#?}", self.curr_token)
+            return Err(self.peek_error("Error parsing function parameters: missing )"))
+        }
+        Ok((identifiers, annotations))
+    }
+
+    fn parse_parameter_type(&mut self) -> Result<Option<TypeAnnotation>, ParseError> {
+        if !self.peektoken_is(TokenType::COLON) {
+            return Ok(None)
+        }
+        self.next_token();
+        self.next_token();
+        Ok(Some(self.parse_type_annotation()?))
+    }
+
+    fn parse_parameter(&mut self) -> Result<Identifier, ParseError> {
+        match self.curr_token.tokentype {
+            TokenType::IDENT => Ok(Identifier::new(self.curr_token.clone(), self.curr_token.literal.symbol())),
+            TokenType::RESERVED => Err(self.error("reserved word can not be used as a parameter name")),
+            _ => Err(self.error("function parameters need to be identifiers")),
         }
-        Ok(identifiers)
     }
 
-    fn parse_if_expression(&mut self) -> Result<MonkeyExpression, &'static str> {
+    fn parse_if_expression(&mut self) -> Result<B::Expression, ParseError> {
         let if_token = self.curr_token.clone();
         if !self.expect_peek(TokenType::LPAREN) {
-           panic!("Opening braces missing. condition of If-expression needs to be in brackets => (<condition>)")
+           return Err(self.peek_error("Opening braces missing. condition of If-expression needs to be in brackets => (<condition>)"))
         }
         self.next_token();
-        let condition = self.parse_expression(Precedence::LOWEST.into_i32()).unwrap();
+        let condition = self.parse_expression(Precedence::LOWEST.into_i32())?;
         
         if !self.expect_peek(TokenType::RPAREN) {
-            panic!("Closing braces missing. condition of If-expression needs to be in brackets => (<condition>)")
+            return Err(self.peek_error("Closing braces missing. condition of If-expression needs to be in brackets => (<condition>)"))
         }
         if !self.expect_peek(TokenType::LBRACE) {
-            panic!("\"{{\" missing. the following blockstatement needs to be look like this => {{<BlockStatement>}}")
+            return Err(self.peek_error("\"{\" missing. the following blockstatement needs to be look like this => {<BlockStatement>}"))
         }
-        let consequence = self.parse_block_statement().unwrap();
-        let alternative: Option<BlockStatement>;
-        if self.peektoken_is(TokenType::ELSE) {
+        let consequence = self.parse_block_statement()?;
+        let alternative: Option<B::Block> = if self.peektoken_is(TokenType::ELSE) {
             self.next_token();
             if !self.expect_peek(TokenType::LBRACE) {
-                panic!("\"{{\" missing. the following blockstatement needs to be look like this => {{<BlockStatement>}}")
+                return Err(self.peek_error("\"{\" missing. the following blockstatement needs to be look like this => {<BlockStatement>}"))
             }
-            alternative = Some(self.parse_block_statement().unwrap());
+            Some(self.parse_block_statement()?)
         } else {
-            alternative = None;
-        }
+            None
+        };
 
-        Ok(MonkeyExpression::IF(
-            IfExpression::new(if_token, Box::new(condition), consequence, alternative)
-        ))
+        Ok(self.builder.if_expression(if_token, condition, consequence, alternative))
         
     }
 
-    fn parse_identifier(&mut self) -> Result<MonkeyExpression, &'static str> {
-        Ok(
-            MonkeyExpression::IDENT(
-                Identifier::new(self.curr_token.clone(), self.curr_token.literal.clone())
-            )
-        )
+    fn parse_identifier(&mut self) -> Result<B::Expression, ParseError> {
+        let identifier = Identifier::new(self.curr_token.clone(), self.curr_token.literal.symbol());
+        Ok(self.builder.identifier(identifier))
     }
 
-    fn parse_integer_literal(&mut self) -> Result<MonkeyExpression, &'static str> {
-        let int_val = match self.curr_token.literal.parse::<i64>() {
+    fn parse_integer_literal(&mut self) -> Result<B::Expression, ParseError> {
+        let literal = self.curr_token.literal.as_str().replace('_', "");
+        let (digits, radix) = match literal.get(..2).map(|prefix| prefix.to_ascii_lowercase()).as_deref() {
+            Some("0x") => (&literal[2..], 16),
+            Some("0o") => (&literal[2..], 8),
+            Some("0b") => (&literal[2..], 2),
+            _ => (literal.as_str(), 10),
+        };
+        let int_val = match i64::from_str_radix(digits, radix) {
             Ok(x) => x,
-            Err(err) => panic!("Parse Error! could not parse integer value: {}", err),
+            Err(err) => match err.kind() {
+                IntErrorKind::PosOverflow => return Err(self.error("integer literal is out of range for a 64-bit integer")),
+                IntErrorKind::Empty => return Err(self.error("integer literal has no digits")),
+                _ => return Err(self.error("integer literal contains an invalid digit")),
+            },
         };
-        Ok(
-            MonkeyExpression::INTEGERLITERAL(
-                IntegerLiteral::new(self.curr_token.clone(), int_val)
-            )
-        )
-    }
-
-    fn parse_boolean(&mut self) -> Result<MonkeyExpression, &'static str> {
-        Ok(
-            MonkeyExpression::BOOLEAN(
-                Boolean::new(self.curr_token.clone(), self.currtoken_is(TokenType::TRUE))
-            )
-        )
-        
+        let literal = IntegerLiteral::new(self.curr_token.clone(), int_val);
+        Ok(self.builder.integer_literal(literal))
+    }
+
+    fn parse_string_literal(&mut self) -> Result<B::Expression, ParseError> {
+        let literal = StringLiteral::new(self.curr_token.clone(), self.curr_token.literal.to_string());
+        Ok(self.builder.string_literal(literal))
     }
 
-    fn parse_prefix_expression(&mut self) -> Result<MonkeyExpression, &'static str> {
+    fn parse_boolean(&mut self) -> Result<B::Expression, ParseError> {
+        let boolean = Boolean::new(self.curr_token.clone(), self.currtoken_is(TokenType::TRUE));
+        Ok(self.builder.boolean(boolean))
+    }
+
+    fn parse_prefix_expression(&mut self) -> Result<B::Expression, ParseError> {
         let tok = self.curr_token.clone();
-        let op = self.curr_token.literal.clone();
         self.next_token();
-        let right = self.parse_expression(Precedence::PREFIX.into_i32()).unwrap();
-        Ok(MonkeyExpression::PREFIX(PrefixExpression::new(tok, op, right)))
+        let right = self.parse_expression(Precedence::PREFIX.into_i32())?;
+        Ok(self.builder.prefix_expression(tok, right))
 
     }
 
-    fn parse_infix_expression(&mut self, left: MonkeyExpression) -> Result<MonkeyExpression, &'static str> {
+    fn parse_infix_expression(&mut self, left: B::Expression) -> Result<B::Expression, ParseError> {
         let token = self.curr_token.clone();
         let precedence = self.get_precedence(false).into_i32();
         self.next_token();
-        Ok(MonkeyExpression::INFIX(
-            InfixExpression::new(
-                token.literal.clone(),
-                token,
-                left,
-                self.parse_expression(precedence).unwrap()
-            )
-        ))
+        let right = self.parse_expression(precedence)?;
+        Ok(self.builder.infix_expression(token, left, right))
+    }
+
+    fn parse_call_expression(&mut self, function: B::Expression) -> Result<B::Expression, ParseError> {
+        let tok = self.curr_token.clone();
+        let args = self.parse_call_arguments()?;
+        Ok(self.builder.call_expression(tok, function, args))
+    }
+
+    fn parse_member_expression(&mut self, object: B::Expression) -> Result<B::Expression, ParseError> {
+        let tok = self.curr_token.clone();
+        if !self.expect_peek(TokenType::IDENT) {
+            return Err(self.peek_error("\".\" needs to be followed by the name of a binding"))
+        }
+        let property = Identifier::new(self.curr_token.clone(), self.curr_token.literal.symbol());
+        Ok(self.builder.member_expression(tok, object, property))
+    }
+    fn parse_array_literal(&mut self) -> Result<B::Expression, ParseError> {
+        let tok = self.curr_token.clone();
+        let mut elements: Vec<B::Expression> = Vec::new();
+        if self.peektoken_is(TokenType::RBRACKET) {
+            self.next_token();
+            return Ok(self.builder.array_literal(tok, elements))
+        }
+        self.next_token();
+        elements.push(self.parse_expression(Precedence::LOWEST.into_i32())?);
+
+        while self.peektoken_is(TokenType::COMMA) {
+            self.next_token();
+            self.next_token();
+            elements.push(self.parse_expression(Precedence::LOWEST.into_i32())?);
+        }
+        if !self.expect_peek(TokenType::RBRACKET) {
+            return Err(self.peek_error("Error parsing array: missing closing ]"))
+        }
+        Ok(self.builder.array_literal(tok, elements))
+    }
+
+    // {<key>: <value>, ...}
+    fn parse_hash_literal(&mut self) -> Result<B::Expression, ParseError> {
+        let tok = self.curr_token.clone();
+        let mut pairs: Vec<(B::Expression, B::Expression)> = Vec::new();
+        while !self.peektoken_is(TokenType::RBRACE) {
+            self.next_token();
+            let key = self.parse_expression(Precedence::LOWEST.into_i32())?;
+            if !self.expect_peek(TokenType::COLON) {
+                return Err(self.peek_error("Error parsing hash: key and value need to be separated by \":\""))
+            }
+            self.next_token();
+            let value = self.parse_expression(Precedence::LOWEST.into_i32())?;
+            pairs.push((key, value));
+            if !self.peektoken_is(TokenType::RBRACE) && !self.expect_peek(TokenType::COMMA) {
+                return Err(self.peek_error("Error parsing hash: pairs need to be separated by \",\""))
+            }
+        }
+        self.next_token();
+        Ok(self.builder.hash_literal(tok, pairs))
     }
 
-    fn parse_call_expression(&mut self, function: MonkeyExpression) -> Result<MonkeyExpression, &'static str> {
+    fn parse_index_expression(&mut self, left: B::Expression) -> Result<B::Expression, ParseError> {
         let tok = self.curr_token.clone();
-        let args = self.parse_call_arguments();
-        Ok(MonkeyExpression::CALL(CallExpression::new(tok, function, args)))
+        self.next_token();
+        let index = self.parse_expression(Precedence::LOWEST.into_i32())?;
+        if !self.expect_peek(TokenType::RBRACKET) {
+            return Err(self.peek_error("Error parsing index expression: missing closing ]"))
+        }
+        Ok(self.builder.index_expression(tok, left, index))
     }
-    fn parse_call_arguments(&mut self) -> Option<Vec<Box<MonkeyExpression>>> {
+
+    fn parse_call_arguments(&mut self) -> Result<Option<Vec<B::Expression>>, ParseError> {
 
         if self.peektoken_is(TokenType::RPAREN) {
             self.next_token();
-            return None
+            return Ok(None)
         }
 
-        let mut args : Vec<Box<MonkeyExpression>> = Vec::new();
+        let mut args : Vec<B::Expression> = Vec::new();
         self.next_token();
-        args.push(Box::new(self.parse_expression(Precedence::LOWEST.into_i32()).unwrap()));
+        args.push(self.parse_expression(Precedence::LOWEST.into_i32())?);
 
         while self.peektoken_is(TokenType::COMMA) {
             self.next_token();
             self.next_token();
-            args.push(Box::new(self.parse_expression(Precedence::LOWEST.into_i32()).unwrap()))
+            args.push(self.parse_expression(Precedence::LOWEST.into_i32())?)
         }
         
         if !self.expect_peek(TokenType::RPAREN) {
-            panic!("Error parsing arguments of function call: missing closing ). curr_token: {:#?}", self.curr_token)
+            return Err(self.peek_error("Error parsing arguments of function call: missing closing )"))
         }
-        Some(args)
+        Ok(Some(args))
     }
     fn get_precedence(&self, peek: bool) -> Precedence {
         let token = if peek {&self.peek_token} else {&self.curr_token};
-        match token.tokentype {
-            TokenType::EQ | TokenType::NOTEQ => Precedence::EQUAL,
-            TokenType::GT | TokenType::LT => Precedence::LESSGREATER,
-            TokenType::GTEQ | TokenType::LTEQ => Precedence::LESSGREATER,
-            TokenType::PLUS | TokenType::MINUS => Precedence::SUM,
-            TokenType::ASTERISK | TokenType::SLASH => Precedence::PRODUCT,
-            TokenType::POWER => Precedence::POWER,
-            TokenType::LPAREN => Precedence::CALL,
-            _ => Precedence::LOWEST
-        }
+        Precedence::of(&token.tokentype)
     }
 
     fn expect_peek(&mut self, tok_type: TokenType) -> bool {
         if self.peektoken_is(tok_type) {
             self.next_token();
-            return true
+            true
         } else {
-            return false
+            false
         }
     }
     fn peektoken_is(&self, tok_type: TokenType) -> bool {
-        return self.peek_token.tokentype == tok_type
+        self.peek_token.tokentype == tok_type
     }
 
     fn currtoken_is(&self, tok_type: TokenType) -> bool {
-        return self.curr_token.tokentype == tok_type
+        self.curr_token.tokentype == tok_type
     }
 }
 
@@ -398,23 +905,48 @@ pub enum Precedence {
     LOWEST,
     EQUAL,
     LESSGREATER,
+    BITOR,
+    BITAND,
+    SHIFT,
     SUM,
     PRODUCT,
     POWER,
     PREFIX,
     CALL,
+    INDEX,
 }
 impl Precedence {
+    // how strongly an infix or postfix operator binds, every other token has the LOWEST precedence
+    pub fn of(tok_type: &TokenType) -> Precedence {
+        match tok_type {
+            TokenType::EQ | TokenType::NOTEQ => Precedence::EQUAL,
+            TokenType::GT | TokenType::LT => Precedence::LESSGREATER,
+            TokenType::GTEQ | TokenType::LTEQ => Precedence::LESSGREATER,
+            TokenType::PIPE => Precedence::BITOR,
+            TokenType::AMPERSAND => Precedence::BITAND,
+            TokenType::SHIFTLEFT | TokenType::SHIFTRIGHT => Precedence::SHIFT,
+            TokenType::PLUS | TokenType::MINUS => Precedence::SUM,
+            TokenType::ASTERISK | TokenType::SLASH => Precedence::PRODUCT,
+            TokenType::POWER => Precedence::POWER,
+            TokenType::LPAREN | TokenType::DOT => Precedence::CALL,
+            TokenType::LBRACKET => Precedence::INDEX,
+            _ => Precedence::LOWEST
+        }
+    }
     pub fn into_i32(&self) -> i32 {
         match self {
             Self::LOWEST => 1,
             Self::EQUAL => 2,
             Self::LESSGREATER => 3,
-            Self::SUM => 4,
-            Self::PRODUCT => 5,
-            Self::POWER => 6,
-            Self::PREFIX => 7,
-            Self::CALL => 8,
+            Self::BITOR => 4,
+            Self::BITAND => 5,
+            Self::SHIFT => 6,
+            Self::SUM => 7,
+            Self::PRODUCT => 8,
+            Self::POWER => 9,
+            Self::PREFIX => 10,
+            Self::CALL => 11,
+            Self::INDEX => 12,
         }
     }
     pub fn from_i32(int: i32) -> Option<Precedence> {
@@ -422,11 +954,15 @@ impl Precedence {
             1 => Some(Precedence::LOWEST),
             2 => Some(Precedence::EQUAL),
             3 => Some(Precedence::LESSGREATER),
-            4 => Some(Precedence::SUM),
-            5 => Some(Precedence::PRODUCT),
-            6 => Some(Precedence::POWER),
-            7 => Some(Precedence::PREFIX),
-            8 => Some(Precedence::CALL),
+            4 => Some(Precedence::BITOR),
+            5 => Some(Precedence::BITAND),
+            6 => Some(Precedence::SHIFT),
+            7 => Some(Precedence::SUM),
+            8 => Some(Precedence::PRODUCT),
+            9 => Some(Precedence::POWER),
+            10 => Some(Precedence::PREFIX),
+            11 => Some(Precedence::CALL),
+            12 => Some(Precedence::INDEX),
             _ => None
         }
     }
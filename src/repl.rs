@@ -1,16 +1,28 @@
+use crate::ast_json;
+use crate::gc;
 use crate::object::Environment;
+use crate::module::{self, ModuleLoader};
 use crate::parser::Parser;
 use crate::token::TokenType;
 use crate::lexer::Lexer;
 use crate::evaluator::*;
 use std::fs;
 use std::io;
+use std::path::Path;
 use std::rc::Rc;
 use std::cell::RefCell;
 
 pub fn start_interactive() {
 
-    
+    let loader = Rc::new(RefCell::new(ModuleLoader::from_env()));
+    let prelude = match module::prelude_env(&loader) {
+        Ok(x) => x,
+        Err(err) => {
+            println!("Error: {}", err);
+            return
+        }
+    };
+    let env = gc::track(Environment::new_module(None, Rc::clone(&loader), Some(prelude)));
     loop {
         println!(">> ");
         let mut user_input = String::new();
@@ -19,34 +31,97 @@ pub fn start_interactive() {
         let mut monkey_lexer = Lexer::new(user_input);
         // create_tokens(&mut monkey_lexer);
         let mut monkey_parser = Parser::new(&mut monkey_lexer);
-        let program = monkey_parser.parse_programm();
+        let program = match monkey_parser.parse_programm() {
+            Ok(x) => x,
+            Err(err) => {
+                println!("Parse error: {}", err);
+                continue
+            }
+        };
+        match eval(&program.statements, Rc::clone(&env)) {
+            Ok(results) => {
+                for result in results {
+                    println!("{}", result.inspect());
+                }
+            },
+            Err(err) => println!("Error: {}", err),
+        }
     }
     
 }
 
-pub fn start(mut args: impl Iterator<Item = String>) {
+// runs a file, returns the exit code: 0 if it ran, 1 if it could not be read, parsed or run
+pub fn start(mut args: impl Iterator<Item = String>) -> i32 {
 
     args.next();
-    let filepath = "test.txt";
-    // let filepath = match args.next() {
-    //     Some(x) => x,
-    //     None => panic!("Error: could not open monkeyfile"),
-    // };
-    let mut monkey_lexer = match fs::read_to_string(filepath) {
-        Ok(l) => Lexer::new(l),
-        Err(err) => panic!("Could not create lexer. Error: {}", err),
-    };
-    println!("{}", monkey_lexer.input);
-    //create_tokens(&mut monkey_lexer);
-    let mut monkey_parser = Parser::new(&mut monkey_lexer);
-    let program = monkey_parser.parse_programm();
-    let env = Rc::new(RefCell::new(Environment::new()));
-    let eval_program = eval(program.unwrap().statements, Rc::clone(&env));
-    println!("{:#?}", eval_program);
+    // imports are searched next to the importing file and then in the directories of MONKEY_PATH
+    let loader = Rc::new(RefCell::new(ModuleLoader::from_env()));
+    let mut filepath = String::from("test.txt");
+    let mut dump_ast = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--no-prelude" => loader.borrow_mut().disable_prelude(),
+            "-O" => loader.borrow_mut().set_optimize(true),
+            "--dump-ast" => dump_ast = true,
+            "--prelude" => {
+                let source = match args.next().map(fs::read_to_string) {
+                    Some(Ok(x)) => x,
+                    Some(Err(err)) => {
+                        eprintln!("Error: could not read prelude: {}", err);
+                        return 1
+                    },
+                    None => {
+                        eprintln!("Error: --prelude needs a file");
+                        return 1
+                    },
+                };
+                loader.borrow_mut().set_prelude(Some(source));
+            },
+            _ => filepath = arg,
+        }
+    }
+    if dump_ast {
+        return print_ast(&filepath)
+    }
+    // the results of the statements, written like monkey shows values
+    match module::run_file(&loader, Path::new(&filepath)) {
+        Ok(results) => {
+            for result in results {
+                println!("{}", result.inspect());
+            }
+            0
+        },
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            1
+        },
+    }
+
+}
 
+// --dump-ast prints the ast of the file as json instead of running it, see ast_json.rs
+fn print_ast(filepath: &str) -> i32 {
+    let source = match fs::read_to_string(filepath) {
+        Ok(x) => x,
+        Err(err) => {
+            eprintln!("Error: could not read {}: {}", filepath, err);
+            return 1
+        }
+    };
+    let mut lexer = Lexer::new(source);
+    match Parser::new(&mut lexer).parse_programm() {
+        Ok(x) => {
+            println!("{}", ast_json::to_json(&x));
+            0
+        },
+        Err(err) => {
+            eprintln!("Parse error: {}", err);
+            1
+        },
+    }
 }
 
-fn create_tokens(monkey_lexer: &mut Lexer) {
+pub fn create_tokens(monkey_lexer: &mut Lexer) {
     loop {
         let tok = monkey_lexer.next_token();
         match tok.tokentype {
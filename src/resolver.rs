@@ -0,0 +1,162 @@
+use std::rc::Rc;
+
+use crate::ast::{walk_function_literal, walk_function_literal_mut, walk_return_statement_mut, MonkeyExpression, Programm, Statement, Visitor, VisitorMut};
+use crate::scope::definitions;
+use crate::symbol::Symbol;
+use crate::token::{BlockStatement, CallExpression, FunctionLiteral, Identifier, MemberExpression, Resolved, ReturnStatement};
+
+// finds the slot of every variable that belongs to a function, so the evaluator does not have to look it up by name.
+// a call gets one slot for every parameter and every name declared with let or import anywhere in the body,
+// blocks and if expressions share the slots of the function they are in. nested functions get their own.
+//
+// the names are hoisted, a slot that was not set yet when it is read (the let comes later or was in a branch
+// that did not run) is looked up by name in the enclosing environments, just like before the resolver existed.
+// everything outside of functions is a global and always looked up by name, the repl adds to them line by line.
+//
+// a local that is read as the value of a return or of the last statement, or as an argument of the call that
+// gives that value, is read for the last time in the call. it is marked as last_use and the evaluator moves it
+// out of its slot, so reduce(arr, [], fn(acc, x) { push(acc, x) }) hands push the only reference to acc.
+// a name that appears twice in that expression or that a nested function reads is never moved
+
+pub fn resolve(program: &mut Programm) {
+    let mut resolver = Resolver { scopes: Vec::new() };
+    resolver.visit_program_mut(program);
+}
+
+struct Resolver {
+    // the locals of the functions the resolver is in, the innermost last
+    scopes: Vec<Vec<Symbol>>,
+}
+
+impl VisitorMut for Resolver {
+    fn visit_identifier_mut(&mut self, identifier: &mut Identifier) {
+        identifier.resolved = None;
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            // a parameter that appears twice gets the last argument, like it did in the hashmap
+            if let Some(slot) = scope.iter().rposition(|x| *x == identifier.value) {
+                identifier.resolved = Some(Resolved { depth, slot, last_use: false });
+                return
+            }
+        }
+    }
+    fn visit_function_literal_mut(&mut self, function: &mut FunctionLiteral) {
+        let names = locals(function);
+        function.locals = Rc::new(names.clone());
+        self.scopes.push(names);
+        walk_function_literal_mut(self, function);
+        self.scopes.pop();
+        let mut captured = Captured { names: Vec::new(), functions: 0 };
+        captured.visit_block_statement(&function.blockstatment);
+        let mut last_uses = LastUses { captured: captured.names };
+        last_uses.visit_block_statement_mut(&mut function.blockstatment);
+        last_uses.tail_block(&mut function.blockstatment);
+    }
+    // the property is looked up in the module, not in the environment
+    fn visit_member_expression_mut(&mut self, expression: &mut MemberExpression) {
+        self.visit_expression_mut(&mut expression.object);
+    }
+}
+
+// the parameters and the names the body declares, without the ones of nested functions
+fn locals(function: &FunctionLiteral) -> Vec<Symbol> {
+    let mut names: Vec<Symbol> = function.parameters.iter().map(|x| x.value).collect();
+    for definition in definitions(&[], &function.blockstatment.statements) {
+        if !names.contains(&definition.name.value) {
+            names.push(definition.name.value);
+        }
+    }
+    names
+}
+
+// the names that nested functions read, they can run after the call and still need the slots
+struct Captured {
+    names: Vec<Symbol>,
+    functions: usize,
+}
+impl Visitor<'_> for Captured {
+    fn visit_identifier(&mut self, identifier: &Identifier) {
+        if self.functions > 0 {
+            self.names.push(identifier.value);
+        }
+    }
+    fn visit_function_literal(&mut self, function: &FunctionLiteral) {
+        self.functions += 1;
+        walk_function_literal(self, function);
+        self.functions -= 1;
+    }
+}
+
+// how often a name appears in an expression
+struct Uses {
+    name: Symbol,
+    count: usize,
+}
+impl Visitor<'_> for Uses {
+    fn visit_identifier(&mut self, identifier: &Identifier) {
+        if identifier.value == self.name {
+            self.count += 1;
+        }
+    }
+}
+
+// marks the locals read in tail position of one function, the returns anywhere in it and its last statement
+struct LastUses {
+    captured: Vec<Symbol>,
+}
+impl LastUses {
+    fn mark(&self, identifier: &mut Identifier) {
+        if let Some(resolved) = &mut identifier.resolved {
+            if resolved.depth == 0 && !self.captured.contains(&identifier.value) {
+                resolved.last_use = true;
+            }
+        }
+    }
+    fn tail_block(&self, block: &mut BlockStatement) {
+        match block.statements.last_mut() {
+            Some(Statement::EXPRESSION(x)) => self.tail(&mut x.expression),
+            Some(Statement::BLOCK(x)) => self.tail_block(x),
+            _ => {},
+        }
+    }
+    fn tail(&self, expression: &mut MonkeyExpression) {
+        match expression {
+            MonkeyExpression::IDENT(x) => self.mark(x),
+            MonkeyExpression::CALL(x) => self.tail_call(x),
+            MonkeyExpression::IF(x) => {
+                self.tail_block(&mut x.consequence);
+                if let Some(alternative) = &mut x.alternative {
+                    self.tail_block(alternative);
+                }
+            },
+            _ => {},
+        }
+    }
+    // the arguments are evaluated one after another, a name can only be moved if no other part of the call reads it
+    fn tail_call(&self, call: &mut CallExpression) {
+        let mut moved: Vec<usize> = Vec::new();
+        for (i, argument) in call.arguments.iter().flatten().enumerate() {
+            if let MonkeyExpression::IDENT(x) = argument {
+                let mut uses = Uses { name: x.value, count: 0 };
+                uses.visit_call_expression(call);
+                if uses.count == 1 {
+                    moved.push(i);
+                }
+            }
+        }
+        for (i, argument) in call.arguments.iter_mut().flatten().enumerate() {
+            if let MonkeyExpression::IDENT(x) = argument {
+                if moved.contains(&i) {
+                    self.mark(x);
+                }
+            }
+        }
+    }
+}
+impl VisitorMut for LastUses {
+    // nothing of the function runs after a return
+    fn visit_return_statement_mut(&mut self, statement: &mut ReturnStatement) {
+        walk_return_statement_mut(self, statement);
+        self.tail(&mut statement.return_value);
+    }
+    fn visit_function_literal_mut(&mut self, _function: &mut FunctionLiteral) {}
+}
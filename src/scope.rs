@@ -0,0 +1,268 @@
+use crate::ast::*;
+use crate::token::*;
+
+// the scoping rules of the evaluator, for the tools that need to know what a name refers to.
+// a module and every function call get their own environment, the blocks of an if share the environment they are in.
+// a let is visible from the point it ran, so a name is looked up in the bindings declared so far.
+// names that are only defined further down are "hoisted": using them in the same scope is an error at runtime,
+// using them from a nested function is fine as long as the function is called after the let ran
+
+pub enum DefinitionKind<'a> {
+    PARAMETER,
+    LET(&'a LetStatement),
+    EXPORT(&'a LetStatement),
+    IMPORT(&'a ImportStatement),
+}
+
+// a name defined by a parameter, a let or an import
+pub struct Definition<'a> {
+    pub name: &'a Identifier,
+    pub kind: DefinitionKind<'a>,
+    // inside of an if block, so the let might never run
+    pub conditional: bool,
+}
+
+// the definitions of one scope in the order of the source: the parameters and then the lets and imports
+// of the statements, the ones inside of if blocks included
+pub fn definitions<'a>(parameters: &'a [Identifier], statements: &'a [Statement]) -> Vec<Definition<'a>> {
+    let mut collector = Collector {
+        definitions: parameters.iter().map(|x| Definition { name: x, kind: DefinitionKind::PARAMETER, conditional: false }).collect(),
+        nested_blocks: 0,
+    };
+    for statement in statements {
+        collector.visit_statement(statement);
+    }
+    collector.definitions
+}
+
+struct Collector<'a> {
+    definitions: Vec<Definition<'a>>,
+    nested_blocks: usize,
+}
+impl<'a> Collector<'a> {
+    fn define(&mut self, name: &'a Identifier, kind: DefinitionKind<'a>) {
+        self.definitions.push(Definition { name, kind, conditional: self.nested_blocks > 0 });
+    }
+}
+impl<'a> Visitor<'a> for Collector<'a> {
+    fn visit_block_statement(&mut self, block: &'a BlockStatement) {
+        self.nested_blocks += 1;
+        walk_block_statement(self, block);
+        self.nested_blocks -= 1;
+    }
+
+    fn visit_let_statement(&mut self, statement: &'a LetStatement) {
+        self.define(&statement.name, DefinitionKind::LET(statement));
+        walk_let_statement(self, statement);
+    }
+
+    fn visit_export_statement(&mut self, statement: &'a ExportStatement) {
+        self.define(&statement.statement.name, DefinitionKind::EXPORT(&statement.statement));
+        walk_let_statement(self, &statement.statement);
+    }
+
+    fn visit_import_statement(&mut self, statement: &'a ImportStatement) {
+        for name in statement.alias.iter().chain(statement.names.iter()) {
+            self.define(name, DefinitionKind::IMPORT(statement));
+        }
+    }
+
+    // a function body gets a scope of its own
+    fn visit_function_literal(&mut self, _expression: &'a FunctionLiteral) {}
+}
+
+// the module or a function with the definitions made inside of it
+pub struct Scope<'a> {
+    // None for the module
+    pub function: Option<&'a FunctionLiteral>,
+    pub definitions: Vec<usize>,
+}
+
+// an identifier expression and what it refers to
+pub struct Reference<'a> {
+    pub name: &'a Identifier,
+    // None for builtins, the prelude and names that are not defined at all
+    pub target: Option<usize>,
+    // the own scope defines the name, but only further down
+    pub before_definition: bool,
+}
+
+// a definition with the name of one that was declared before it
+pub struct Shadowing {
+    pub definition: usize,
+    pub previous: usize,
+    // the previous one is in the same scope and not in an outer one
+    pub same_scope: bool,
+}
+
+// every scope, definition and reference of a program, the indices point into scopes and definitions
+pub struct Scopes<'a> {
+    pub scopes: Vec<Scope<'a>>,
+    pub definitions: Vec<Definition<'a>>,
+    pub references: Vec<Reference<'a>>,
+    pub shadowing: Vec<Shadowing>,
+}
+impl<'a> Scopes<'a> {
+    pub fn new(program: &'a Programm) -> Scopes<'a> {
+        let mut resolver = Resolver {
+            scopes: Scopes { scopes: Vec::new(), definitions: Vec::new(), references: Vec::new(), shadowing: Vec::new() },
+            stack: Vec::new(),
+        };
+        resolver.enter(None, &[], &program.statements);
+        resolver.visit_program(program);
+        resolver.scopes
+    }
+}
+
+// a scope while resolving and the definitions of it that were reached so far
+struct Entered {
+    scope: usize,
+    declared: Vec<usize>,
+}
+
+struct Resolver<'a> {
+    scopes: Scopes<'a>,
+    stack: Vec<Entered>,
+}
+impl<'a> Resolver<'a> {
+    fn enter(&mut self, function: Option<&'a FunctionLiteral>, parameters: &'a [Identifier], statements: &'a [Statement]) {
+        let first = self.scopes.definitions.len();
+        self.scopes.definitions.extend(definitions(parameters, statements));
+        let definitions: Vec<usize> = (first..self.scopes.definitions.len()).collect();
+        self.scopes.scopes.push(Scope { function, definitions });
+        self.stack.push(Entered { scope: self.scopes.scopes.len() - 1, declared: Vec::new() });
+        for param in parameters {
+            self.declare(param);
+        }
+    }
+
+    fn named(&self, index: usize, name: &Identifier) -> bool {
+        self.scopes.definitions[index].name.value == name.value
+    }
+
+    fn declare(&mut self, name: &'a Identifier) {
+        let last = self.stack.len() - 1;
+        let scope = &self.scopes.scopes[self.stack[last].scope];
+        let definition = match scope.definitions.iter().find(|x| std::ptr::eq(self.scopes.definitions[**x].name, name)) {
+            Some(x) => *x,
+            None => return,
+        };
+        for (i, entered) in self.stack.iter().enumerate().rev() {
+            if let Some(previous) = entered.declared.iter().rev().find(|x| self.named(**x, name)) {
+                self.scopes.shadowing.push(Shadowing { definition, previous: *previous, same_scope: i == last });
+                break
+            }
+        }
+        self.stack[last].declared.push(definition);
+    }
+
+    // the last definition reached in the innermost scope that has one, in outer scopes
+    // a definition further down is fine as well
+    fn lookup(&mut self, name: &'a Identifier) {
+        let last = self.stack.len() - 1;
+        let mut target = None;
+        let mut before_definition = false;
+        for (i, entered) in self.stack.iter().enumerate().rev() {
+            if let Some(x) = entered.declared.iter().rev().find(|x| self.named(**x, name)) {
+                target = Some(*x);
+                break
+            }
+            if let Some(x) = self.scopes.scopes[entered.scope].definitions.iter().find(|x| self.named(**x, name)) {
+                if i == last {
+                    // the lookup at runtime goes on to the outer scopes
+                    before_definition = true;
+                    continue
+                }
+                target = Some(*x);
+                break
+            }
+        }
+        self.scopes.references.push(Reference { name, target, before_definition });
+    }
+}
+impl<'a> Visitor<'a> for Resolver<'a> {
+    fn visit_let_statement(&mut self, statement: &'a LetStatement) {
+        self.visit_expression(&statement.value);
+        self.declare(&statement.name);
+    }
+
+    fn visit_import_statement(&mut self, statement: &'a ImportStatement) {
+        for name in statement.alias.iter().chain(statement.names.iter()) {
+            self.declare(name);
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &'a MonkeyExpression) {
+        match expression {
+            MonkeyExpression::IDENT(x) => self.lookup(x),
+            other => walk_expression(self, other),
+        }
+    }
+
+    fn visit_function_literal(&mut self, expression: &'a FunctionLiteral) {
+        self.enter(Some(expression), &expression.parameters, &expression.blockstatment.statements);
+        self.visit_block_statement(&expression.blockstatment);
+        self.stack.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Programm {
+        match Parser::new(&mut Lexer::new(String::from(source))).parse_programm() {
+            Ok(x) => x,
+            Err(err) => panic!("could not parse {:?}: {}", source, err),
+        }
+    }
+
+    // the name of every reference and the line and column of its definition
+    fn targets(source: &str) -> Vec<(String, Option<(usize, usize)>)> {
+        let program = parse(source);
+        let scopes = Scopes::new(&program);
+        scopes.references.iter()
+            .map(|x| (x.name.value.to_string(), x.target.map(|i| {
+                let span = scopes.definitions[i].name.token.span;
+                (span.line, span.column)
+            })))
+            .collect()
+    }
+
+    #[test]
+    fn marks_lets_inside_of_if_blocks_as_conditional() {
+        let program = parse("let a = 1; if (a) { let b = 2; } else { let c = 3; } let f = fn(x) { let d = 3; };");
+        let found: Vec<(String, bool)> = definitions(&[], &program.statements).iter()
+            .map(|x| (x.name.value.to_string(), x.conditional))
+            .collect();
+        let expected = [("a", false), ("b", true), ("c", true), ("f", false)].map(|(name, x)| (String::from(name), x));
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn resolves_names_like_the_evaluator() {
+        // a let further down is only found from inside of a function
+        assert_eq!(targets("let f = fn() { x }; let x = 1; x;"), vec![
+            (String::from("x"), Some((1, 25))),
+            (String::from("x"), Some((1, 25))),
+        ]);
+        assert_eq!(targets("x; let x = 1;"), vec![(String::from("x"), None)]);
+        // the parameter hides the let of the module
+        assert_eq!(targets("let x = 1; fn(x) { x };"), vec![(String::from("x"), Some((1, 15)))]);
+        assert_eq!(targets("puts(1);"), vec![(String::from("puts"), None)]);
+    }
+
+    #[test]
+    fn finds_shadowed_definitions() {
+        let program = parse("let a = 1; let a = 2; fn(a) { let b = a; };");
+        let scopes = Scopes::new(&program);
+        let found: Vec<(usize, usize, bool)> = scopes.shadowing.iter().map(|x| (x.definition, x.previous, x.same_scope)).collect();
+        assert_eq!(found, vec![(1, 0, true), (2, 1, false)]);
+        assert!(scopes.references.iter().all(|x| !x.before_definition));
+
+        let program = parse("x; let x = 1;");
+        assert!(Scopes::new(&program).references[0].before_definition);
+    }
+}
@@ -0,0 +1,20 @@
+use crate::ast::Statement;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+// the monkey sources of the standard library are compiled into the binary,
+// so the interpreter works without any files next to it
+pub const PRELUDE: &str = include_str!("stdlib/prelude.mk");
+
+// the names the prelude defines at the top level
+pub fn prelude_names() -> Vec<String> {
+    let mut lexer = Lexer::new(String::from(PRELUDE));
+    let program = match Parser::new(&mut lexer).parse_programm() {
+        Ok(x) => x,
+        Err(_) => return Vec::new(),
+    };
+    program.statements.into_iter().filter_map(|x| match x {
+        Statement::LET(x) => Some(x.name.value.to_string()),
+        _ => None,
+    }).collect()
+}
@@ -0,0 +1,111 @@
+// the prelude is evaluated into the root environment before any file runs.
+// it is built on top of the builtins in builtins.rs, everything here can be shadowed by a let
+
+/// applies f to every element of arr and returns the results as a new array
+let map = fn(arr, f) {
+    reduce(arr, [], fn(acc, x) { push(acc, f(x)) })
+};
+
+/// the elements of arr for which pred returns true
+let filter = fn(arr, pred) {
+    reduce(arr, [], fn(acc, x) {
+        if (pred(x)) { push(acc, x) } else { acc }
+    })
+};
+
+/// pairs up the elements of a and b, the result is as long as the shorter array
+let zip = fn(a, b) {
+    map(range(min(len(a), len(b))), fn(i) { [a[i], b[i]] })
+};
+
+/// whether a string contains a substring, an array contains an element or a hash contains a key
+let contains = fn(collection, item) {
+    let kind = type_of(collection);
+    if (kind == "STRING") {
+        if (item == "") { true } else { len(split(collection, item)) > 1 }
+    } else {
+        if (kind == "HASH") {
+            contains(keys(collection), item)
+        } else {
+            reduce(collection, false, fn(found, x) {
+                if (found) { true } else { x == item }
+            })
+        }
+    }
+};
+
+/// sorts arr with less(a, b) deciding whether a belongs in front of b
+let sort_by = fn(arr, less) {
+    if (len(arr) < 2) {
+        arr
+    } else {
+        let pivot = arr[len(arr) / 2];
+        let smaller = filter(arr, fn(x) { less(x, pivot) });
+        let larger = filter(arr, fn(x) { less(pivot, x) });
+        let same = filter(arr, fn(x) {
+            if (less(x, pivot)) { false } else { !less(pivot, x) }
+        });
+        sort_by(smaller, less) + same + sort_by(larger, less)
+    }
+};
+
+/// sorts integers or strings in ascending order
+let sort = fn(arr) {
+    sort_by(arr, fn(a, b) { a < b })
+};
+
+// math
+
+let abs = fn(x) {
+    if (x < 0) { -x } else { x }
+};
+
+let min = fn(a, b) {
+    if (a < b) { a } else { b }
+};
+
+let max = fn(a, b) {
+    if (a > b) { a } else { b }
+};
+
+/// limits x to the range from low to high
+let clamp = fn(x, low, high) {
+    min(max(x, low), high)
+};
+
+let sum = fn(arr) {
+    reduce(arr, 0, fn(acc, x) { acc + x })
+};
+
+let product = fn(arr) {
+    reduce(arr, 1, fn(acc, x) { acc * x })
+};
+
+/// the remainder of a / b, it has the sign of a
+let rem = fn(a, b) {
+    a - (a / b) * b
+};
+
+/// the greatest common divisor of a and b
+let gcd = fn(a, b) {
+    if (b == 0) { abs(a) } else { gcd(b, rem(a, b)) }
+};
+
+// strings
+
+/// s repeated n times
+let repeat = fn(s, n) {
+    reduce(range(n), "", fn(acc, i) { acc + s })
+};
+
+let starts_with = fn(s, prefix) {
+    slice(s, 0, len(prefix)) == prefix
+};
+
+let ends_with = fn(s, suffix) {
+    slice(s, len(s) - len(suffix), len(s)) == suffix
+};
+
+let lines = fn(s) {
+    split(s, "\n")
+};
@@ -0,0 +1,87 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+// identifiers, keywords and operators are interned, so each name is stored once no matter how often it is used.
+// a Symbol is an index into the table of the thread that interned it, comparing or hashing one does not look at the text.
+// the table only grows with the number of different names and is freed when the thread ends.
+// the text of strings, numbers and illegal input is never interned, it belongs to the token (see token::Literal)
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32, PhantomData<*const ()>);
+
+#[derive(Default)]
+struct Interner {
+    symbols: HashMap<Rc<str>, Symbol>,
+    strings: Vec<Rc<str>>,
+}
+
+// one table per thread, no lock is needed to look a name up. the raw pointer in Symbol keeps it on its thread,
+// in another thread the index would point into another table
+thread_local! {
+    static INTERNER: RefCell<Interner> = RefCell::new(Interner::default());
+}
+
+impl Symbol {
+    pub fn intern(string: &str) -> Symbol {
+        Symbol::intern_text(string).0
+    }
+    // the symbol together with the text the table keeps for it
+    pub fn intern_text(string: &str) -> (Symbol, Rc<str>) {
+        INTERNER.with(|interner| {
+            let mut interner = interner.borrow_mut();
+            if let Some((text, symbol)) = interner.symbols.get_key_value(string) {
+                return (*symbol, Rc::clone(text))
+            }
+            let text: Rc<str> = Rc::from(string);
+            let symbol = Symbol(interner.strings.len() as u32, PhantomData);
+            interner.strings.push(Rc::clone(&text));
+            interner.symbols.insert(Rc::clone(&text), symbol);
+            (symbol, text)
+        })
+    }
+    pub fn text(self) -> Rc<str> {
+        INTERNER.with(|interner| Rc::clone(&interner.borrow().strings[self.0 as usize]))
+    }
+    pub fn is_empty(self) -> bool {
+        self.text().is_empty()
+    }
+}
+
+impl Default for Symbol {
+    fn default() -> Self {
+        Symbol::intern("")
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(string: &str) -> Symbol {
+        Symbol::intern(string)
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        *self.text() == *other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        *self.text() == **other
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text())
+    }
+}
+
+// shows the text, a bare index would not tell anything in the debug output of the ast
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", &*self.text())
+    }
+}
@@ -4,17 +4,110 @@ use crate::ast::MonkeyExpression;
 use crate::ast::MonkeyStatement;
 use crate::ast::Node;
 use crate::ast::Statement;
+use crate::ast::TypeAnnotation;
 use crate::ast::NodeType;
+use crate::ast::{fmt_block, fmt_block_with, fmt_operand, quote_string};
+use crate::parser::Precedence;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::symbol::Symbol;
+
+
+// start and end are byte offsets into the source, line and column (both starting at 1) point to the first char
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Span {
+        Span { start, end, line, column }
+    }
+}
+
+// the text of a token. names, keywords and operators are interned, strings, numbers and illegal input
+// belong to the token, so an unterminated string does not keep the rest of the file in the symbol table
+#[derive(Clone)]
+pub enum Literal {
+    SYMBOL(Symbol, Rc<str>),
+    TEXT(Rc<str>),
+}
+impl Literal {
+    pub fn new(tok_type: &TokenType, text: &str) -> Literal {
+        match tok_type {
+            TokenType::STRING | TokenType::INT | TokenType::ILLEGAL => Literal::TEXT(Rc::from(text)),
+            _ => {
+                let (symbol, text) = Symbol::intern_text(text);
+                Literal::SYMBOL(symbol, text)
+            },
+        }
+    }
+    pub fn as_str(&self) -> &str {
+        match self {
+            Literal::SYMBOL(_, text) | Literal::TEXT(text) => text,
+        }
+    }
+    // the name of an identifier token, only text that is not interned yet is interned here
+    pub fn symbol(&self) -> Symbol {
+        match self {
+            Literal::SYMBOL(symbol, _) => *symbol,
+            Literal::TEXT(text) => Symbol::intern(text),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.as_str().is_empty()
+    }
+}
+impl PartialEq for Literal {
+    fn eq(&self, other: &Literal) -> bool {
+        match (self, other) {
+            (Literal::SYMBOL(a, _), Literal::SYMBOL(b, _)) => a == b,
+            _ => self.as_str() == other.as_str(),
+        }
+    }
+}
+impl PartialEq<str> for Literal {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+impl PartialEq<&str> for Literal {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl fmt::Debug for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
 
-
-#[derive(Debug, Clone, PartialEq)]
+// doc holds the "///" comment lines written directly in front of the token
+#[derive(Debug, Clone)]
 pub struct Token{
     pub tokentype: TokenType,
-    pub literal: String,
+    pub literal: Literal,
+    pub span: Span,
+    pub doc: Option<String>,
 }
 impl Token {
-    pub fn new(tok_type: TokenType, literal: String) -> Token {
-        Token {tokentype: tok_type, literal: literal}
+    pub fn new(tok_type: TokenType, literal: &str) -> Token {
+        Token {literal: Literal::new(&tok_type, literal), tokentype: tok_type, span: Span::default(), doc: None}
+    }
+}
+// span and doc are only metadata, two tokens are the same if type and literal match.
+// that way ast nodes parsed from differently formatted sources compare equal
+impl PartialEq for Token {
+    fn eq(&self, other: &Token) -> bool {
+        self.tokentype == other.tokentype && self.literal == other.literal
     }
 }
 #[derive(Debug, PartialEq, Clone, Eq, Hash)]
@@ -25,6 +118,7 @@ pub enum TokenType {
     //Identifiers + literals
     IDENT,
     INT,
+    STRING,
 
     //Operators
     ASSIGN,
@@ -36,6 +130,11 @@ pub enum TokenType {
     BANG,
     ASTERISK,
     SLASH,
+    AMPERSAND,
+    PIPE,
+    TILDE,
+    SHIFTLEFT,
+    SHIFTRIGHT,
 
     LT,
     LTEQ,
@@ -49,11 +148,16 @@ pub enum TokenType {
 
     COMMA,
     SEMICOLON,
+    COLON,
+    DOT,
+    ARROW,
 
     LPAREN,
     RPAREN,
     LBRACE,
     RBRACE,
+    LBRACKET,
+    RBRACKET,
 
     //Keywords
 
@@ -64,10 +168,24 @@ pub enum TokenType {
     IF,
     ELSE,
     RETURN,
+    IMPORT,
+    EXPORT,
+    AS,
+    FROM,
+
+    //words kept free for future keywords, they can not be used as identifiers
+    RESERVED,
 }
+
+pub const RESERVED_WORDS: [&str; 12] = [
+    "break", "const", "continue", "enum", "for", "in",
+    "loop", "match", "null", "struct", "type", "while",
+];
+
 impl TokenType {
+    // keywords are case sensitive: "let" is a keyword, "Let" and "LET" are identifiers
     pub fn lookup_keyword(keyword: &str) -> TokenType {
-        let result = match keyword.to_lowercase().as_str() {
+        match keyword {
             "fn" => TokenType::FUNCTION,
             "let" => TokenType::LET,
             "true" => TokenType::TRUE,
@@ -75,29 +193,57 @@ impl TokenType {
             "if" => TokenType::IF,
             "else" => TokenType::ELSE,
             "return" => TokenType::RETURN,
+            "import" => TokenType::IMPORT,
+            "export" => TokenType::EXPORT,
+            "as" => TokenType::AS,
+            "from" => TokenType::FROM,
+            _ if RESERVED_WORDS.contains(&keyword) => TokenType::RESERVED,
             _ => TokenType::IDENT,
-        };
-        return result
+        }
     }
 }
 #[derive(Debug, PartialEq, Clone)]
 pub struct LetStatement {
     pub token: Token,
     pub name: Identifier,
+    pub annotation: Option<TypeAnnotation>,
     pub value: MonkeyExpression,
 
 }
 impl LetStatement {
     pub fn new(tok: Token, name: Identifier, value: MonkeyExpression) -> LetStatement {
-        LetStatement{token: tok, name: name, value: value}
+        LetStatement{token: tok, name, annotation: None, value}
+    }
+    // the "///" doc comment written above the let statement
+    pub fn doc(&self) -> Option<&str> {
+        self.token.doc.as_deref()
+    }
+    fn fmt_doc(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(doc) = self.doc() {
+            for line in doc.lines() {
+                if line.is_empty() {
+                    writeln!(f, "///")?;
+                } else {
+                    writeln!(f, "/// {}", line)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    // everything after "let"
+    fn fmt_binding(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.annotation {
+            Some(annotation) => write!(f, "{}: {} = {};", self.name, annotation, self.value),
+            None => write!(f, "{} = {};", self.name, self.value),
+        }
     }
 }
 impl MonkeyStatement for LetStatement {}
 
 impl Node for LetStatement {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None
         }
@@ -109,7 +255,7 @@ impl Node for LetStatement {
 
 #[derive(Debug, PartialEq,Clone)]
 pub struct ReturnStatement {
-    token: Token,
+    pub token: Token,
     pub return_value: MonkeyExpression,
 }
 impl ReturnStatement {
@@ -120,9 +266,9 @@ impl ReturnStatement {
 impl MonkeyStatement for ReturnStatement {}
 
 impl Node for ReturnStatement {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None   
         }
@@ -133,11 +279,17 @@ impl Node for ReturnStatement {
 }
 
 
-#[derive(Debug, PartialEq,Clone)]
+#[derive(Debug, Clone)]
 pub struct ExpressionStatement {
-    token: Token,
+    pub token: Token,
     pub expression: MonkeyExpression
 }
+// the token is the first token of the statement, which may be a parenthesis around the expression
+impl PartialEq for ExpressionStatement {
+    fn eq(&self, other: &ExpressionStatement) -> bool {
+        self.expression == other.expression
+    }
+}
 
 impl ExpressionStatement {
     pub fn new(tok:Token, expr: MonkeyExpression) -> ExpressionStatement {
@@ -148,9 +300,9 @@ impl MonkeyStatement for ExpressionStatement {}
 // impl MonkeyExpr for ExpressionStatement {}
 
 impl Node for ExpressionStatement {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None
         }
@@ -167,20 +319,22 @@ impl Node for ExpressionStatement {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct BlockStatement {
-    token: Token,
+    pub token: Token,
     pub statements: Vec<Statement>,
+    // printed over several lines even if it would fit on one, the formatter sets it for blocks with comments in them
+    pub multiline: bool,
 }
 impl BlockStatement {
     pub fn new(tok: Token, statements: Vec<Statement>) -> BlockStatement {
-        BlockStatement {token: tok, statements: statements}
+        BlockStatement {token: tok, statements, multiline: false}
     }
 }
 impl MonkeyExpr for BlockStatement {}
 
 impl Node for BlockStatement {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None
         }
@@ -193,22 +347,34 @@ impl Expression for BlockStatement {
     fn expression_node(&self) {}
 }
 
+// where the resolver found a variable that belongs to a function: depth is the number of functions
+// to go outwards from the one the identifier is used in, slot the index in the locals of that function.
+// last_use is set if nothing in the call reads the slot after this, the value is moved out of it then
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Resolved {
+    pub depth: usize,
+    pub slot: usize,
+    pub last_use: bool,
+}
+
 #[derive(Debug, PartialEq,Clone)]
 pub struct Identifier {
     pub token: Token,
-    pub value: String, 
+    pub value: Symbol,
+    // None for globals and for identifiers that were never resolved, those are looked up by name
+    pub resolved: Option<Resolved>,
 }
 impl Identifier {
-    pub fn new(tok: Token, val: String) -> Identifier {
-        Identifier { token: tok, value: val }
+    pub fn new(tok: Token, val: Symbol) -> Identifier {
+        Identifier { token: tok, value: val, resolved: None }
     }
 }
 impl MonkeyExpr for Identifier {}
 
 impl Node for Identifier {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None
         }
@@ -236,9 +402,9 @@ impl IntegerLiteral {
 impl MonkeyExpr for IntegerLiteral {}
 
 impl Node for IntegerLiteral {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None
         }
@@ -251,6 +417,30 @@ impl Expression for IntegerLiteral {
     fn expression_node(&self) {}
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct StringLiteral {
+    pub token: Token,
+    pub value: String,
+}
+impl StringLiteral {
+    pub fn new(tok: Token, val: String) -> StringLiteral {
+        StringLiteral { token: tok, value: val }
+    }
+}
+impl MonkeyExpr for StringLiteral {}
+
+impl Node for StringLiteral {
+    fn token_literal(&self) -> Option<Literal> {
+        Some(self.token.literal.clone())
+    }
+    fn node_type(&self) -> NodeType {
+        NodeType::EXPRESSION
+    }
+}
+impl Expression for StringLiteral {
+    fn expression_node(&self) {}
+}
+
 #[derive(Debug, PartialEq,Clone)]
 pub struct Boolean {
     pub token: Token,
@@ -264,9 +454,9 @@ impl Boolean {
 impl MonkeyExpr for Boolean {}
 
 impl Node for Boolean {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None
         }
@@ -292,9 +482,9 @@ impl PrefixExpression {
 impl MonkeyExpr for PrefixExpression {}
 
 impl Node for PrefixExpression {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None
         }
@@ -319,16 +509,16 @@ pub struct InfixExpression {
 
 impl InfixExpression {
     pub fn new(operator: String, tok: Token, left: MonkeyExpression, right: MonkeyExpression) -> InfixExpression {
-        InfixExpression { operator: operator, token: tok,  left: Box::new(left), right: Box::new(right) }
+        InfixExpression { operator, token: tok,  left: Box::new(left), right: Box::new(right) }
     }
 }
 
 impl MonkeyExpr for InfixExpression {}
 
 impl Node for InfixExpression {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None
         }
@@ -345,21 +535,29 @@ impl Expression for InfixExpression {
 
 #[derive(Debug, PartialEq,Clone)]
 pub struct FunctionLiteral {
-    token: Token,
+    pub token: Token,
     pub parameters: Vec<Identifier>,
+    // one entry for every parameter, None where the parameter has no annotation
+    pub parameter_types: Vec<Option<TypeAnnotation>>,
+    pub return_type: Option<TypeAnnotation>,
     pub blockstatment: BlockStatement,
+    // the names of the slots of a call, the parameters come first. the resolver adds the variables declared in the body.
+    // every call environment shares them
+    pub locals: Rc<Vec<Symbol>>,
 }
 impl FunctionLiteral {
     pub fn new(tok: Token, params: Vec<Identifier>, blockstatement: BlockStatement) -> FunctionLiteral {
-        FunctionLiteral { token: tok, parameters: params, blockstatment: blockstatement }
+        let parameter_types = vec![None; params.len()];
+        let locals = Rc::new(params.iter().map(|x| x.value).collect());
+        FunctionLiteral { token: tok, parameters: params, parameter_types, return_type: None, blockstatment: blockstatement, locals }
     } 
 }
 impl MonkeyExpr for FunctionLiteral {}
 
 impl Node for FunctionLiteral {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None
         }
@@ -373,20 +571,20 @@ impl Expression for FunctionLiteral {
 }
 #[derive(Debug, PartialEq,Clone)]
 pub struct IfExpression {
-    tok: Token,
+    pub tok: Token,
     pub condition: Box<MonkeyExpression>,
     pub consequence: BlockStatement,
     pub alternative: Option<BlockStatement>,
 }
 impl IfExpression {
     pub fn new(tok: Token, condition: Box<MonkeyExpression>, consequence: BlockStatement, alternative: Option<BlockStatement>) -> IfExpression {
-        IfExpression { tok:tok, condition: condition, consequence: consequence, alternative: alternative }
+        IfExpression { tok, condition, consequence, alternative }
     }
 }
 impl MonkeyExpr for IfExpression {}
 
 impl Node for IfExpression {
-    fn token_literal(&self) -> Option<&String> {
+    fn token_literal(&self) -> Option<Literal> {
         self.condition.token_literal()
     }
     fn node_type(&self) -> NodeType {
@@ -399,13 +597,13 @@ impl Expression for IfExpression {
 
 #[derive(Debug, PartialEq,Clone)]
 pub struct CallExpression {
-    token: Token,
+    pub token: Token,
     pub function: Box<MonkeyExpression>,
-    pub arguments: Option<Vec<Box<MonkeyExpression>>>,
+    pub arguments: Option<Vec<MonkeyExpression>>,
 }
 
 impl  CallExpression {
-    pub fn new(tok: Token, function: MonkeyExpression, arguments: Option<Vec<Box<MonkeyExpression>>>) -> CallExpression {
+    pub fn new(tok: Token, function: MonkeyExpression, arguments: Option<Vec<MonkeyExpression>>) -> CallExpression {
         CallExpression { token: tok, function: Box::new(function), arguments }
     }
 }
@@ -413,9 +611,9 @@ impl  CallExpression {
 impl MonkeyExpr for CallExpression {}
 
 impl Node for CallExpression {
-    fn token_literal(&self) -> Option<&String> {
-        if &self.token.literal != "" {
-            Some(&self.token.literal)
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
         } else {
             None
         }       
@@ -426,4 +624,351 @@ impl Node for CallExpression {
 }
 impl Expression for CallExpression {
     fn expression_node(&self) {}
-}
\ No newline at end of file
+}
+
+// import "path" as name;  binds the whole module to name
+// import { a, b } from "path";  binds the exported names a and b
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportStatement {
+    pub token: Token,
+    pub path: String,
+    pub alias: Option<Identifier>,
+    pub names: Vec<Identifier>,
+}
+impl ImportStatement {
+    pub fn new(tok: Token, path: String, alias: Option<Identifier>, names: Vec<Identifier>) -> ImportStatement {
+        ImportStatement { token: tok, path, alias, names }
+    }
+}
+impl MonkeyStatement for ImportStatement {}
+
+impl Node for ImportStatement {
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
+        } else {
+            None
+        }
+    }
+    fn node_type(&self) -> NodeType {
+        NodeType::STATEMENT
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExportStatement {
+    pub token: Token,
+    pub statement: LetStatement,
+}
+impl ExportStatement {
+    pub fn new(tok: Token, statement: LetStatement) -> ExportStatement {
+        ExportStatement { token: tok, statement }
+    }
+}
+impl MonkeyStatement for ExportStatement {}
+
+impl Node for ExportStatement {
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
+        } else {
+            None
+        }
+    }
+    fn node_type(&self) -> NodeType {
+        NodeType::STATEMENT
+    }
+}
+
+// module.name
+#[derive(Debug, PartialEq, Clone)]
+pub struct MemberExpression {
+    pub token: Token,
+    pub object: Box<MonkeyExpression>,
+    pub property: Identifier,
+}
+impl MemberExpression {
+    pub fn new(tok: Token, object: MonkeyExpression, property: Identifier) -> MemberExpression {
+        MemberExpression { token: tok, object: Box::new(object), property }
+    }
+}
+impl MonkeyExpr for MemberExpression {}
+
+impl Node for MemberExpression {
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
+        } else {
+            None
+        }
+    }
+    fn node_type(&self) -> NodeType {
+        NodeType::EXPRESSION
+    }
+}
+impl Expression for MemberExpression {
+    fn expression_node(&self) {}
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ArrayLiteral {
+    pub token: Token,
+    pub elements: Vec<MonkeyExpression>,
+}
+impl ArrayLiteral {
+    pub fn new(tok: Token, elements: Vec<MonkeyExpression>) -> ArrayLiteral {
+        ArrayLiteral { token: tok, elements }
+    }
+}
+impl MonkeyExpr for ArrayLiteral {}
+
+impl Node for ArrayLiteral {
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
+        } else {
+            None
+        }
+    }
+    fn node_type(&self) -> NodeType {
+        NodeType::EXPRESSION
+    }
+}
+impl Expression for ArrayLiteral {
+    fn expression_node(&self) {}
+}
+
+// the pairs are kept in the order they are written in
+#[derive(Debug, PartialEq, Clone)]
+pub struct HashLiteral {
+    pub token: Token,
+    pub pairs: Vec<(MonkeyExpression, MonkeyExpression)>,
+}
+impl HashLiteral {
+    pub fn new(tok: Token, pairs: Vec<(MonkeyExpression, MonkeyExpression)>) -> HashLiteral {
+        HashLiteral { token: tok, pairs }
+    }
+}
+impl MonkeyExpr for HashLiteral {}
+
+impl Node for HashLiteral {
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
+        } else {
+            None
+        }
+    }
+    fn node_type(&self) -> NodeType {
+        NodeType::EXPRESSION
+    }
+}
+impl Expression for HashLiteral {
+    fn expression_node(&self) {}
+}
+
+// left[index]
+#[derive(Debug, PartialEq, Clone)]
+pub struct IndexExpression {
+    pub token: Token,
+    pub left: Box<MonkeyExpression>,
+    pub index: Box<MonkeyExpression>,
+}
+impl IndexExpression {
+    pub fn new(tok: Token, left: MonkeyExpression, index: MonkeyExpression) -> IndexExpression {
+        IndexExpression { token: tok, left: Box::new(left), index: Box::new(index) }
+    }
+}
+impl MonkeyExpr for IndexExpression {}
+
+impl Node for IndexExpression {
+    fn token_literal(&self) -> Option<Literal> {
+        if !self.token.literal.is_empty() {
+            Some(self.token.literal.clone())
+        } else {
+            None
+        }
+    }
+    fn node_type(&self) -> NodeType {
+        NodeType::EXPRESSION
+    }
+}
+impl Expression for IndexExpression {
+    fn expression_node(&self) {}
+}
+
+// the source code of every node. together with Programm and Statement in ast.rs this is what
+// "monkey fmt" writes, parsing the output again gives back the same tree
+
+impl fmt::Display for LetStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_doc(f)?;
+        write!(f, "let ")?;
+        self.fmt_binding(f)
+    }
+}
+
+impl fmt::Display for ReturnStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "return {};", self.return_value)
+    }
+}
+
+impl fmt::Display for ExpressionStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{};", self.expression)
+    }
+}
+
+impl fmt::Display for BlockStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_block(f, self)
+    }
+}
+
+impl fmt::Display for ImportStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.alias {
+            Some(alias) => write!(f, "import {} as {};", quote_string(&self.path), alias),
+            None => {
+                let names: Vec<String> = self.names.iter().map(|x| x.value.to_string()).collect();
+                write!(f, "import {{ {} }} from {};", names.join(", "), quote_string(&self.path))
+            },
+        }
+    }
+}
+
+impl fmt::Display for ExportStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.statement.fmt_doc(f)?;
+        write!(f, "export let ")?;
+        self.statement.fmt_binding(f)
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+// integers are written the way they were in the source, so 0xff stays 0xff
+impl fmt::Display for IntegerLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.token.literal.is_empty() {
+            write!(f, "{}", self.value)
+        } else {
+            write!(f, "{}", self.token.literal)
+        }
+    }
+}
+
+impl fmt::Display for StringLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", quote_string(&self.value))
+    }
+}
+
+impl fmt::Display for Boolean {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+// a prefix operator in front of another one keeps the parentheses, --1 would look like a decrement
+impl fmt::Display for PrefixExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.operator)?;
+        match &*self.right {
+            MonkeyExpression::PREFIX(_) => write!(f, "({})", self.right),
+            _ => fmt_operand(f, &self.right, Precedence::PREFIX.into_i32()),
+        }
+    }
+}
+
+// all infix operators are left associative, so the right operand needs parentheses
+// already if it binds exactly as strong as the operator: a - (b - c)
+impl fmt::Display for InfixExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let precedence = Precedence::of(&self.token.tokentype).into_i32();
+        fmt_operand(f, &self.left, precedence)?;
+        write!(f, " {} ", self.operator)?;
+        fmt_operand(f, &self.right, precedence + 1)
+    }
+}
+
+impl fmt::Display for FunctionLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let params: Vec<String> = self.parameters.iter().zip(&self.parameter_types).map(|(param, annotation)| match annotation {
+            Some(x) => format!("{}: {}", param, x),
+            None => param.to_string(),
+        }).collect();
+        write!(f, "fn({}) ", params.join(", "))?;
+        if let Some(x) = &self.return_type {
+            write!(f, "-> {} ", x)?;
+        }
+        fmt_block(f, &self.blockstatment)
+    }
+}
+
+// both branches are kept on one line or both are split over several lines
+impl fmt::Display for IfExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inline = self.to_source(true);
+        if inline.contains('\n') || inline.len() > 80 {
+            write!(f, "{}", self.to_source(false))
+        } else {
+            write!(f, "{}", inline)
+        }
+    }
+}
+impl IfExpression {
+    fn to_source(&self, allow_inline: bool) -> String {
+        let mut source = format!("if ({}) ", self.condition);
+        let _ = fmt_block_with(&mut source, &self.consequence, allow_inline);
+        if let Some(alternative) = &self.alternative {
+            source.push_str(" else ");
+            let _ = fmt_block_with(&mut source, alternative, allow_inline);
+        }
+        source
+    }
+}
+
+impl fmt::Display for CallExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_operand(f, &self.function, Precedence::CALL.into_i32())?;
+        let args: Vec<String> = match &self.arguments {
+            Some(x) => x.iter().map(|arg| arg.to_string()).collect(),
+            None => Vec::new(),
+        };
+        write!(f, "({})", args.join(", "))
+    }
+}
+
+impl fmt::Display for MemberExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_operand(f, &self.object, Precedence::CALL.into_i32())?;
+        write!(f, ".{}", self.property)
+    }
+}
+
+impl fmt::Display for ArrayLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let elements: Vec<String> = self.elements.iter().map(|x| x.to_string()).collect();
+        write!(f, "[{}]", elements.join(", "))
+    }
+}
+
+impl fmt::Display for HashLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pairs: Vec<String> = self.pairs.iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+        write!(f, "{{{}}}", pairs.join(", "))
+    }
+}
+
+impl fmt::Display for IndexExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_operand(f, &self.left, Precedence::CALL.into_i32())?;
+        write!(f, "[{}]", self.index)
+    }
+}
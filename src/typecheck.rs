@@ -0,0 +1,787 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::ast::*;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::scope::{DefinitionKind, Scopes};
+use crate::stdlib::PRELUDE;
+use crate::token::*;
+
+// a gradual hindley-milner type checker. the types of unannotated code are inferred and checked against
+// the annotations, a value of type any fits everywhere. whatever the checker can not know for sure gets
+// the type any: names that are defined further down, members of modules, arrays with elements of different
+// types or ifs whose branches give different types. so a program without annotations only gets errors for
+// things that fail whenever they run, like true + 1
+pub fn check(program: &Programm) -> Vec<TypeError> {
+    let mut checker = Checker::new();
+    checker.load_prelude();
+    checker.conditional = conditional_lets(program);
+    checker.scopes.push(HashMap::new());
+    checker.check_statements(&program.statements);
+    checker.errors
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TypeError {
+    pub message: String,
+    pub span: Span,
+}
+impl TypeError {
+    pub fn new(message: String, span: Span) -> TypeError {
+        TypeError { message, span }
+    }
+}
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: error[type-error]: {}", self.span.line, self.span.column, self.message)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Type {
+    INT,
+    BOOL,
+    STRING,
+    NULL,
+    ARRAY(Box<Type>),
+    HASH(Box<Type>, Box<Type>),
+    FUNCTION(Vec<Type>, Box<Type>),
+    // the dynamic type, it fits together with every other type
+    ANY,
+    // a type that is not known yet, what it stands for is found out by unification
+    VAR(usize),
+}
+impl Type {
+    fn kind(&self) -> &'static str {
+        match self {
+            Type::INT => "int",
+            Type::BOOL => "bool",
+            Type::STRING => "string",
+            Type::NULL => "null",
+            Type::ARRAY(_) => "array",
+            Type::HASH(_, _) => "hash",
+            Type::FUNCTION(_, _) => "function",
+            Type::ANY => "any",
+            Type::VAR(_) => "var",
+        }
+    }
+}
+
+// the kinds of values the operators and builtins that work on more than one type accept
+const ADDABLE: [&str; 3] = ["int", "string", "array"];
+const COMPARABLE: [&str; 2] = ["int", "string"];
+const HASHABLE: [&str; 3] = ["int", "string", "bool"];
+const INDEXABLE: [&str; 3] = ["array", "hash", "string"];
+const SLICEABLE: [&str; 2] = ["array", "string"];
+
+// the type of a let bound function can be used with different types for vars
+#[derive(Debug, Clone)]
+struct Scheme {
+    vars: Vec<usize>,
+    ty: Type,
+}
+impl Scheme {
+    fn new(vars: Vec<usize>, ty: Type) -> Scheme {
+        Scheme { vars, ty }
+    }
+}
+
+// the function that is checked right now
+struct Frame {
+    returns: Vec<Type>,
+    declared: Option<Type>,
+}
+
+struct Checker {
+    // what every type variable stands for once it is known
+    substitution: Vec<Option<Type>>,
+    // the kinds of values a type variable can still become, + and < put them on their operands
+    constraints: HashMap<usize, Vec<&'static str>>,
+    prelude: HashMap<String, Scheme>,
+    scopes: Vec<HashMap<String, Scheme>>,
+    frames: Vec<Frame>,
+    // the lets of the program that is checked that are inside of if blocks
+    conditional: HashSet<*const LetStatement>,
+    errors: Vec<TypeError>,
+}
+impl Checker {
+    fn new() -> Checker {
+        Checker {
+            substitution: Vec::new(),
+            constraints: HashMap::new(),
+            prelude: HashMap::new(),
+            scopes: Vec::new(),
+            frames: Vec::new(),
+            conditional: HashSet::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    // the prelude is checked like a program in front of the file,
+    // a function that does not type check, like contains which takes strings, arrays and hashes, gets any
+    fn load_prelude(&mut self) {
+        let mut lexer = Lexer::new(String::from(PRELUDE));
+        let program = match Parser::new(&mut lexer).parse_programm() {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+        self.conditional = conditional_lets(&program);
+        self.scopes.push(HashMap::new());
+        for statement in &program.statements {
+            let errors = self.errors.len();
+            self.check_statement(statement);
+            if let (Statement::LET(x), true) = (statement, self.errors.len() > errors) {
+                self.errors.truncate(errors);
+                self.define(&x.name.value.text(), Scheme::new(Vec::new(), Type::ANY));
+            }
+        }
+        self.prelude = self.scopes.pop().unwrap_or_default();
+    }
+
+    fn error(&mut self, span: Span, message: String) {
+        self.errors.push(TypeError::new(message, span));
+    }
+
+    fn fresh(&mut self) -> Type {
+        self.substitution.push(None);
+        Type::VAR(self.substitution.len() - 1)
+    }
+
+    fn constrained(&mut self, kinds: &[&'static str]) -> Type {
+        let ty = self.fresh();
+        if let Type::VAR(x) = ty {
+            self.constraints.insert(x, kinds.to_vec());
+        }
+        ty
+    }
+
+    // follows the variables that are already known until the outermost part of the type is known or a free variable
+    fn prune(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        while let Type::VAR(x) = ty {
+            match &self.substitution[x] {
+                Some(known) => ty = known.clone(),
+                None => break,
+            }
+        }
+        ty
+    }
+
+    // the type with all known variables replaced
+    fn resolve(&self, ty: &Type) -> Type {
+        match self.prune(ty) {
+            Type::ARRAY(x) => Type::ARRAY(Box::new(self.resolve(&x))),
+            Type::HASH(key, value) => Type::HASH(Box::new(self.resolve(&key)), Box::new(self.resolve(&value))),
+            Type::FUNCTION(params, result) => {
+                Type::FUNCTION(params.iter().map(|x| self.resolve(x)).collect(), Box::new(self.resolve(&result)))
+            },
+            other => other,
+        }
+    }
+
+    fn occurs(&self, var: usize, ty: &Type) -> bool {
+        match self.prune(ty) {
+            Type::VAR(x) => x == var,
+            Type::ARRAY(x) => self.occurs(var, &x),
+            Type::HASH(key, value) => self.occurs(var, &key) || self.occurs(var, &value),
+            Type::FUNCTION(params, result) => params.iter().any(|x| self.occurs(var, x)) || self.occurs(var, &result),
+            _ => false,
+        }
+    }
+
+    // makes both types the same, false if they can not be
+    fn unify(&mut self, a: &Type, b: &Type) -> bool {
+        match (self.prune(a), self.prune(b)) {
+            (Type::ANY, _) | (_, Type::ANY) => true,
+            (Type::VAR(x), ty) | (ty, Type::VAR(x)) => self.bind(x, ty),
+            (Type::ARRAY(x), Type::ARRAY(y)) => self.unify(&x, &y),
+            (Type::HASH(key_a, value_a), Type::HASH(key_b, value_b)) => self.unify(&key_a, &key_b) && self.unify(&value_a, &value_b),
+            (Type::FUNCTION(params_a, result_a), Type::FUNCTION(params_b, result_b)) => {
+                params_a.len() == params_b.len()
+                    && params_a.iter().zip(&params_b).all(|(x, y)| self.unify(x, y))
+                    && self.unify(&result_a, &result_b)
+            },
+            (x, y) => x == y,
+        }
+    }
+
+    // unify that leaves everything as it was if the types do not fit
+    fn try_unify(&mut self, a: &Type, b: &Type) -> bool {
+        let substitution = self.substitution.clone();
+        let constraints = self.constraints.clone();
+        if self.unify(a, b) {
+            return true
+        }
+        self.substitution = substitution;
+        self.constraints = constraints;
+        false
+    }
+
+    fn bind(&mut self, var: usize, ty: Type) -> bool {
+        if ty == Type::VAR(var) {
+            return true
+        }
+        if self.occurs(var, &ty) {
+            return false
+        }
+        if let Some(kinds) = self.constraints.get(&var).cloned() {
+            if !self.constrain(&ty, &kinds) {
+                return false
+            }
+        }
+        self.substitution[var] = Some(ty);
+        true
+    }
+
+    // false if the type can not be one of the kinds
+    fn constrain(&mut self, ty: &Type, kinds: &[&'static str]) -> bool {
+        match self.prune(ty) {
+            Type::ANY => true,
+            Type::VAR(x) => {
+                let allowed: Vec<&'static str> = match self.constraints.get(&x) {
+                    Some(existing) => existing.iter().filter(|x| kinds.contains(x)).copied().collect(),
+                    None => kinds.to_vec(),
+                };
+                if allowed.is_empty() {
+                    return false
+                }
+                self.constraints.insert(x, allowed);
+                true
+            },
+            other => kinds.contains(&other.kind()),
+        }
+    }
+
+    fn free_vars(&self, ty: &Type, vars: &mut Vec<usize>) {
+        match self.prune(ty) {
+            Type::VAR(x) if !vars.contains(&x) => vars.push(x),
+            Type::ARRAY(x) => self.free_vars(&x, vars),
+            Type::HASH(key, value) => {
+                self.free_vars(&key, vars);
+                self.free_vars(&value, vars);
+            },
+            Type::FUNCTION(params, result) => {
+                for param in &params {
+                    self.free_vars(param, vars);
+                }
+                self.free_vars(&result, vars);
+            },
+            _ => (),
+        }
+    }
+
+    // the variables of the type that nothing around it depends on can be different at every use
+    fn generalize(&self, ty: &Type) -> Scheme {
+        let mut outside: Vec<usize> = Vec::new();
+        for scope in &self.scopes {
+            for scheme in scope.values() {
+                let mut vars: Vec<usize> = Vec::new();
+                self.free_vars(&scheme.ty, &mut vars);
+                outside.extend(vars.into_iter().filter(|x| !scheme.vars.contains(x)));
+            }
+        }
+        for frame in &self.frames {
+            for ty in frame.returns.iter().chain(frame.declared.iter()) {
+                self.free_vars(ty, &mut outside);
+            }
+        }
+        let mut vars: Vec<usize> = Vec::new();
+        self.free_vars(ty, &mut vars);
+        vars.retain(|x| !outside.contains(x));
+        Scheme::new(vars, self.resolve(ty))
+    }
+
+    fn instantiate(&mut self, scheme: &Scheme) -> Type {
+        let mut fresh: HashMap<usize, Type> = HashMap::new();
+        for var in &scheme.vars {
+            let ty = match self.constraints.get(var).cloned() {
+                Some(kinds) => self.constrained(&kinds),
+                None => self.fresh(),
+            };
+            fresh.insert(*var, ty);
+        }
+        replace_vars(&scheme.ty, &fresh)
+    }
+
+    fn define(&mut self, name: &str, scheme: Scheme) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(String::from(name), scheme);
+        }
+    }
+
+    // names that are not known yet, like the ones defined further down, could be anything
+    fn lookup(&mut self, name: &str) -> Type {
+        let mut found: Option<Scheme> = None;
+        for scope in self.scopes.iter().rev() {
+            if let Some(scheme) = scope.get(name) {
+                found = Some(scheme.clone());
+                break
+            }
+        }
+        if found.is_none() {
+            found = self.prelude.get(name).cloned();
+        }
+        match found {
+            Some(scheme) => self.instantiate(&scheme),
+            None => self.builtin_type(name).unwrap_or(Type::ANY),
+        }
+    }
+
+    fn builtin_type(&mut self, name: &str) -> Option<Type> {
+        let a = self.fresh();
+        let b = self.fresh();
+        let function = |params: Vec<Type>, result: Type| Type::FUNCTION(params, Box::new(result));
+        let array = |x: &Type| Type::ARRAY(Box::new(x.clone()));
+        let ty = match name {
+            "len" => function(vec![self.constrained(&INDEXABLE)], Type::INT),
+            "first" | "last" => function(vec![array(&a)], a),
+            "rest" => function(vec![array(&a)], array(&a)),
+            "push" => function(vec![array(&a), a.clone()], array(&a)),
+            "slice" => {
+                let x = self.constrained(&SLICEABLE);
+                function(vec![x.clone(), Type::INT, Type::INT], x)
+            },
+            "reduce" => function(vec![array(&a), b.clone(), function(vec![b.clone(), a], b.clone())], b),
+            "keys" => function(vec![Type::HASH(Box::new(a.clone()), Box::new(b))], array(&a)),
+            "values" => function(vec![Type::HASH(Box::new(a), Box::new(b.clone()))], array(&b)),
+            "split" => function(vec![Type::STRING, Type::STRING], array(&Type::STRING)),
+            "join" => function(vec![array(&a), Type::STRING], Type::STRING),
+            "reverse" => {
+                let x = self.constrained(&SLICEABLE);
+                function(vec![x.clone()], x)
+            },
+            "upper" | "lower" | "trim" => function(vec![Type::STRING], Type::STRING),
+            "str" | "type_of" => function(vec![Type::ANY], Type::STRING),
+            "int" => function(vec![self.constrained(&["int", "string"])], Type::INT),
+            // these take a different number of arguments
+            "range" | "puts" => Type::ANY,
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    // the type as it is written in annotations, variables that can only be some kinds are written as "int or string"
+    fn show(&self, ty: &Type) -> String {
+        self.show_with(ty, &mut Vec::new())
+    }
+
+    fn show_with(&self, ty: &Type, names: &mut Vec<usize>) -> String {
+        match self.prune(ty) {
+            Type::ARRAY(x) => format!("[{}]", self.show_with(&x, names)),
+            Type::HASH(key, value) => format!("{{{}: {}}}", self.show_with(&key, names), self.show_with(&value, names)),
+            Type::FUNCTION(params, result) => {
+                let params: Vec<String> = params.iter().map(|x| self.show_with(x, names)).collect();
+                format!("fn({}) -> {}", params.join(", "), self.show_with(&result, names))
+            },
+            Type::VAR(x) if self.constraints.contains_key(&x) => self.constraints[&x].join(" or "),
+            Type::VAR(x) => {
+                let index = match names.iter().position(|name| *name == x) {
+                    Some(index) => index,
+                    None => {
+                        names.push(x);
+                        names.len() - 1
+                    },
+                };
+                format!("'{}", (b'a' + (index % 26) as u8) as char)
+            },
+            other => String::from(other.kind()),
+        }
+    }
+
+    // the value of the statements is the value of the last one
+    fn check_statements(&mut self, statements: &[Statement]) -> Type {
+        let mut value = Type::NULL;
+        for statement in statements {
+            value = self.check_statement(statement);
+        }
+        value
+    }
+
+    fn check_statement(&mut self, statement: &Statement) -> Type {
+        match statement {
+            Statement::LET(x) => {
+                self.check_let(x);
+                Type::NULL
+            },
+            Statement::EXPORT(x) => {
+                self.check_let(&x.statement);
+                Type::NULL
+            },
+            // the statements after a return are never reached, the block can have any value
+            Statement::RETURN(x) => {
+                let value = self.check_expression(&x.return_value);
+                self.add_return(value, expression_span(&x.return_value));
+                self.fresh()
+            },
+            Statement::EXPRESSION(x) => self.check_expression(&x.expression),
+            Statement::BLOCK(x) => self.check_block(x),
+            Statement::IMPORT(x) => {
+                for name in x.alias.iter().chain(x.names.iter()) {
+                    self.define(&name.value.text(), Scheme::new(Vec::new(), Type::ANY));
+                }
+                Type::NULL
+            },
+        }
+    }
+
+    fn check_block(&mut self, block: &BlockStatement) -> Type {
+        self.check_statements(&block.statements)
+    }
+
+    fn check_let(&mut self, statement: &LetStatement) {
+        let name = &*statement.name.value.text();
+        let previous = self.scopes.last().and_then(|x| x.get(name)).cloned();
+        let declared = statement.annotation.as_ref().map(annotation_type);
+        let own_type = match &declared {
+            Some(x) => x.clone(),
+            None => self.fresh(),
+        };
+        // a function can call itself, so its name is known inside of the body
+        let is_function = matches!(statement.value, MonkeyExpression::FUNCTIONLITERAL(_));
+        if is_function {
+            self.define(name, Scheme::new(Vec::new(), own_type.clone()));
+        }
+        let value = self.check_expression(&statement.value);
+        if !self.unify(&own_type, &value) {
+            let message = match &declared {
+                Some(x) => format!("{} is declared as {}, but the value is {}", name, self.show(x), self.show(&value)),
+                None => format!("{} is used as {} in its own body, but is {}", name, self.show(&own_type), self.show(&value)),
+            };
+            self.error(statement.name.token.span, message);
+        }
+        let mut scheme = if is_function {
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.remove(name);
+            }
+            self.generalize(&own_type)
+        } else {
+            Scheme::new(Vec::new(), own_type)
+        };
+        // when the branch is not taken the name still has the previous value,
+        // so it keeps its type only if the new value has the same one
+        if let (Some(previous), true) = (previous, self.conditional.contains(&(statement as *const LetStatement))) {
+            if !previous.vars.is_empty() || !scheme.vars.is_empty() || !self.try_unify(&previous.ty, &scheme.ty) {
+                scheme = Scheme::new(Vec::new(), Type::ANY);
+            }
+        }
+        self.define(name, scheme);
+    }
+
+    fn add_return(&mut self, value: Type, span: Span) {
+        let declared = match self.frames.last() {
+            Some(frame) => frame.declared.clone(),
+            None => return,
+        };
+        match declared {
+            Some(declared) => {
+                if !self.unify(&declared, &value) {
+                    let message = format!("the function is declared to return {}, but returns {}", self.show(&declared), self.show(&value));
+                    self.error(span, message);
+                }
+            },
+            None => {
+                if let Some(frame) = self.frames.last_mut() {
+                    frame.returns.push(value);
+                }
+            },
+        }
+    }
+
+    // one type for all of the types, any if they do not fit together
+    fn join(&mut self, types: Vec<Type>) -> Type {
+        let result = self.fresh();
+        for ty in types {
+            if self.prune(&ty) == Type::ANY || !self.try_unify(&result, &ty) {
+                return Type::ANY
+            }
+        }
+        result
+    }
+
+    fn check_expression(&mut self, expression: &MonkeyExpression) -> Type {
+        match expression {
+            MonkeyExpression::IDENT(x) => self.lookup(&x.value.text()),
+            MonkeyExpression::INTEGERLITERAL(_) => Type::INT,
+            MonkeyExpression::STRINGLITERAL(_) => Type::STRING,
+            MonkeyExpression::BOOLEAN(_) => Type::BOOL,
+            MonkeyExpression::PREFIX(x) => self.check_prefix(x),
+            MonkeyExpression::INFIX(x) => self.check_infix(x),
+            MonkeyExpression::IF(x) => self.check_if(x),
+            MonkeyExpression::FUNCTIONLITERAL(x) => self.check_function(x),
+            MonkeyExpression::CALL(x) => self.check_call(x),
+            // the members of a module are not known
+            MonkeyExpression::MEMBER(x) => {
+                self.check_expression(&x.object);
+                Type::ANY
+            },
+            MonkeyExpression::ARRAY(x) => {
+                let elements: Vec<Type> = x.elements.iter().map(|x| self.check_expression(x)).collect();
+                Type::ARRAY(Box::new(self.join(elements)))
+            },
+            MonkeyExpression::HASH(x) => self.check_hash(x),
+            MonkeyExpression::INDEX(x) => self.check_index(x),
+        }
+    }
+
+    fn check_prefix(&mut self, expression: &PrefixExpression) -> Type {
+        let right = self.check_expression(&expression.right);
+        if expression.operator == "!" {
+            return Type::BOOL
+        }
+        if !self.unify(&Type::INT, &right) {
+            let message = format!("{} can only be used with int, found {}", expression.operator, self.show(&right));
+            self.error(expression.token.span, message);
+        }
+        Type::INT
+    }
+
+    fn check_infix(&mut self, expression: &InfixExpression) -> Type {
+        let left = self.check_expression(&expression.left);
+        let right = self.check_expression(&expression.right);
+        let operator = expression.operator.as_str();
+        let span = expression.token.span;
+        match operator {
+            "==" | "!=" => Type::BOOL,
+            "+" => {
+                if !self.try_unify(&left, &right) {
+                    // arrays with different elements can be added, the result has both of them
+                    if let (Type::ARRAY(_), Type::ARRAY(_)) = (self.prune(&left), self.prune(&right)) {
+                        return Type::ARRAY(Box::new(Type::ANY))
+                    }
+                    let message = format!("+ can not be used with {} and {}", self.show(&left), self.show(&right));
+                    self.error(span, message);
+                    return Type::ANY
+                }
+                if !self.constrain(&left, &ADDABLE) {
+                    let message = format!("+ can not be used with {}", self.show(&left));
+                    self.error(span, message);
+                    return Type::ANY
+                }
+                left
+            },
+            "<" | ">" | "<=" | ">=" => {
+                if !self.try_unify(&left, &right) {
+                    let message = format!("{} can not be used with {} and {}", operator, self.show(&left), self.show(&right));
+                    self.error(span, message);
+                } else if !self.constrain(&left, &COMPARABLE) {
+                    let message = format!("{} can not be used with {}", operator, self.show(&left));
+                    self.error(span, message);
+                }
+                Type::BOOL
+            },
+            _ => {
+                let left_is_int = self.unify(&Type::INT, &left);
+                let right_is_int = self.unify(&Type::INT, &right);
+                if !left_is_int || !right_is_int {
+                    let message = format!("{} can only be used with int, found {} and {}", operator, self.show(&left), self.show(&right));
+                    self.error(span, message);
+                }
+                Type::INT
+            },
+        }
+    }
+
+    // without an else the if is null when the condition is false
+    fn check_if(&mut self, expression: &IfExpression) -> Type {
+        let condition = self.check_expression(&expression.condition);
+        if !self.unify(&Type::BOOL, &condition) {
+            let message = format!("the condition of an if has to be a bool, found {}", self.show(&condition));
+            self.error(expression_span(&expression.condition), message);
+        }
+        let consequence = self.check_block(&expression.consequence);
+        match &expression.alternative {
+            Some(alternative) => {
+                let alternative = self.check_block(alternative);
+                self.join(vec![consequence, alternative])
+            },
+            None if self.prune(&consequence) == Type::NULL => Type::NULL,
+            None => Type::ANY,
+        }
+    }
+
+    fn check_function(&mut self, function: &FunctionLiteral) -> Type {
+        let mut scope: HashMap<String, Scheme> = HashMap::new();
+        let mut params: Vec<Type> = Vec::new();
+        for (param, annotation) in function.parameters.iter().zip(&function.parameter_types) {
+            let ty = match annotation {
+                Some(x) => annotation_type(x),
+                None => self.fresh(),
+            };
+            scope.insert(param.value.to_string(), Scheme::new(Vec::new(), ty.clone()));
+            params.push(ty);
+        }
+        let declared = function.return_type.as_ref().map(annotation_type);
+
+        self.scopes.push(scope);
+        self.frames.push(Frame { returns: Vec::new(), declared: declared.clone() });
+        let value = self.check_statements(&function.blockstatment.statements);
+        let span = match function.blockstatment.statements.last() {
+            Some(Statement::EXPRESSION(x)) => expression_span(&x.expression),
+            _ => function.token.span,
+        };
+        self.add_return(value, span);
+        let returns = self.frames.pop().map(|x| x.returns).unwrap_or_default();
+        self.scopes.pop();
+
+        let result = match declared {
+            Some(x) => x,
+            None => self.join(returns),
+        };
+        Type::FUNCTION(params, Box::new(result))
+    }
+
+    fn check_call(&mut self, expression: &CallExpression) -> Type {
+        let function = self.check_expression(&expression.function);
+        let mut args: Vec<(Type, Span)> = Vec::new();
+        for arg in expression.arguments.iter().flatten() {
+            args.push((self.check_expression(arg), expression_span(arg)));
+        }
+        let span = expression_span(&expression.function);
+        match self.prune(&function) {
+            Type::FUNCTION(params, result) => {
+                if params.len() != args.len() {
+                    self.error(span, format!("wrong number of arguments: expected {}, got {}", params.len(), args.len()));
+                    return *result
+                }
+                for (i, ((arg, arg_span), param)) in args.iter().zip(&params).enumerate() {
+                    if !self.unify(param, arg) {
+                        let message = format!("argument {} has to be {}, found {}", i + 1, self.show(param), self.show(arg));
+                        self.error(*arg_span, message);
+                    }
+                }
+                *result
+            },
+            // the call decides what kind of function the variable is
+            Type::VAR(_) => {
+                let result = self.fresh();
+                let called = Type::FUNCTION(args.into_iter().map(|(x, _)| x).collect(), Box::new(result.clone()));
+                if !self.unify(&function, &called) {
+                    let message = format!("{} can not be called like {}", self.show(&function), self.show(&called));
+                    self.error(span, message);
+                }
+                result
+            },
+            Type::ANY => Type::ANY,
+            other => {
+                let message = format!("{} is not a function", self.show(&other));
+                self.error(span, message);
+                Type::ANY
+            },
+        }
+    }
+
+    fn check_hash(&mut self, expression: &HashLiteral) -> Type {
+        let mut keys: Vec<Type> = Vec::new();
+        let mut values: Vec<Type> = Vec::new();
+        for (key, value) in &expression.pairs {
+            let key_type = self.check_expression(key);
+            if !self.constrain(&key_type, &HASHABLE) {
+                let message = format!("{} can not be used as a hash key", self.show(&key_type));
+                self.error(expression_span(key), message);
+            }
+            keys.push(key_type);
+            values.push(self.check_expression(value));
+        }
+        let key = self.join(keys);
+        let value = self.join(values);
+        Type::HASH(Box::new(key), Box::new(value))
+    }
+
+    // an index that is not there gives null, that is not checked
+    fn check_index(&mut self, expression: &IndexExpression) -> Type {
+        let left = self.check_expression(&expression.left);
+        let index = self.check_expression(&expression.index);
+        let index_span = expression_span(&expression.index);
+        match self.prune(&left) {
+            Type::ARRAY(element) => {
+                if !self.unify(&Type::INT, &index) {
+                    let message = format!("an array index has to be an int, found {}", self.show(&index));
+                    self.error(index_span, message);
+                }
+                *element
+            },
+            Type::STRING => {
+                if !self.unify(&Type::INT, &index) {
+                    let message = format!("a string index has to be an int, found {}", self.show(&index));
+                    self.error(index_span, message);
+                }
+                Type::STRING
+            },
+            // a key of another type is just not in the hash
+            Type::HASH(key, value) => {
+                if self.try_unify(&key, &index) {
+                    *value
+                } else {
+                    Type::ANY
+                }
+            },
+            Type::ANY => Type::ANY,
+            other => {
+                if !self.constrain(&other, &INDEXABLE) {
+                    let message = format!("{} can not be indexed", self.show(&other));
+                    self.error(expression.token.span, message);
+                }
+                Type::ANY
+            },
+        }
+    }
+}
+
+// the lets that might not run, only their addresses are kept to find them again while checking
+fn conditional_lets(program: &Programm) -> HashSet<*const LetStatement> {
+    let scopes = Scopes::new(program);
+    let mut lets: HashSet<*const LetStatement> = HashSet::new();
+    for definition in scopes.definitions.iter().filter(|x| x.conditional) {
+        if let DefinitionKind::LET(x) | DefinitionKind::EXPORT(x) = definition.kind {
+            lets.insert(x);
+        }
+    }
+    lets
+}
+
+fn annotation_type(annotation: &TypeAnnotation) -> Type {
+    match annotation {
+        TypeAnnotation::NAME(x) => match &*x.value.text() {
+            "int" => Type::INT,
+            "bool" => Type::BOOL,
+            "string" => Type::STRING,
+            _ => Type::ANY,
+        },
+        TypeAnnotation::ARRAY(_, element) => Type::ARRAY(Box::new(annotation_type(element))),
+        TypeAnnotation::HASH(_, key, value) => Type::HASH(Box::new(annotation_type(key)), Box::new(annotation_type(value))),
+        TypeAnnotation::FUNCTION(_, params, result) => {
+            Type::FUNCTION(params.iter().map(annotation_type).collect(), Box::new(annotation_type(result)))
+        },
+    }
+}
+
+fn replace_vars(ty: &Type, replacements: &HashMap<usize, Type>) -> Type {
+    match ty {
+        Type::VAR(x) => replacements.get(x).cloned().unwrap_or(Type::VAR(*x)),
+        Type::ARRAY(x) => Type::ARRAY(Box::new(replace_vars(x, replacements))),
+        Type::HASH(key, value) => Type::HASH(Box::new(replace_vars(key, replacements)), Box::new(replace_vars(value, replacements))),
+        Type::FUNCTION(params, result) => {
+            Type::FUNCTION(params.iter().map(|x| replace_vars(x, replacements)).collect(), Box::new(replace_vars(result, replacements)))
+        },
+        other => other.clone(),
+    }
+}
+
+fn expression_span(expression: &MonkeyExpression) -> Span {
+    match expression {
+        MonkeyExpression::IDENT(x) => x.token.span,
+        MonkeyExpression::INTEGERLITERAL(x) => x.token.span,
+        MonkeyExpression::STRINGLITERAL(x) => x.token.span,
+        MonkeyExpression::BOOLEAN(x) => x.token.span,
+        MonkeyExpression::PREFIX(x) => x.token.span,
+        MonkeyExpression::INFIX(x) => x.token.span,
+        MonkeyExpression::IF(x) => x.tok.span,
+        MonkeyExpression::FUNCTIONLITERAL(x) => x.token.span,
+        MonkeyExpression::CALL(x) => expression_span(&x.function),
+        MonkeyExpression::MEMBER(x) => x.token.span,
+        MonkeyExpression::ARRAY(x) => x.token.span,
+        MonkeyExpression::HASH(x) => x.token.span,
+        MonkeyExpression::INDEX(x) => x.token.span,
+    }
+}
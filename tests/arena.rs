@@ -0,0 +1,80 @@
+use monkey::arena::{self, Ast, Visitor};
+use monkey::ast::Programm;
+use monkey::lexer::Lexer;
+use monkey::parser::{ParseError, Parser};
+use monkey::token::Identifier;
+
+const SOURCE: &str = "/// adds two numbers
+export let add = fn(a: int, b: int) -> int { a + b };
+import \"lib/math\" as math;
+import { max, min } from \"lib/math\";
+let point: {string: int} = {\"x\": add(1, 2), \"y\": [1, 2, 3][0]};
+let pick = fn(p) {
+    let x = p[\"x\"];
+    if (x >= 3 | 1 << 2) { return -x; } else { !true }
+};
+math.max(pick(point), ~1 * (2 - 3) ^ 2);
+";
+
+fn parse_tree(source: &str) -> Result<Programm, ParseError> {
+    let mut lexer = Lexer::new(String::from(source));
+    Parser::new(&mut lexer).parse_programm()
+}
+
+fn parse_arena(source: &str) -> Result<Ast, ParseError> {
+    let mut lexer = Lexer::new(String::from(source));
+    Ast::parse(&mut lexer)
+}
+
+#[test]
+fn arena_builds_the_same_program_as_the_tree() {
+    let tree = parse_tree(SOURCE).unwrap();
+    let arena = parse_arena(SOURCE).unwrap();
+    let lowered = arena.to_program();
+    assert_eq!(lowered.statements, tree.statements);
+    assert_eq!(lowered.to_string(), tree.to_string());
+    assert_eq!(lowered.to_string(), parse_tree(&tree.to_string()).unwrap().to_string());
+}
+
+#[test]
+fn arena_reports_the_same_errors() {
+    for source in ["let = 5;", "fn(x { x }", "{\"a\" 1}", "if (x) { import \"a\" as a; }", ""] {
+        let tree = parse_tree(source).unwrap_err();
+        let arena = parse_arena(source).unwrap_err();
+        assert_eq!(tree, arena, "source: {:?}", source);
+    }
+}
+
+// collects the names and operators in the order they are visited
+#[derive(Default)]
+struct Collect {
+    visited: Vec<String>,
+}
+impl Visitor for Collect {
+    fn visit_identifier(&mut self, _ast: &Ast, identifier: &Identifier) {
+        self.visited.push(identifier.value.to_string());
+    }
+    fn visit_infix_expression(&mut self, ast: &Ast, expression: &arena::InfixExpression) {
+        self.visited.push(expression.operator.to_string());
+        arena::walk_infix_expression(self, ast, expression);
+    }
+}
+impl monkey::ast::Visitor<'_> for Collect {
+    fn visit_identifier(&mut self, identifier: &Identifier) {
+        self.visited.push(identifier.value.to_string());
+    }
+    fn visit_infix_expression(&mut self, expression: &monkey::token::InfixExpression) {
+        self.visited.push(expression.operator.clone());
+        monkey::ast::walk_infix_expression(self, expression);
+    }
+}
+
+#[test]
+fn visitor_walks_the_arena_like_the_tree() {
+    let mut tree = Collect::default();
+    monkey::ast::Visitor::visit_program(&mut tree, &parse_tree(SOURCE).unwrap());
+    let mut arena = Collect::default();
+    arena::Visitor::visit_program(&mut arena, &parse_arena(SOURCE).unwrap());
+    assert!(tree.visited.len() > 20);
+    assert_eq!(arena.visited, tree.visited);
+}
@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::Path;
+
+use monkey::ast::Programm;
+use monkey::ast_json::{from_json, to_json};
+use monkey::json;
+use monkey::lexer::Lexer;
+use monkey::parser::Parser;
+use monkey::stdlib::PRELUDE;
+
+fn parse(source: &str) -> Programm {
+    match Parser::new(&mut Lexer::new(String::from(source))).parse_programm() {
+        Ok(x) => x,
+        Err(err) => panic!("could not parse {:?}: {}", source, err),
+    }
+}
+
+// the json as text, parsed again and turned back into a program
+fn round_trip(program: &Programm) -> Programm {
+    let text = to_json(program).to_string();
+    let value = match json::parse(&text) {
+        Ok(x) => x,
+        Err(err) => panic!("could not parse the json {}: {}", text, err),
+    };
+    match from_json(&value) {
+        Ok(x) => x,
+        Err(err) => panic!("could not read the json {}: {}", text, err),
+    }
+}
+
+fn assert_round_trip(source: &str) {
+    let program = parse(source);
+    let read = round_trip(&program);
+    assert_eq!(read.statements, program.statements, "source: {:?}", source);
+    // the spans are not compared by ==, they have to come back the same as well
+    assert_eq!(to_json(&read).to_string(), to_json(&program).to_string(), "source: {:?}", source);
+    assert_eq!(read.to_string(), program.to_string(), "source: {:?}", source);
+}
+
+#[test]
+fn round_trips_the_golden_scripts_and_the_prelude() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+    let mut scripts: Vec<_> = fs::read_dir(dir).unwrap().map(|x| x.unwrap().path()).filter(|x| x.extension().is_some_and(|x| x == "mk")).collect();
+    scripts.sort();
+    let mut count = 0;
+    for script in scripts {
+        let source = fs::read_to_string(&script).unwrap();
+        if Parser::new(&mut Lexer::new(source.clone())).parse_programm().is_ok() {
+            assert_round_trip(&source);
+            count += 1;
+        }
+    }
+    assert!(count > 5, "only {} golden scripts parse", count);
+    assert_round_trip(PRELUDE);
+}
+
+#[test]
+fn round_trips_every_kind_of_node() {
+    let cases = [
+        "let x = 0xFF + 1_000;",
+        "/// doc\nexport let f = fn(a: int, b) -> [int] { return [a, b]; };",
+        "let g: fn(int) -> {string: bool} = fn(n) { {\"a\": n > 1} };",
+        "import { a, b } from \"lib\"; import \"lib\" as lib; lib.a[0](1)();",
+        "if (!true) { -1 } else { ~2 }; if (x) { y };",
+        "\"escaped \\\"quotes\\\" and größe\";",
+    ];
+    for source in cases {
+        assert_round_trip(source);
+    }
+}
+
+#[test]
+fn reads_nodes_written_without_spans() {
+    let text = r#"{"kind": "Programm", "statements": [
+        {"kind": "LetStatement", "name": {"kind": "Identifier", "name": "x"}, "value": {"kind": "IntegerLiteral", "value": 2}},
+        {"kind": "ExpressionStatement", "expression": {"kind": "InfixExpression", "operator": "*",
+            "left": {"kind": "Identifier", "name": "x"}, "right": {"kind": "IntegerLiteral", "value": 3}}}
+    ]}"#;
+    let program = from_json(&json::parse(text).unwrap()).unwrap();
+    assert_eq!(program.to_string(), "let x = 2;\nx * 3;");
+}
+
+#[test]
+fn reports_json_that_does_not_parse() {
+    let cases = [
+        ("", "unexpected end of input"),
+        ("{", "expected '\"' at 1"),
+        ("[1,]", "unexpected character at 3"),
+        ("{\"kind\": \"Programm\", \"statements\": [}", "unexpected character at 36"),
+        ("\"open", "unterminated string"),
+        ("{\"kind\": \"Programm\"} x", "unexpected character at 21"),
+        ("{\"a\" 1}", "expected ':' at 5"),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(json::parse(text), Err(String::from(expected)), "json: {:?}", text);
+    }
+}
+
+fn program_with(statement: &str) -> String {
+    format!("{{\"kind\": \"Programm\", \"statements\": [{}]}}", statement)
+}
+
+fn program_with_expression(expression: &str) -> String {
+    program_with(&format!("{{\"kind\": \"ExpressionStatement\", \"expression\": {}}}", expression))
+}
+
+#[test]
+fn reports_json_that_is_not_a_program() {
+    let cases = [
+        (String::from("[]"), "expected a node with a kind, found []"),
+        (String::from("{\"kind\": \"LetStatement\"}"), "expected Programm, found LetStatement"),
+        (String::from("{\"kind\": \"Programm\"}"), "Programm is missing \"statements\""),
+        (String::from("{\"kind\": \"Programm\", \"statements\": {}}"), "\"statements\" of Programm has to be an array"),
+        (program_with("{\"kind\": \"Identifier\", \"name\": \"x\"}"), "Identifier is not a statement"),
+        (program_with_expression("{\"kind\": \"Identifier\", \"name\": 5}"), "\"name\" of Identifier has to be a string"),
+        (program_with_expression("{\"kind\": \"IntegerLiteral\", \"value\": \"1\"}"), "\"value\" of IntegerLiteral has to be a number"),
+        (program_with_expression("{\"kind\": \"Boolean\", \"value\": 1}"), "\"value\" of Boolean has to be true or false"),
+        (program_with_expression("{\"kind\": \"PrefixExpression\", \"operator\": \"+\", \"right\": {\"kind\": \"Boolean\", \"value\": true}}"), "+ is not a prefix operator"),
+        (program_with_expression("{\"kind\": \"InfixExpression\", \"operator\": \"=\", \"left\": {\"kind\": \"Boolean\", \"value\": true}, \"right\": {\"kind\": \"Boolean\", \"value\": true}}"), "= is not an infix operator"),
+        (program_with_expression("{\"kind\": \"IfExpression\", \"condition\": {\"kind\": \"Boolean\", \"value\": true}, \"consequence\": {\"kind\": \"ArrayLiteral\", \"elements\": []}}"), "expected BlockStatement, found ArrayLiteral"),
+        (program_with_expression("{\"kind\": \"HashLiteral\", \"pairs\": [{\"key\": {\"kind\": \"Boolean\", \"value\": true}}]}"), "every pair of HashLiteral needs a \"key\" and a \"value\""),
+        (program_with_expression("{\"kind\": \"FunctionLiteral\", \"parameters\": [{\"kind\": \"Identifier\", \"name\": \"a\"}], \"parameterTypes\": [], \"body\": {\"kind\": \"BlockStatement\", \"statements\": []}}"), "FunctionLiteral needs one entry in \"parameterTypes\" for every parameter"),
+        (program_with_expression("{\"kind\": \"Loop\"}"), "Loop is not an expression"),
+        (program_with("{\"kind\": \"LetStatement\", \"name\": {\"kind\": \"Identifier\", \"name\": \"x\"}, \"value\": {\"kind\": \"IntegerLiteral\", \"value\": 1}, \"annotation\": {\"kind\": \"TypeName\", \"name\": \"float\"}}"), "unknown type float"),
+        (program_with("{\"kind\": \"LetStatement\", \"name\": {\"kind\": \"Identifier\", \"name\": \"x\"}, \"value\": {\"kind\": \"IntegerLiteral\", \"value\": 1}, \"doc\": 3}"), "\"doc\" of LetStatement has to be a string"),
+        (program_with("{\"kind\": \"LetStatement\", \"name\": {\"kind\": \"Identifier\", \"name\": \"x\"}}"), "LetStatement is missing \"value\""),
+        (program_with("{\"kind\": \"ExportStatement\", \"statement\": {\"kind\": \"ReturnStatement\"}}"), "expected LetStatement, found ReturnStatement"),
+    ];
+    for (text, expected) in cases {
+        let value = json::parse(&text).unwrap();
+        assert_eq!(from_json(&value).map(|x| x.to_string()), Err(String::from(expected)), "json: {}", text);
+    }
+}
@@ -0,0 +1,99 @@
+// the random source code the tests share. every test file is a crate of its own and uses only some of this
+#![allow(dead_code)]
+
+// xorshift, the same seed always gives the same programs
+pub fn next_random(seed: &mut u64) -> u64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    *seed
+}
+
+pub fn pick<'a>(seed: &mut u64, choices: &[&'a str]) -> &'a str {
+    choices[(next_random(seed) % choices.len() as u64) as usize]
+}
+
+// programs that parse, with every operator and every kind of expression. sub expressions are in parentheses,
+// so printing them does not depend on the precedence of the operators
+pub struct Generator {
+    seed: u64,
+    // functions are never bound twice and only call the ones defined before them, so nothing recurses
+    functions: usize,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Generator {
+        Generator { seed, functions: 0 }
+    }
+
+    pub fn program(&mut self) -> String {
+        // most programs start with the variables defined, so fewer of them stop at the first name
+        let mut statements: Vec<String> = Vec::new();
+        for name in ["a", "b", "c"] {
+            if next_random(&mut self.seed) % 4 < 3 {
+                statements.push(format!("let {} = {};", name, next_random(&mut self.seed) % 10));
+            }
+        }
+        for _ in 0..(next_random(&mut self.seed) % 8 + 1) {
+            statements.push(self.statement(2, &[]));
+        }
+        statements.join("\n")
+    }
+
+    pub fn statement(&mut self, depth: u32, params: &[&str]) -> String {
+        match next_random(&mut self.seed) % 10 {
+            0..=3 => format!("let {} = {};", pick(&mut self.seed, &["a", "b", "c"]), self.expression(3, params)),
+            4 if depth > 0 => {
+                let body: Vec<String> = (0..next_random(&mut self.seed) % 3 + 1).map(|_| self.statement(depth - 1, &["p", "q"])).collect();
+                let name = format!("f{}", self.functions);
+                self.functions += 1;
+                format!("let {} = fn(p, q) {{ {} }};", name, body.join(" "))
+            },
+            5 if depth > 0 => {
+                let consequence = self.statement(depth - 1, params);
+                let alternative = self.statement(depth - 1, params);
+                format!("if ({}) {{ {} }} else {{ {} }};", self.condition(params), consequence, alternative)
+            },
+            6 => format!("return {};", self.expression(2, params)),
+            _ => format!("{};", self.expression(3, params)),
+        }
+    }
+
+    fn condition(&mut self, params: &[&str]) -> String {
+        match next_random(&mut self.seed) % 3 {
+            0 => String::from(pick(&mut self.seed, &["true", "false"])),
+            _ => self.expression(2, params),
+        }
+    }
+
+    pub fn expression(&mut self, depth: u32, params: &[&str]) -> String {
+        let choice = if depth == 0 { next_random(&mut self.seed) % 4 } else { next_random(&mut self.seed) % 13 };
+        match choice {
+            0 => String::from(pick(&mut self.seed, &["0", "1", "2", "7", "64", "0b101", "0xff", "9223372036854775807"])),
+            1 => String::from(pick(&mut self.seed, &["3", "5", "true", "\"s\"", "[]", "{}"])),
+            2 | 3 => {
+                let mut names = vec!["a", "b", "c"];
+                names.extend_from_slice(params);
+                String::from(pick(&mut self.seed, &names))
+            },
+            4..=6 => {
+                let operator = pick(&mut self.seed, &["+", "-", "*", "/", "^", "<", ">", "<=", ">=", "==", "!=", "&", "|", "<<", ">>"]);
+                // small exponents only, 9 ^ (9 ^ 9) would take forever
+                let right = match operator {
+                    "^" => String::from(pick(&mut self.seed, &["0", "1", "2", "3", "-1", "true"])),
+                    _ => self.expression(depth - 1, params),
+                };
+                format!("({} {} {})", self.expression(depth - 1, params), operator, right)
+            },
+            7 => format!("({}{})", pick(&mut self.seed, &["-", "!", "~"]), self.expression(depth - 1, params)),
+            8 if self.functions > 0 => {
+                let function = next_random(&mut self.seed) as usize % self.functions;
+                format!("f{}({}, {})", function, self.expression(depth - 1, params), self.expression(depth - 1, params))
+            },
+            9 => format!("[{}, {}]", self.expression(depth - 1, params), self.expression(depth - 1, params)),
+            10 => format!("({}[{}])", self.expression(depth - 1, params), self.expression(depth - 1, params)),
+            11 => format!("({}.name)", self.expression(depth - 1, params)),
+            _ => format!("(if ({}) {{ {} }} else {{ {} }})", self.condition(params), self.expression(depth - 1, params), self.expression(depth - 1, params)),
+        }
+    }
+}
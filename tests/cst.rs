@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::Path;
+
+use monkey::ast::Statement;
+use monkey::cst::{self, SyntaxKind, TriviaKind};
+use monkey::lexer::Lexer;
+use monkey::parser::Parser;
+use monkey::stdlib::PRELUDE;
+
+fn parse(source: &str) -> Vec<Statement> {
+    match Parser::new(&mut Lexer::new(String::from(source))).parse_programm() {
+        Ok(x) => x.statements,
+        Err(err) => panic!("could not parse {:?}: {}", source, err),
+    }
+}
+
+// the prelude and every golden script that parses
+fn corpus() -> Vec<String> {
+    let mut programs = vec![String::from(PRELUDE)];
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+    let mut scripts: Vec<_> = fs::read_dir(dir).unwrap().map(|x| x.unwrap().path()).filter(|x| x.extension().is_some_and(|x| x == "mk")).collect();
+    scripts.sort();
+    for script in scripts {
+        let source = fs::read_to_string(script).unwrap();
+        if cst::parse(&source).is_ok() {
+            programs.push(source);
+        }
+    }
+    programs
+}
+
+#[test]
+fn writes_back_the_source_unchanged() {
+    let cases = [
+        "let x = 1;",
+        "  let   x=1 ;  \n\n",
+        "// comment\n/* block /* nested */ */ let x = (1 + 2) * 3; // trailing\n",
+        "/// doc\nexport let f = fn(a: int) -> int { a };\r\nf(1)",
+        "let s = \"größe \\\"quoted\\\"\"; if (s) { 1 } else { 2 }",
+        "import { a, b } from \"lib\";\nimport \"lib\" as lib;\nlib.a[0](1)",
+    ];
+    for source in cases {
+        assert_eq!(cst::parse(source).unwrap().to_string(), source);
+    }
+    for source in corpus() {
+        assert_eq!(cst::parse(&source).unwrap().to_string(), source);
+    }
+}
+
+#[test]
+fn keeps_whitespace_and_comments_as_trivia() {
+    let tree = cst::parse("// line\n/// doc\nlet x = /* block */ 1;").unwrap();
+    let tokens = tree.tokens();
+    let kinds: Vec<TriviaKind> = tokens[0].leading_trivia.iter().map(|x| x.kind).collect();
+    assert_eq!(kinds, vec![TriviaKind::LINECOMMENT, TriviaKind::WHITESPACE, TriviaKind::DOCCOMMENT, TriviaKind::WHITESPACE]);
+    assert_eq!(tokens[0].doc().as_deref(), Some("doc"));
+    assert_eq!(tokens[3].text, "1");
+    assert_eq!(tokens[3].leading_trivia[1].text, "/* block */");
+}
+
+#[test]
+fn records_a_node_for_every_statement_and_expression() {
+    let tree = cst::parse("let x = -(1 + 2);\nreturn f(x)[0];").unwrap();
+    assert_eq!(tree.kind, SyntaxKind::PROGRAM);
+    let statements: Vec<SyntaxKind> = tree.nodes().map(|x| x.kind).collect();
+    assert_eq!(statements, vec![SyntaxKind::LET, SyntaxKind::RETURN]);
+    let value = tree.nodes().next().unwrap().nodes().next().unwrap();
+    assert_eq!(value.kind, SyntaxKind::PREFIX);
+    assert_eq!(value.nodes().next().unwrap().kind, SyntaxKind::PAREN);
+    let returned = tree.nodes().nth(1).unwrap().nodes().next().unwrap();
+    assert_eq!(returned.kind, SyntaxKind::INDEX);
+    assert_eq!(returned.nodes().next().unwrap().kind, SyntaxKind::CALL);
+}
+
+#[test]
+fn lowers_to_the_statements_of_the_parser() {
+    for source in corpus() {
+        let lowered = cst::parse(&source).unwrap().lower().unwrap();
+        assert_eq!(lowered.statements, parse(&source));
+    }
+    // a single statement node becomes a program with that statement
+    let tree = cst::parse("let a = 1;\nlet b = a + 1;").unwrap();
+    let second = tree.nodes().nth(1).unwrap();
+    assert_eq!(second.lower().unwrap().statements, parse("let b = a + 1;"));
+    // doc comments end up on the tokens like the lexer puts them there
+    let lowered = cst::parse("/// adds\nlet add = fn(a, b) { a + b };").unwrap().lower().unwrap();
+    match &lowered.statements[0] {
+        Statement::LET(x) => assert_eq!(x.doc(), Some("adds")),
+        other => panic!("not a let statement: {:?}", other),
+    }
+}
+
+#[test]
+fn replaces_a_binding_and_keeps_the_rest_of_the_file() {
+    let source = "// numbers\nlet a = 1; // one\n\n/// two\nexport let b = 2;\nlet c = a + b;\n";
+    let mut tree = cst::parse(source).unwrap();
+    assert_eq!(tree.nodes().next().and_then(|x| x.binding_name()), Some("a"));
+    assert_eq!(tree.find_binding("a"), Some(0));
+    assert_eq!(tree.find_binding("b"), Some(1));
+    assert_eq!(tree.find_binding("c"), Some(2));
+    assert_eq!(tree.find_binding("d"), None);
+
+    let index = tree.find_binding("b").unwrap();
+    tree.replace_child(index, cst::parse_statement("export let b = 20 * 2;").unwrap());
+    assert_eq!(tree.to_string(), "// numbers\nlet a = 1; // one\n\n/// two\nexport let b = 20 * 2;\nlet c = a + b;\n");
+    assert_eq!(tree.lower().unwrap().statements, parse("let a = 1; export let b = 20 * 2; let c = a + b;"));
+
+    assert!(cst::parse_statement("let a = 1; let b = 2;").is_err());
+    assert!(cst::parse_statement("let a = ;").is_err());
+}
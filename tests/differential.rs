@@ -0,0 +1,534 @@
+// well-typed random programs are built right from the ast and run by every way there is to evaluate a program:
+// the generated tree itself, the printed source parsed again, the optimizer, the arena and the formatter.
+// all of them have to give the same results or the same error. a program they disagree on is shrunk
+// to the smallest one that still shows the difference before the test fails
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use monkey::arena::Ast;
+use monkey::ast::{walk_block_statement_mut, walk_expression_mut, walk_program_mut, MonkeyExpression, Programm, Statement, VisitorMut};
+use monkey::builtins::builtin_store;
+use monkey::evaluator::eval;
+use monkey::fmt::format_source;
+use monkey::lexer::Lexer;
+use monkey::object::Environment;
+use monkey::optimizer::optimize;
+use monkey::parser::Parser;
+use monkey::resolver;
+use monkey::symbol::Symbol;
+use monkey::token::*;
+use monkey::typecheck::{self, Type};
+
+mod common;
+use common::next_random;
+
+// the value of a program, the result of its last statement, as "<type> <value>", or the error.
+// the optimizer changes how many statements there are, so the results before the last one can not be compared
+type Outcome = Result<Option<String>, String>;
+
+type Engine = fn(&Programm) -> Outcome;
+
+const ENGINES: [(&str, Engine); 5] = [
+    ("tree", run_tree),
+    ("parsed", run_parsed),
+    ("optimized", run_optimized),
+    ("arena", run_arena),
+    ("formatted", run_formatted),
+];
+
+fn run(program: &Programm) -> Outcome {
+    let env = Rc::new(RefCell::new(Environment::from_store(builtin_store())));
+    match eval(&program.statements, env) {
+        Ok(results) => Ok(results.last().map(|x| format!("{} {}", x.type_name(), x.inspect()))),
+        Err(err) => Err(err.message),
+    }
+}
+
+fn parse(source: &str) -> Result<Programm, String> {
+    match Parser::new(&mut Lexer::new(String::from(source))).parse_programm() {
+        Ok(x) => Ok(x),
+        Err(err) => Err(format!("could not parse the printed program: {}", err)),
+    }
+}
+
+fn run_tree(program: &Programm) -> Outcome {
+    run(program)
+}
+
+fn run_parsed(program: &Programm) -> Outcome {
+    run(&parse(&program.to_string())?)
+}
+
+fn run_optimized(program: &Programm) -> Outcome {
+    run(&optimize(parse(&program.to_string())?))
+}
+
+fn run_arena(program: &Programm) -> Outcome {
+    match Ast::parse(&mut Lexer::new(program.to_string())) {
+        Ok(x) => run(&x.to_program()),
+        Err(err) => Err(format!("could not parse the printed program: {}", err)),
+    }
+}
+
+fn run_formatted(program: &Programm) -> Outcome {
+    match format_source(&program.to_string()) {
+        Ok(x) => run(&parse(&x)?),
+        Err(err) => Err(format!("could not format the printed program: {}", err)),
+    }
+}
+
+// what every engine gave, if not all of them gave the same
+fn disagreement(program: &Programm) -> Option<Vec<(&'static str, Outcome)>> {
+    let outcomes: Vec<(&str, Outcome)> = ENGINES.iter().map(|(name, engine)| (*name, engine(program))).collect();
+    if outcomes.iter().all(|(_, x)| *x == outcomes[0].1) {
+        None
+    } else {
+        Some(outcomes)
+    }
+}
+
+// tries to remove statements and to replace expressions by one of their parts or by a literal,
+// and keeps every change after which the program still fails and got shorter
+fn shrink(program: &Programm, fails: impl Fn(&Programm) -> bool) -> Programm {
+    let mut smallest = Programm { statements: program.statements.clone() };
+    let mut target = 0;
+    loop {
+        let mut shrinker = Shrinker { target, done: false };
+        let mut candidate = Programm { statements: smallest.statements.clone() };
+        shrinker.visit_program_mut(&mut candidate);
+        if !shrinker.done {
+            return smallest
+        }
+        resolver::resolve(&mut candidate);
+        if candidate.to_string().len() < smallest.to_string().len() && fails(&candidate) {
+            smallest = candidate;
+            target = 0;
+        } else {
+            target += 1;
+        }
+    }
+}
+
+// makes the change with the number target, counted in the order the changes are found
+struct Shrinker {
+    target: usize,
+    done: bool,
+}
+impl Shrinker {
+    // true if this is the change to make
+    fn next(&mut self) -> bool {
+        if self.done {
+            return false
+        }
+        if self.target == 0 {
+            self.done = true;
+            return true
+        }
+        self.target -= 1;
+        false
+    }
+
+    // removes a statement or keeps only the value of a let or a return
+    fn shrink_statements(&mut self, statements: &mut Vec<Statement>) {
+        for i in 0..statements.len() {
+            if self.next() {
+                statements.remove(i);
+                return
+            }
+            let value = match &statements[i] {
+                Statement::LET(x) => x.value.clone(),
+                Statement::RETURN(x) => x.return_value.clone(),
+                _ => continue,
+            };
+            if self.next() {
+                statements[i] = expression_statement(value);
+                return
+            }
+        }
+    }
+}
+impl VisitorMut for Shrinker {
+    fn visit_program_mut(&mut self, program: &mut Programm) {
+        self.shrink_statements(&mut program.statements);
+        walk_program_mut(self, program);
+    }
+    fn visit_block_statement_mut(&mut self, block: &mut BlockStatement) {
+        self.shrink_statements(&mut block.statements);
+        walk_block_statement_mut(self, block);
+    }
+    fn visit_expression_mut(&mut self, expression: &mut MonkeyExpression) {
+        if self.done {
+            return
+        }
+        // a literal that is not shorter than the one before is thrown away by shrink
+        let mut replacements = parts(expression);
+        replacements.push(int(0));
+        replacements.push(boolean(true));
+        for replacement in replacements {
+            if self.next() {
+                *expression = replacement;
+                return
+            }
+        }
+        walk_expression_mut(self, expression);
+    }
+}
+
+// the expressions an expression is made of, the value of a branch counts as a part of the if
+fn parts(expression: &MonkeyExpression) -> Vec<MonkeyExpression> {
+    let last_value = |block: &BlockStatement| match block.statements.last() {
+        Some(Statement::EXPRESSION(x)) => Some(x.expression.clone()),
+        _ => None,
+    };
+    match expression {
+        MonkeyExpression::PREFIX(x) => vec![(*x.right).clone()],
+        MonkeyExpression::INFIX(x) => vec![(*x.left).clone(), (*x.right).clone()],
+        MonkeyExpression::IF(x) => {
+            let mut parts = vec![(*x.condition).clone()];
+            parts.extend(last_value(&x.consequence));
+            parts.extend(x.alternative.as_ref().and_then(last_value));
+            parts
+        },
+        MonkeyExpression::CALL(x) => {
+            let mut parts = vec![(*x.function).clone()];
+            parts.extend(x.arguments.iter().flatten().cloned());
+            parts
+        },
+        MonkeyExpression::FUNCTIONLITERAL(x) => last_value(&x.blockstatment).into_iter().collect(),
+        MonkeyExpression::ARRAY(x) => x.elements.clone(),
+        MonkeyExpression::HASH(x) => x.pairs.iter().flat_map(|(key, value)| [key.clone(), value.clone()]).collect(),
+        MonkeyExpression::INDEX(x) => vec![(*x.left).clone(), (*x.index).clone()],
+        MonkeyExpression::MEMBER(x) => vec![(*x.object).clone()],
+        _ => Vec::new(),
+    }
+}
+
+// the nodes are built with the tokens the lexer gives for their source, so they print like parsed ones
+fn token(source: &str) -> Token {
+    Lexer::new(String::from(source)).next_token()
+}
+
+fn identifier(name: &str) -> Identifier {
+    Identifier::new(token(name), Symbol::intern(name))
+}
+
+fn int(value: i64) -> MonkeyExpression {
+    MonkeyExpression::INTEGERLITERAL(IntegerLiteral::new(token(&value.to_string()), value))
+}
+
+fn string(value: &str) -> MonkeyExpression {
+    MonkeyExpression::STRINGLITERAL(StringLiteral::new(Token::new(TokenType::STRING, value), String::from(value)))
+}
+
+fn boolean(value: bool) -> MonkeyExpression {
+    MonkeyExpression::BOOLEAN(Boolean::new(token(&value.to_string()), value))
+}
+
+fn prefix(operator: &str, right: MonkeyExpression) -> MonkeyExpression {
+    MonkeyExpression::PREFIX(PrefixExpression::new(token(operator), String::from(operator), right))
+}
+
+fn infix(operator: &str, left: MonkeyExpression, right: MonkeyExpression) -> MonkeyExpression {
+    MonkeyExpression::INFIX(InfixExpression::new(String::from(operator), token(operator), left, right))
+}
+
+fn call(function: MonkeyExpression, arguments: Vec<MonkeyExpression>) -> MonkeyExpression {
+    MonkeyExpression::CALL(CallExpression::new(token("("), function, Some(arguments)))
+}
+
+fn builtin(name: &str, arguments: Vec<MonkeyExpression>) -> MonkeyExpression {
+    call(MonkeyExpression::IDENT(identifier(name)), arguments)
+}
+
+fn index(left: MonkeyExpression, index: MonkeyExpression) -> MonkeyExpression {
+    MonkeyExpression::INDEX(IndexExpression::new(token("["), left, index))
+}
+
+fn block(statements: Vec<Statement>) -> BlockStatement {
+    BlockStatement::new(token("{"), statements)
+}
+
+fn let_statement(name: &str, value: MonkeyExpression) -> Statement {
+    Statement::LET(LetStatement::new(token("let"), identifier(name), value))
+}
+
+fn expression_statement(expression: MonkeyExpression) -> Statement {
+    Statement::EXPRESSION(ExpressionStatement::new(token(";"), expression))
+}
+
+struct Generator {
+    seed: u64,
+    // the variables that can be used, one scope for every function the generator is in
+    scopes: Vec<Vec<(String, Type)>>,
+    variables: usize,
+}
+
+impl Generator {
+    fn new(seed: u64) -> Generator {
+        Generator { seed, scopes: vec![Vec::new()], variables: 0 }
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        next_random(&mut self.seed) % n
+    }
+
+    fn program(&mut self) -> Programm {
+        let mut statements: Vec<Statement> = (0..self.below(6) + 1).map(|_| self.statement(3)).collect();
+        let ty = self.random_type(1);
+        statements.push(expression_statement(self.expression(&ty, 3)));
+        let mut program = Programm { statements };
+        resolver::resolve(&mut program);
+        program
+    }
+
+    fn random_type(&mut self, depth: u32) -> Type {
+        match self.below(if depth > 0 { 8 } else { 5 }) {
+            0 | 1 => Type::INT,
+            2 => Type::BOOL,
+            3 | 4 => Type::STRING,
+            5 | 6 => Type::ARRAY(Box::new(self.random_type(0))),
+            _ => {
+                let params = (0..self.below(3)).map(|_| self.random_type(0)).collect();
+                Type::FUNCTION(params, Box::new(self.random_type(depth - 1)))
+            },
+        }
+    }
+
+    // the innermost variable of every name that has the type
+    fn variables_of(&self, ty: &Type) -> Vec<String> {
+        let mut seen: Vec<&String> = Vec::new();
+        let mut found = Vec::new();
+        for scope in self.scopes.iter().rev() {
+            for (name, var_ty) in scope.iter().rev() {
+                if !seen.contains(&name) {
+                    seen.push(name);
+                    if var_ty == ty {
+                        found.push(name.clone());
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    fn statement(&mut self, depth: u32) -> Statement {
+        match self.below(10) {
+            // rebinds a variable with a value of the same type
+            0 | 1 => {
+                let all: Vec<(String, Type)> = self.scopes.iter().flatten().cloned().collect();
+                if all.is_empty() {
+                    return self.statement(depth)
+                }
+                let (name, ty) = all[self.below(all.len() as u64) as usize].clone();
+                if !self.variables_of(&ty).contains(&name) {
+                    return self.statement(depth)
+                }
+                let value = self.expression(&ty, depth);
+                let_statement(&name, value)
+            },
+            2 if depth > 0 => {
+                // the lets in the branches are not visible after the if, they might not have run
+                let condition = self.expression(&Type::BOOL, depth - 1);
+                let consequence = self.branch(depth - 1);
+                let alternative = self.branch(depth - 1);
+                let expression = MonkeyExpression::IF(IfExpression::new(token("if"), Box::new(condition), consequence, Some(alternative)));
+                expression_statement(expression)
+            },
+            3 if depth > 0 => {
+                let ty = self.random_type(1);
+                expression_statement(self.expression(&ty, depth))
+            },
+            _ => {
+                let ty = self.random_type(1);
+                let value = self.expression(&ty, depth);
+                let name = format!("v{}", self.variables);
+                self.variables += 1;
+                self.scopes.last_mut().unwrap().push((name.clone(), ty));
+                let_statement(&name, value)
+            },
+        }
+    }
+
+    fn branch(&mut self, depth: u32) -> BlockStatement {
+        let known = self.scopes.last().unwrap().len();
+        let statements = (0..self.below(3)).map(|_| self.statement(depth)).collect();
+        self.scopes.last_mut().unwrap().truncate(known);
+        block(statements)
+    }
+
+    fn expression(&mut self, ty: &Type, depth: u32) -> MonkeyExpression {
+        if depth == 0 || self.below(4) == 0 {
+            let variables = self.variables_of(ty);
+            if !variables.is_empty() && self.below(2) == 0 {
+                return MonkeyExpression::IDENT(identifier(&variables[self.below(variables.len() as u64) as usize]))
+            }
+            return self.literal(ty)
+        }
+        match self.below(12) {
+            0 => {
+                let condition = self.expression(&Type::BOOL, depth - 1);
+                let consequence = self.body(ty, depth - 1, false);
+                let alternative = self.body(ty, depth - 1, false);
+                MonkeyExpression::IF(IfExpression::new(token("if"), Box::new(condition), consequence, Some(alternative)))
+            },
+            1 => {
+                let params: Vec<Type> = (0..self.below(3)).map(|_| self.random_type(0)).collect();
+                let function = self.expression(&Type::FUNCTION(params.clone(), Box::new(ty.clone())), depth - 1);
+                let arguments = params.iter().map(|x| self.expression(x, depth - 1)).collect();
+                call(function, arguments)
+            },
+            // arrays are never empty, so the first element is always there
+            2 => {
+                let array = self.expression(&Type::ARRAY(Box::new(ty.clone())), depth - 1);
+                index(array, int(0))
+            },
+            3 => {
+                let keys = ["a", "b", "c"];
+                let pairs: Vec<(MonkeyExpression, MonkeyExpression)> = keys.iter().map(|x| (string(x), self.expression(ty, depth - 1))).collect();
+                let key = keys[self.below(3) as usize];
+                index(MonkeyExpression::HASH(HashLiteral::new(token("{"), pairs)), string(key))
+            },
+            _ => self.typed_expression(ty, depth),
+        }
+    }
+
+    fn typed_expression(&mut self, ty: &Type, depth: u32) -> MonkeyExpression {
+        match ty {
+            Type::INT => match self.below(8) {
+                0 => prefix(["-", "~"][self.below(2) as usize], self.expression(ty, depth - 1)),
+                1 => {
+                    let operator = ["^", "<<", ">>"][self.below(3) as usize];
+                    // small right sides, 2 ^ 2 ^ 2 ^ 2 ^ 2 is already too big
+                    infix(operator, self.expression(ty, depth - 1), int(self.below(5) as i64))
+                },
+                2 => {
+                    let element = self.random_type(0);
+                    let array = self.expression(&Type::ARRAY(Box::new(element)), depth - 1);
+                    builtin("len", vec![array])
+                },
+                3 => builtin("len", vec![self.expression(&Type::STRING, depth - 1)]),
+                _ => {
+                    let operator = ["+", "-", "*", "/", "&", "|"][self.below(6) as usize];
+                    infix(operator, self.expression(ty, depth - 1), self.expression(ty, depth - 1))
+                },
+            },
+            Type::BOOL => match self.below(5) {
+                0 => prefix("!", self.expression(ty, depth - 1)),
+                1 => {
+                    let operator = ["==", "!="][self.below(2) as usize];
+                    infix(operator, self.expression(ty, depth - 1), self.expression(ty, depth - 1))
+                },
+                2 => {
+                    let operator = ["<", ">", "==", "!="][self.below(4) as usize];
+                    infix(operator, self.expression(&Type::STRING, depth - 1), self.expression(&Type::STRING, depth - 1))
+                },
+                _ => {
+                    let operator = ["<", ">", "<=", ">=", "==", "!="][self.below(6) as usize];
+                    infix(operator, self.expression(&Type::INT, depth - 1), self.expression(&Type::INT, depth - 1))
+                },
+            },
+            Type::STRING => match self.below(4) {
+                0 => builtin("str", vec![self.expression(&Type::INT, depth - 1)]),
+                1 => builtin(["upper", "lower"][self.below(2) as usize], vec![self.expression(ty, depth - 1)]),
+                _ => infix("+", self.expression(ty, depth - 1), self.expression(ty, depth - 1)),
+            },
+            Type::ARRAY(element) => match self.below(3) {
+                0 => builtin("push", vec![self.expression(ty, depth - 1), self.expression(element, depth - 1)]),
+                1 => infix("+", self.expression(ty, depth - 1), self.expression(ty, depth - 1)),
+                _ => self.literal(ty),
+            },
+            Type::FUNCTION(params, result) => self.function(params, result, depth),
+            _ => self.literal(ty),
+        }
+    }
+
+    fn literal(&mut self, ty: &Type) -> MonkeyExpression {
+        match ty {
+            Type::INT => match self.below(10) {
+                0 => int(9223372036854775807),
+                _ => int(self.below(20) as i64),
+            },
+            Type::BOOL => boolean(self.below(2) == 0),
+            Type::STRING => string(["", "a", "monkey", "größe", "say \"hi\"\n"][self.below(5) as usize]),
+            Type::ARRAY(element) => {
+                let elements = (0..self.below(3) + 1).map(|_| self.expression(element, 0)).collect();
+                MonkeyExpression::ARRAY(ArrayLiteral::new(token("["), elements))
+            },
+            Type::FUNCTION(params, result) => self.function(params, result, 0),
+            _ => int(0),
+        }
+    }
+
+    fn function(&mut self, params: &[Type], result: &Type, depth: u32) -> MonkeyExpression {
+        // the same parameter names in every function, so inner ones hide outer ones
+        let names: Vec<String> = (0..params.len()).map(|i| format!("p{}", i)).collect();
+        self.scopes.push(names.iter().cloned().zip(params.iter().cloned()).collect());
+        let body = self.body(result, depth.saturating_sub(1), true);
+        self.scopes.pop();
+        let parameters = names.iter().map(|x| identifier(x)).collect();
+        MonkeyExpression::FUNCTIONLITERAL(Rc::new(FunctionLiteral::new(token("fn"), parameters, body)))
+    }
+
+    // a block that ends with a value of the type. the body of a function may end with a return,
+    // a return in a branch would be the value of the if and not leave the function
+    fn body(&mut self, ty: &Type, depth: u32, function: bool) -> BlockStatement {
+        let known = self.scopes.last().unwrap().len();
+        let mut statements: Vec<Statement> = (0..self.below(3)).map(|_| self.statement(depth)).collect();
+        let value = self.expression(ty, depth);
+        if function && self.below(4) == 0 {
+            statements.push(Statement::RETURN(ReturnStatement::new(token("return"), value)));
+        } else {
+            statements.push(expression_statement(value));
+        }
+        self.scopes.last_mut().unwrap().truncate(known);
+        block(statements)
+    }
+}
+
+fn report(program: &Programm, outcomes: &[(&str, Outcome)]) -> String {
+    let mut report = format!("the engines disagree on:\n{}\n", program);
+    for (name, outcome) in outcomes {
+        report.push_str(&format!("{:>10}: {:?}\n", name, outcome));
+    }
+    report
+}
+
+#[test]
+fn engines_agree_on_random_programs() {
+    let mut seed: u64 = 0x853c_49e6_748f_ea9b;
+    for _ in 0..1500 {
+        let program = Generator::new(next_random(&mut seed)).program();
+        if let Some(outcomes) = disagreement(&program) {
+            let smallest = shrink(&program, |x| disagreement(x).is_some());
+            let outcomes_of_smallest = disagreement(&smallest).unwrap();
+            panic!("{}\nshrunk from:\n{}", report(&smallest, &outcomes_of_smallest), report(&program, &outcomes));
+        }
+    }
+}
+
+#[test]
+fn generated_programs_are_well_typed() {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    for _ in 0..500 {
+        let program = Generator::new(next_random(&mut seed)).program();
+        let errors = typecheck::check(&program);
+        assert!(errors.is_empty(), "{:?} in:\n{}", errors, program);
+    }
+}
+
+#[test]
+fn shrinks_to_a_minimal_program() {
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let has_division = |program: &Programm| program.to_string().contains(" / ");
+    let mut checked = 0;
+    while checked < 20 {
+        let program = Generator::new(next_random(&mut seed)).program();
+        if !has_division(&program) {
+            continue
+        }
+        // only one division of two literals with a single digit each is left, like "3 / 7;"
+        let smallest = shrink(&program, has_division);
+        assert!(has_division(&smallest) && smallest.to_string().len() == "0 / 0;".len(), "{:?} shrunk from:\n{}", smallest.to_string(), program);
+        checked += 1;
+    }
+}
@@ -0,0 +1,281 @@
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+
+use monkey::ast::Visitor;
+use monkey::evaluator::{eval, EvalError};
+use monkey::lexer::Lexer;
+use monkey::module::{self, ModuleLoader};
+use monkey::object::{Environment, MonkeyObject};
+use monkey::parser::Parser;
+use monkey::token::Identifier;
+
+// evaluates the source like a file without a path, with the builtins and the prelude, and returns the last result
+fn try_run(source: &str) -> Result<MonkeyObject, EvalError> {
+    let mut lexer = Lexer::new(String::from(source));
+    let program = match Parser::new(&mut lexer).parse_programm() {
+        Ok(x) => x,
+        Err(err) => panic!("could not parse {:?}: {}", source, err),
+    };
+    let loader = Rc::new(RefCell::new(ModuleLoader::new(Vec::new())));
+    let prelude = module::prelude_env(&loader).unwrap();
+    let env = Rc::new(RefCell::new(Environment::new_module(None, loader, Some(prelude))));
+    let mut results = eval(&program.statements, env)?;
+    Ok(results.pop().unwrap())
+}
+
+fn run(source: &str) -> MonkeyObject {
+    match try_run(source) {
+        Ok(x) => x,
+        Err(err) => panic!("could not evaluate {:?}: {}", source, err),
+    }
+}
+
+// every case has to evaluate to a value of the given type that inspects to the expected string
+fn check(type_name: &str, cases: &[(&str, &str)]) {
+    for (source, expected) in cases {
+        let result = run(source);
+        assert_eq!((result.type_name(), result.inspect().as_str()), (type_name, *expected), "source: {:?}", source);
+    }
+}
+
+fn check_errors(cases: &[(&str, &str)]) {
+    for (source, expected) in cases {
+        match try_run(source) {
+            Ok(x) => panic!("{:?} should fail, got {}", source, x.inspect()),
+            Err(err) => assert_eq!(err.message, *expected, "source: {:?}", source),
+        }
+    }
+}
+
+#[test]
+fn evaluates_integers() {
+    check("INTEGER", &[
+        ("5", "5"),
+        ("-5", "-5"),
+        ("2 + 3 * 4 - 1", "13"),
+        ("(2 + 3) * 4", "20"),
+        ("7 / 2", "3"),
+        ("-7 / 2", "-3"),
+        ("2 ^ 10", "1024"),
+        ("0xFF + 0o17 + 0b101 + 1_000", "1275"),
+        ("6 & 3", "2"),
+        ("6 | 3", "7"),
+        ("~0", "-1"),
+        ("1 << 4", "16"),
+        ("-16 >> 2", "-4"),
+        // results that do not fit into 64 bits become big integers
+        ("9223372036854775807 + 1", "9223372036854775808"),
+        ("2 ^ 100", "1267650600228229401496703205376"),
+        ("(2 ^ 100) - (2 ^ 100) + 1", "1"),
+    ]);
+}
+
+#[test]
+fn evaluates_strings() {
+    check("STRING", &[
+        ("\"hello\"", "hello"),
+        ("\"hello\" + \" \" + \"world\"", "hello world"),
+        ("\"a\\tb\"", "a\tb"),
+        ("let s = \"abc\"; s", "abc"),
+    ]);
+}
+
+#[test]
+fn evaluates_booleans() {
+    check("BOOLEAN", &[
+        ("true", "true"),
+        ("!true", "false"),
+        ("!!false", "false"),
+        ("!5", "false"),
+        ("1 < 2", "true"),
+        ("1 >= 2", "false"),
+        ("1 == 1", "true"),
+        ("1 != 1", "false"),
+        ("true == false", "false"),
+        ("true != false", "true"),
+        ("(1 < 2) == true", "true"),
+        ("\"a\" == \"a\"", "true"),
+        ("\"a\" < \"b\"", "true"),
+        ("2 ^ 64 > 2 ^ 63", "true"),
+    ]);
+}
+
+#[test]
+fn evaluates_null() {
+    check("NULL", &[
+        ("let x = 1;", "Null"),
+        ("if (false) { 1 }", "Null"),
+        ("[1, 2][5]", "Null"),
+        ("{\"a\": 1}[\"b\"]", "Null"),
+    ]);
+}
+
+#[test]
+fn evaluates_return_values() {
+    check("RETURN", &[
+        ("return 10; 9;", "10"),
+        ("9; return 2 * 5; 9;", "10"),
+        ("if (true) { if (true) { return 10; } return 1; }", "10"),
+    ]);
+    // a return only leaves the function it is in
+    check("INTEGER", &[
+        ("let f = fn() { return 1; 2 }; f() + 1", "2"),
+        ("let f = fn(x) { if (x > 0) { return x; }; -x }; f(-3) + f(4)", "7"),
+    ]);
+}
+
+#[test]
+fn evaluates_functions_and_closures() {
+    check("FUNCTION", &[
+        ("fn(x, y) { x + y }", "fn(x, y) {...}"),
+        ("let adder = fn(x) { fn(y) { x + y } }; adder(1)", "fn(y) {...}"),
+    ]);
+    check("INTEGER", &[
+        ("let add = fn(a, b) { a + b }; add(2, add(3, 4))", "9"),
+        ("fn(x) { x * 2 }(21)", "42"),
+        ("let adder = fn(x) { fn(y) { x + y } }; let add2 = adder(2); add2(40)", "42"),
+        ("let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(15)", "610"),
+        ("let counter = fn(x) { if (x > 0) { counter(x - 1) } else { 0 } }; counter(50)", "0"),
+        ("let x = 1; let f = fn() { let x = 2; x }; f() + x", "3"),
+    ]);
+}
+
+// the locals of a program in order, with a * after the ones that are moved out of their slot
+struct Locals {
+    names: Vec<String>,
+}
+impl Visitor<'_> for Locals {
+    fn visit_identifier(&mut self, identifier: &Identifier) {
+        if let Some(x) = identifier.resolved {
+            self.names.push(format!("{}{}", identifier.value, if x.last_use { "*" } else { "" }));
+        }
+    }
+}
+
+#[test]
+fn moves_locals_that_are_read_for_the_last_time() {
+    let cases = [
+        ("fn(acc, x) { push(acc, x) }", "acc x acc* x*"),
+        ("fn(acc) { push(acc, acc) }", "acc acc acc"),
+        ("fn(acc, x) { if (x) { push(acc, x) } else { acc } }", "acc x x acc* x* acc*"),
+        ("fn(a) { return a; a + 1 }", "a a* a"),
+        ("fn(a) { let b = a; b }", "a b a b*"),
+        ("fn(a) { push(a, len(a)) }", "a a a"),
+        ("fn(a) { f(a)[0] }", "a a"),
+        // a nested function might run after the call, it needs the slot
+        ("fn(acc) { let f = fn() { acc }; push(acc, 1) }", "acc f acc acc"),
+        ("fn(a) { fn(b) { push(a, b) } }", "a b a b*"),
+    ];
+    for (source, expected) in cases {
+        let mut lexer = Lexer::new(String::from(source));
+        let program = Parser::new(&mut lexer).parse_programm().unwrap();
+        let mut locals = Locals { names: Vec::new() };
+        locals.visit_program(&program);
+        assert_eq!(locals.names.join(" "), expected, "source: {:?}", source);
+    }
+    check("ARRAY", &[
+        // the array stays in the global a, push copies it
+        ("let a = [1]; let f = fn(x) { push(x, 2) }; let b = f(a); [a, b]", "[[1], [1, 2]]"),
+        ("let f = fn(acc) { let g = fn() { len(acc) }; let r = push(acc, 1); [g(), r] }; f([5])", "[1, [5, 1]]"),
+        ("reduce(range(5), [], fn(acc, x) { push(acc, x * x) })", "[0, 1, 4, 9, 16]"),
+    ]);
+    // a slot that was never set is still looked up outside of the function
+    check("INTEGER", &[("let x = 10; let f = fn() { if (false) { let x = 1; }; x }; f()", "10")]);
+}
+
+#[test]
+fn evaluates_builtins() {
+    check("BUILTIN", &[("len", "builtin len")]);
+    check("INTEGER", &[
+        ("len(\"four\")", "4"),
+        ("len([1, 2, 3])", "3"),
+        ("first([7, 8])", "7"),
+        ("sum(map([1, 2, 3], fn(x) { x * x }))", "14"),
+    ]);
+    check("ARRAY", &[
+        ("push([1], 2)", "[1, 2]"),
+        ("rest([1, 2, 3])", "[2, 3]"),
+        ("filter(range(10), fn(x) { x / 2 * 2 == x })", "[0, 2, 4, 6, 8]"),
+        ("reverse([1, [2], \"three\"])", "[three, [2], 1]"),
+        ("let a = [1, 2]; let b = reverse(a); [a, b]", "[[1, 2], [2, 1]]"),
+        ("reverse([])", "[]"),
+    ]);
+    check("STRING", &[
+        ("reverse(\"größe\")", "eßörg"),
+        ("join([1, \"two\", [3]], \", \")", "1, two, [3]"),
+        ("join([], \",\")", ""),
+        ("join(reverse(split(\"a-b-c\", \"-\")), \"+\")", "c+b+a"),
+    ]);
+    check_errors(&[
+        ("reverse(1)", "argument to reverse not supported, got INTEGER"),
+        ("join(\"abc\", \",\")", "argument to join not supported, got STRING"),
+        ("join([1], 2)", "argument to join not supported, got INTEGER"),
+    ]);
+}
+
+#[test]
+fn evaluates_arrays() {
+    check("ARRAY", &[
+        ("[]", "[]"),
+        ("[1, 2 * 2, \"three\", [true]]", "[1, 4, three, [true]]"),
+        ("let a = [1, 2]; let b = push(a, 3); a", "[1, 2]"),
+    ]);
+    check("INTEGER", &[
+        ("[1, 2, 3][0]", "1"),
+        ("[1, 2, 3][1 + 1]", "3"),
+        ("let a = [[1, 2], [3, 4]]; a[1][0]", "3"),
+    ]);
+}
+
+#[test]
+fn evaluates_hashes() {
+    check("HASH", &[
+        ("{}", "{}"),
+        // keys are ordered by their type and then by their value
+        ("{\"b\": 2, \"a\": 1}", "{a: 1, b: 2}"),
+        ("{1: \"one\", true: \"yes\", \"k\": [1]}", "{1: one, k: [1], true: yes}"),
+        ("{\"a\": 1, \"a\": 2}", "{a: 2}"),
+    ]);
+    check("INTEGER", &[
+        ("{\"a\": 1, \"b\": 2}[\"b\"]", "2"),
+        ("let key = \"x\"; {key: 5}[\"x\"]", "5"),
+        ("{1 + 1: 4}[2]", "4"),
+    ]);
+}
+
+#[test]
+fn evaluates_modules() {
+    let dir = std::env::temp_dir().join(format!("monkey-evaluator-test-{}", std::process::id()));
+    fs::create_dir_all(dir.join("lib")).unwrap();
+    fs::write(dir.join("lib").join("math.mk"), "export let square = fn(x) { x * x };\nlet hidden = 1;\nexport let two = 2;\n").unwrap();
+    fs::write(dir.join("main.mk"), "import \"lib/math\" as math;\nimport { two } from \"lib/math\";\nmath;\nmath.square(two);\n").unwrap();
+
+    let loader = Rc::new(RefCell::new(ModuleLoader::new(Vec::new())));
+    let results = module::run_file(&loader, &dir.join("main.mk"));
+    fs::remove_dir_all(&dir).unwrap();
+    let results = results.unwrap();
+
+    let module = &results[2];
+    assert_eq!(module.type_name(), "MODULE");
+    assert!(module.inspect().starts_with("module(") && module.inspect().ends_with("math.mk)"));
+    assert_eq!(results[3].inspect(), "4");
+}
+
+#[test]
+fn reports_evaluation_errors() {
+    check_errors(&[
+        ("x", "Undefined Identifier used: x"),
+        ("1 + true", "illegal infix expr: INTEGER + BOOLEAN"),
+        ("\"a\" - \"b\"", "illegal operator for strings: -"),
+        ("-true", "The minus operator can only be used for numeric values. you tried to use it on: BOOLEAN"),
+        ("~\"a\"", "The ~ operator can only be used for numeric values. you tried to use it on: STRING"),
+        ("5(1)", "not a function: INTEGER"),
+        ("fn(x) { x }(1, 2)", "wrong number of arguments: expected 1, got 2"),
+        ("1[0]", "index operator not supported: INTEGER[INTEGER]"),
+        ("5.x", "can not access x on INTEGER"),
+        ("import \"nowhere\" as n;", "could not find module \"nowhere\""),
+        // an error inside of a function stops the whole program
+        ("let f = fn() { y }; f(); 1", "Undefined Identifier used: y"),
+    ]);
+}
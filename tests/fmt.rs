@@ -0,0 +1,214 @@
+use std::process::Command;
+
+use monkey::ast::Statement;
+use monkey::fmt::format_source;
+use monkey::lexer::Lexer;
+use monkey::parser::Parser;
+use monkey::stdlib::PRELUDE;
+use monkey::token::TokenType;
+
+mod common;
+use common::{next_random, Generator};
+
+fn parse(source: &str) -> Vec<Statement> {
+    let mut lexer = Lexer::new(String::from(source));
+    match Parser::new(&mut lexer).parse_programm() {
+        Ok(x) => x.statements,
+        Err(err) => panic!("could not parse {:?}: {}", source, err),
+    }
+}
+
+// parse(fmt(x)) == parse(x) and formatting twice changes nothing
+fn assert_round_trip(source: &str) {
+    let formatted = format_source(source).unwrap();
+    assert_eq!(parse(&formatted), parse(source), "formatting changed the program:\n{}", formatted);
+    assert_eq!(format_source(&formatted).unwrap(), formatted, "formatting is not idempotent");
+}
+
+#[test]
+fn formats_to_canonical_source() {
+    let cases = [
+        ("let   x=1+2*3;", "let x = 1 + 2 * 3;\n"),
+        ("let x = (1 + 2) * 3;", "let x = (1 + 2) * 3;\n"),
+        ("let x = ((1 + 2)) + 3;", "let x = 1 + 2 + 3;\n"),
+        ("let x = 1 - (2 - 3);", "let x = 1 - (2 - 3);\n"),
+        ("let x = (1 - 2) - 3;", "let x = 1 - 2 - 3;\n"),
+        ("-(a + b)", "-(a + b);\n"),
+        ("(-a) + b", "-a + b;\n"),
+        ("!(!a)", "!(!a);\n"),
+        ("let a = -(-1);", "let a = -(-1);\n"),
+        ("let a = --1;", "let a = -(-1);\n"),
+        ("-(~(!a))", "-(~(!a));\n"),
+        ("(-a)[0]", "(-a)[0];\n"),
+        ("-(a[0])", "-a[0];\n"),
+        ("(a + b)(c)", "(a + b)(c);\n"),
+        ("lib.f(1)(2)", "lib.f(1)(2);\n"),
+        ("1 << 2 | 3 & 4", "1 << 2 | 3 & 4;\n"),
+        ("(1 | 2) & 3", "(1 | 2) & 3;\n"),
+        ("let x = 0xFF_FF;", "let x = 0xFF_FF;\n"),
+        ("let s = \"a\\\"b\\\\c\\nd\";", "let s = \"a\\\"b\\\\c\\nd\";\n"),
+        ("[1,2 , [3]]", "[1, 2, [3]];\n"),
+        ("{\"a\":1,2:[]}", "{\"a\": 1, 2: []};\n"),
+        ("{}", "{};\n"),
+        ("f()", "f();\n"),
+        ("let f = fn(){};", "let f = fn() {};\n"),
+        ("let f = fn(x){x*2};", "let f = fn(x) { x * 2 };\n"),
+        ("import \"lib/a.mk\" as a;", "import \"lib/a.mk\" as a;\n"),
+        ("import {a,b} from \"lib\";", "import { a, b } from \"lib\";\n"),
+        ("/// doc\n///\n/// more\nexport let a = 1;", "/// doc\n///\n/// more\nexport let a = 1;\n"),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(format_source(source).unwrap(), expected, "source: {:?}", source);
+        assert_round_trip(source);
+    }
+    assert_eq!(format_source("  \n ").unwrap(), "");
+}
+
+#[test]
+fn formats_blocks_with_indentation() {
+    let source = "let f = fn(a, b) { let c = a + b; if (c > 10) { return c; } else { c * 2; } };\nf(1, 2); let g = if (a) { 1 } else { 2 };";
+    let expected = "\
+let f = fn(a, b) {
+    let c = a + b;
+    if (c > 10) {
+        return c;
+    } else {
+        c * 2
+    }
+};
+
+f(1, 2);
+let g = if (a) { 1 } else { 2 };
+";
+    assert_eq!(format_source(source).unwrap(), expected);
+    assert_round_trip(source);
+}
+
+#[test]
+fn round_trips_the_prelude() {
+    assert_round_trip(PRELUDE);
+    let formatted = format_source(PRELUDE).unwrap();
+    for line in PRELUDE.lines().filter(|x| x.trim_start().starts_with("//")) {
+        assert!(formatted.contains(line), "{:?} got lost", line);
+    }
+}
+
+// random programs with every operator, written with parentheses around every sub expression.
+// the formatter may only drop the parentheses the parser does not need
+#[test]
+fn round_trips_random_programs() {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    for _ in 0..2000 {
+        let source = Generator::new(next_random(&mut seed)).program();
+        assert_round_trip(&source);
+    }
+}
+
+// code the parser can not read has to be an error, the formatter must not print only the part it understood
+#[test]
+fn refuses_to_format_code_that_does_not_parse() {
+    let cases = [
+        ("let a = 1 2 3 @ $ ;\nreturn a ) ) foo(;", "let statements need to end with \";\""),
+        ("let a = 1;\nreturn a ) ) foo(;", "return statements need to end with \";\""),
+        ("let a = 1 @;", "illegal character"),
+        ("fn() { let a = 1 2 }", "let statements need to end with \";\""),
+    ];
+    for (source, expected) in cases {
+        match format_source(source) {
+            Ok(x) => panic!("{:?} should not format, got {:?}", source, x),
+            Err(err) => assert_eq!(err.message, expected, "source: {:?}", source),
+        }
+    }
+}
+
+#[test]
+fn keeps_comments() {
+    let cases = [
+        ("// only a comment", "// only a comment\n"),
+        ("let a=1;// one", "let a = 1; // one\n"),
+        ("/* block */ let a = 1;", "/* block */\nlet a = 1;\n"),
+        ("/// doc of an expression\nf();", "/// doc of an expression\nf();\n"),
+        ("/// doc\nlet a = 1;\n/// doc\nexport let b = 2;", "/// doc\nlet a = 1;\n/// doc\nexport let b = 2;\n"),
+        ("// header\n\n\nlet a = 1;\n\n// section\nlet b = 2;", "// header\n\nlet a = 1;\n\n// section\nlet b = 2;\n"),
+        ("let b = ((1 + /* inner */ 2)) * 3;", "let b = (1 + /* inner */ 2) * 3;\n"),
+        ("f(2) // no semicolon\n// last", "f(2); // no semicolon\n// last\n"),
+        ("let f = fn(x) {\n  // first\n  let y = x;\n  y // result\n};", "let f = fn(x) {\n    // first\n    let y = x;\n    y // result\n};\n"),
+        ("let f = fn(x) {\n// inside\nx + // after plus\n1\n};", "let f = fn(x) {\n    // inside\n    x + // after plus\n        1\n};\n"),
+        // a block with comments in it is never written on one line
+        ("let f = fn(x) {\n  // inside\n  x + 1\n};", "let f = fn(x) {\n    // inside\n    x + 1\n};\n"),
+        ("if (a) { b // why\n}", "if (a) {\n    b // why\n};\n"),
+        ("if (a) { /* yes */ b } else { c }", "if (a) { /* yes */\n    b\n} else {\n    c\n};\n"),
+        ("let f = fn() {\n// later\n};", "let f = fn() {\n    // later\n};\n"),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(format_source(source).unwrap(), expected, "source: {:?}", source);
+        assert_eq!(format_source(expected).unwrap(), expected, "formatting {:?} is not idempotent", source);
+    }
+    // the doc comment is still the doc of the let after formatting
+    let formatted = format_source("/// adds one\nlet inc = fn(x) { x + 1 };").unwrap();
+    match &parse(&formatted)[0] {
+        Statement::LET(x) => assert_eq!(x.doc(), Some("adds one")),
+        other => panic!("not a let statement: {:?}", other),
+    }
+}
+
+// comments anywhere between the tokens of random expressions all end up in the output exactly once
+#[test]
+fn keeps_comments_between_any_tokens() {
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    for _ in 0..500 {
+        let expression = Generator::new(next_random(&mut seed)).expression(3, &[]);
+        let mut source = String::new();
+        let mut count = 0;
+        let code = format!("let x = {};", expression);
+        let mut lexer = Lexer::new(code.clone());
+        loop {
+            let tok = lexer.next_token();
+            if tok.tokentype == TokenType::EOF {
+                break
+            }
+            source.push_str(&code[tok.span.start..tok.span.end]);
+            match next_random(&mut seed) % 6 {
+                0 => source.push_str(&format!(" /* c{} */ ", count)),
+                1 => source.push_str(&format!(" // c{}\n", count)),
+                _ => {
+                    source.push(' ');
+                    continue
+                },
+            }
+            count += 1;
+        }
+        let formatted = format_source(&source).unwrap();
+        for i in 0..count {
+            assert_eq!(formatted.matches(&format!("c{} ", i)).count() + formatted.matches(&format!("c{}\n", i)).count(), 1, "comment c{} of {:?}:\n{}", i, source, formatted);
+        }
+        assert_round_trip(&source);
+    }
+}
+
+#[test]
+fn check_mode_reports_unformatted_files() {
+    let dir = std::env::temp_dir().join(format!("monkey-fmt-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let formatted = dir.join("formatted.mk");
+    let unformatted = dir.join("unformatted.mk");
+    std::fs::write(&formatted, "let a = 1;\n").unwrap();
+    std::fs::write(&unformatted, "let a=1;").unwrap();
+
+    let run = |args: &[&std::path::Path], check: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_monkey"));
+        command.arg("fmt");
+        if check {
+            command.arg("--check");
+        }
+        command.args(args).status().unwrap().code()
+    };
+    assert_eq!(run(&[&formatted], true), Some(0));
+    assert_eq!(run(&[&formatted, &unformatted], true), Some(1));
+    assert_eq!(std::fs::read_to_string(&unformatted).unwrap(), "let a=1;");
+    assert_eq!(run(&[&unformatted], false), Some(0));
+    assert_eq!(std::fs::read_to_string(&unformatted).unwrap(), "let a = 1;\n");
+    assert_eq!(run(&[&unformatted], true), Some(0));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
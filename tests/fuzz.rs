@@ -0,0 +1,190 @@
+// the same checks as the fuzz targets in fuzz/, run on inputs from a fixed seed so every run sees the same programs
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::thread;
+
+use monkey::fuzz;
+use monkey::lexer::Lexer;
+use monkey::stdlib::PRELUDE;
+use monkey::token::TokenType;
+
+mod common;
+use common::{next_random, pick};
+
+// the evaluator may go MAX_CALL_DEPTH calls deep, which needs more stack than a test thread has in a debug build
+fn on_big_stack(check: impl FnOnce() + Send + 'static) {
+    let checker = thread::Builder::new().stack_size(64 * 1024 * 1024).spawn(check).unwrap();
+    if let Err(err) = checker.join() {
+        std::panic::resume_unwind(err);
+    }
+}
+
+fn check_all(input: &str) {
+    fuzz::lex(input);
+    fuzz::run(input);
+}
+
+// mostly chars monkey knows, some it does not and a few that take more than one byte
+fn random_chars(seed: &mut u64) -> String {
+    const CHARS: &str = "abcxyz_019 \n\t\"\\/*+-=!<>&|^~()[]{},;:.@$#ü✓";
+    let chars: Vec<char> = CHARS.chars().collect();
+    let length = next_random(seed) % 60;
+    (0..length).map(|_| chars[(next_random(seed) % chars.len() as u64) as usize]).collect()
+}
+
+// tokens put together without any grammar, so most of them end in a parse error somewhere in the middle
+fn random_tokens(seed: &mut u64) -> String {
+    let tokens = [
+        "let", "fn", "if", "else", "return", "true", "false", "import", "export", "as", "from", "while",
+        "x", "y", "f", "len", "range", "reduce", "push", "int", "string", "[int]",
+        "0", "1", "7", "0xFF", "0b2", "99999999999999999999", "\"s\"", "\"\"", "\"",
+        "=", "==", "!=", "+", "-", "*", "/", "^", "<<", ">>", "&", "|", "~", "!", "<", ">=", "->", ".",
+        "(", ")", "[", "]", "{", "}", ",", ";", ":", "/*", "//", "\n",
+    ];
+    let length = next_random(seed) % 40;
+    let mut source = String::new();
+    for _ in 0..length {
+        source.push_str(pick(seed, &tokens));
+        if next_random(seed) % 3 < 2 {
+            source.push(' ');
+        }
+    }
+    source
+}
+
+fn token_spans(source: &str) -> Vec<Range<usize>> {
+    let mut lexer = Lexer::new(String::from(source));
+    let mut spans = Vec::new();
+    loop {
+        let tok = lexer.next_token();
+        if tok.tokentype == TokenType::EOF {
+            return spans
+        }
+        spans.push(tok.span.start..tok.span.end);
+    }
+}
+
+// cuts random tokens out of a working program, doubles them or swaps two of them
+fn mutate(seed: &mut u64, source: &str) -> String {
+    let mut mutated = String::from(source);
+    for _ in 0..(next_random(seed) % 3 + 1) {
+        let spans = token_spans(&mutated);
+        if spans.is_empty() {
+            break
+        }
+        let a = spans[(next_random(seed) % spans.len() as u64) as usize].clone();
+        let b = spans[(next_random(seed) % spans.len() as u64) as usize].clone();
+        let (text_a, text_b) = (&mutated[a.clone()], &mutated[b.clone()]);
+        mutated = match next_random(seed) % 3 {
+            0 => format!("{}{}", &mutated[..a.start], &mutated[a.end..]),
+            1 => format!("{}{} {}", &mutated[..a.start], text_a, &mutated[a.start..]),
+            _ if a.end <= b.start => format!("{}{}{}{}{}", &mutated[..a.start], text_b, &mutated[a.end..b.start], text_a, &mutated[b.end..]),
+            _ => mutated,
+        };
+    }
+    mutated
+}
+
+fn corpus() -> Vec<String> {
+    let mut programs = vec![String::from(PRELUDE)];
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+    let mut scripts: Vec<_> = fs::read_dir(dir).unwrap().map(|x| x.unwrap().path()).filter(|x| x.extension().is_some_and(|x| x == "mk")).collect();
+    scripts.sort();
+    for script in scripts {
+        programs.push(fs::read_to_string(script).unwrap());
+    }
+    programs
+}
+
+#[test]
+fn random_chars_never_panic() {
+    on_big_stack(|| {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..3000 {
+            check_all(&random_chars(&mut seed));
+        }
+    });
+}
+
+#[test]
+fn random_tokens_never_panic() {
+    on_big_stack(|| {
+        let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+        for _ in 0..3000 {
+            check_all(&random_tokens(&mut seed));
+        }
+    });
+}
+
+#[test]
+fn mutated_programs_never_panic() {
+    on_big_stack(|| {
+        let mut seed: u64 = 0xd1b5_4a32_d192_ed03;
+        let programs = corpus();
+        for _ in 0..300 {
+            let program = &programs[(next_random(&mut seed) % programs.len() as u64) as usize];
+            check_all(&mutate(&mut seed, program));
+        }
+    });
+}
+
+// inputs that used to hang, overflow the stack or take forever
+#[test]
+fn limits_are_errors() {
+    on_big_stack(|| {
+        let deep_parens = format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000));
+        let deep_arrays = format!("{}1{}", "[".repeat(10_000), "]".repeat(10_000));
+        let deep_functions = format!("{}1{}", "fn() { ".repeat(10_000), " }".repeat(10_000));
+        let deep_prefix = format!("{}1", "-".repeat(10_000));
+        let deep_type = format!("let x: {}int{} = 1;", "[".repeat(10_000), "]".repeat(10_000));
+        for source in [&deep_parens, &deep_arrays, &deep_functions, &deep_prefix, &deep_type] {
+            let err = fuzz::run(source);
+            assert!(err.is_none(), "{}... should not parse", &source[..20]);
+        }
+
+        let cases = [
+            ("let f = fn(x) { f(x) }; f(1);", "maximum call depth of 1000 exceeded"),
+            ("let f = fn(x) { 1 + f(x) }; f(1);", "maximum call depth of 1000 exceeded"),
+            ("reduce(range(2000), 0, fn(acc, x) { reduce([x], acc, fn(a, y) { a + y }) });", ""),
+            ("2 ^ 4000000000;", "exponent is too large"),
+            ("1 << 4000000000;", "shift amount is too large"),
+            ("range(1 << 40);", "range can have at most 10000000 elements"),
+        ];
+        for (source, expected) in cases {
+            match fuzz::run(source) {
+                Some(Err(err)) => assert_eq!(err.message, expected, "source: {:?}", source),
+                Some(Ok(_)) => assert_eq!(expected, "", "source: {:?}", source),
+                None => panic!("{:?} did not parse", source),
+            }
+        }
+        // nothing is left of the calls before
+        assert!(matches!(fuzz::run("let f = fn(n) { if (n > 0) { f(n - 1) } else { 0 } }; f(999);"), Some(Ok(_))));
+    });
+}
+
+#[test]
+fn statements_can_end_at_the_end_of_the_input() {
+    for source in ["let x = 1", "return 5", "let x = fn() { 1 }", "fn() { let x = 1 }", "fn() { return 1 }"] {
+        fuzz::lex(source);
+        assert!(fuzz::parse(source).is_some(), "source: {:?}", source);
+    }
+}
+
+// the input can not end inside of a block
+#[test]
+fn blocks_need_their_closing_brace() {
+    for source in ["let f = fn() { 1", "fn() { let x = 1", "if (x) { 1", "if (x) { 1 } else {", "fn() { fn() { 1 }"] {
+        fuzz::lex(source);
+        assert!(fuzz::parse(source).is_none(), "{:?} should not parse", source);
+    }
+}
+
+// tokens the parser can not use are errors, they are never skipped
+#[test]
+fn garbage_after_a_statement_is_an_error() {
+    for source in ["let a = 1 @;", "let a = 1 2;", "return a ) );", "fn() { return 1 $ }", "let a = 1 \"open"] {
+        fuzz::lex(source);
+        assert!(fuzz::parse(source).is_none(), "{:?} should not parse", source);
+    }
+}
@@ -0,0 +1,83 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use monkey::ast::Statement;
+use monkey::evaluator::{apply_function, eval};
+use monkey::gc;
+use monkey::lexer::Lexer;
+use monkey::object::{Environment, MonkeyObject};
+use monkey::parser::Parser;
+
+// counts the bytes that are currently allocated
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst);
+        }
+        ptr
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn parse(source: &str) -> Vec<Statement> {
+    let mut lexer = Lexer::new(String::from(source));
+    match Parser::new(&mut lexer).parse_programm() {
+        Ok(x) => x.statements,
+        Err(err) => panic!("could not parse {:?}: {}", source, err),
+    }
+}
+
+// every run defines a recursive function in a new environment, the function and the environment hold each other
+fn run(program: &[Statement]) -> Vec<MonkeyObject> {
+    let env = gc::track(Environment::new());
+    eval(program, env).unwrap()
+}
+
+// one test, the allocation counter is shared by all tests of this file
+#[test]
+fn recursive_definitions_are_collected() {
+    // closures that are still in use survive a collection
+    let closure = parse("let adder = fn(x) { fn(y) { x + y } }; let add2 = adder(2); add2(3);");
+    let env = gc::track(Environment::new());
+    eval(&closure, env.clone()).unwrap();
+    gc::collect();
+    let result = eval(&parse("add2(5);"), env).unwrap();
+    assert_eq!(result.last().map(|x| x.inspect()), Some(String::from("7")));
+
+    // an array returned to the host keeps the closure in it and the environments the closure uses alive
+    let shared = parse("let base = 10; let keep = fn() { let n = 5; [fn() { n + base }] }; let kept = keep(); kept;");
+    let result = eval(&shared, gc::track(Environment::new())).unwrap();
+    gc::collect();
+    let closure = match result.last() {
+        Some(MonkeyObject::ARRAY(x)) => x.elements[0].clone(),
+        other => panic!("expected an array, got {:?}", other),
+    };
+    assert_eq!(apply_function(closure, vec![]).map(|x| x.inspect()), Ok(String::from("15")));
+
+    let program = parse("let countdown = fn(n) { if (n == 0) { 0 } else { countdown(n - 1) } }; countdown(2);");
+    // the warm up fills the list of tracked environments up to its threshold once
+    for _ in 0..10_000 {
+        run(&program);
+    }
+    gc::collect();
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    for _ in 0..100_000 {
+        let result = run(&program);
+        assert_eq!(result.last().map(|x| x.inspect()), Some(String::from("0")));
+    }
+    gc::collect();
+    let after = ALLOCATED.load(Ordering::SeqCst);
+    let grown = after.saturating_sub(before);
+    assert!(grown < 64 * 1024, "memory grew by {} bytes over 100000 runs", grown);
+}
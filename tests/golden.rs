@@ -0,0 +1,72 @@
+// runs every tests/golden/<name>.mk with the monkey binary and compares what it prints to tests/golden/<name>.out,
+// the runner prints the result of every statement with inspect, errors are printed to stderr and come after
+// everything the script printed to stdout.
+// the directory of the scripts is printed as $DIR, so the files do not depend on where the repository is.
+// after an intended change of the output run: UPDATE_GOLDEN=1 cargo test --test golden
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn run_script(script: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_monkey"))
+        .arg(script)
+        .env_remove("MONKEY_PATH")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    // a script that fails prints its error and exits with 1, a panic exits with 101 and fails here
+    let expected_code = if stderr.is_empty() { 0 } else { 1 };
+    assert_eq!(output.status.code(), Some(expected_code), "{} printed to stderr:\n{}", script.display(), stderr);
+    let printed = String::from_utf8(output.stdout).unwrap() + &stderr;
+    let dir = fs::canonicalize(golden_dir()).unwrap();
+    printed.replace(&dir.display().to_string(), "$DIR")
+}
+
+#[test]
+fn scripts_print_the_expected_output() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut scripts: Vec<PathBuf> = fs::read_dir(golden_dir())
+        .unwrap()
+        .map(|x| x.unwrap().path())
+        .filter(|x| x.extension().is_some_and(|x| x == "mk"))
+        .collect();
+    scripts.sort();
+    assert!(!scripts.is_empty());
+
+    let mut failed: Vec<String> = Vec::new();
+    for script in scripts {
+        let actual = run_script(&script);
+        let expected_path = script.with_extension("out");
+        if update {
+            fs::write(&expected_path, &actual).unwrap();
+            continue
+        }
+        let expected = match fs::read_to_string(&expected_path) {
+            Ok(x) => x,
+            Err(_) => {
+                failed.push(format!("{} has no expected output, run with UPDATE_GOLDEN=1 to create it", script.display()));
+                continue
+            },
+        };
+        if actual != expected {
+            failed.push(format!("{} printed:\n{}\nexpected:\n{}", script.display(), actual, expected));
+        }
+    }
+    assert!(failed.is_empty(), "{}", failed.join("\n\n"));
+}
+
+#[test]
+fn failing_scripts_print_to_stderr_and_exit_with_1() {
+    for name in ["parse_error", "runtime_error", "missing_export"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_monkey")).arg(golden_dir().join(format!("{}.mk", name))).output().unwrap();
+        assert_eq!(output.status.code(), Some(1), "{}.mk", name);
+        assert!(String::from_utf8(output.stderr).unwrap().starts_with("Error: "), "{}.mk", name);
+    }
+    let output = Command::new(env!("CARGO_BIN_EXE_monkey")).arg("/nonexistent/script.mk").output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "");
+}
@@ -0,0 +1,11 @@
+// every operator and the precedence between them
+1 + 2 * 3;
+(1 + 2) * 3;
+2 ^ 3 ^ 2;
+-7 / 2;
+0xFF & 0b1010 | 1 << 4;
+-16 >> 2;
+~5;
+9223372036854775807 + 1;
+2 ^ 70 / 2 ^ 60;
+1 < 2 == true;
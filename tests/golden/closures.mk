@@ -0,0 +1,11 @@
+let make_counter = fn(start) {
+    let step = fn(n) { start + n };
+    step
+};
+let counter = make_counter(10);
+counter(1);
+let compose = fn(f, g) { fn(x) { g(f(x)) } };
+let twice = compose(fn(x) { x * 2 }, fn(x) { x * 2 });
+twice(5);
+let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } };
+fib(20);
@@ -0,0 +1,9 @@
+let people = [{"name": "ada", "age": 36}, {"name": "alan", "age": 41}];
+map(people, fn(p) { p["name"] });
+sum(map(people, fn(p) { p["age"] }));
+let table = {"b": [1, 2], "a": true, 3: "three"};
+table;
+keys(table);
+table["missing"];
+push([1, 2], 3)[2];
+len("grüße");
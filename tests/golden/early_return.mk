@@ -0,0 +1,5 @@
+let x = 1;
+if (x == 1) {
+    return x + 41;
+};
+x + 100;
@@ -0,0 +1,2 @@
+import { unit } from "modules/shapes";
+unit;
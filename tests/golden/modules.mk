@@ -0,0 +1,5 @@
+import "modules/shapes" as shapes;
+import { square } from "modules/shapes";
+shapes;
+shapes.unit_square;
+square(4);
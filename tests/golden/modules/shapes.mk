@@ -0,0 +1,4 @@
+/// the area of a square
+export let square = fn(side) { side * side };
+let unit = 1;
+export let unit_square = square(unit);
@@ -0,0 +1,2 @@
+let ok = 1;
+let broken = [1, 2;
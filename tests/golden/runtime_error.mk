@@ -0,0 +1,3 @@
+let add = fn(a, b) { a + b };
+add(1, 2);
+add(1, true);
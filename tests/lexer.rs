@@ -0,0 +1,152 @@
+use monkey::lexer::Lexer;
+use monkey::token::{Literal, Span, Token, TokenType};
+
+// every token up to EOF, EOF not included
+fn lex(source: &str) -> Vec<Token> {
+    let mut lexer = Lexer::new(String::from(source));
+    let mut tokens = Vec::new();
+    loop {
+        let tok = lexer.next_token();
+        if tok.tokentype == TokenType::EOF {
+            return tokens
+        }
+        tokens.push(tok);
+    }
+}
+
+fn types_and_literals(source: &str) -> Vec<(TokenType, String)> {
+    lex(source).into_iter().map(|x| (x.tokentype, x.literal.to_string())).collect()
+}
+
+fn check(cases: &[(&str, Vec<(TokenType, &str)>)]) {
+    for (source, expected) in cases {
+        let expected: Vec<(TokenType, String)> = expected.iter().map(|(x, y)| (x.clone(), String::from(*y))).collect();
+        assert_eq!(types_and_literals(source), expected, "source: {:?}", source);
+    }
+}
+
+#[test]
+fn lexes_operators_and_delimiters() {
+    use TokenType::*;
+    check(&[
+        ("= == ! !=", vec![(ASSIGN, "="), (EQ, "=="), (BANG, "!"), (NOTEQ, "!=")]),
+        ("+ += - -= ->", vec![(PLUS, "+"), (PLUSSELF, "+="), (MINUS, "-"), (MINUSSELF, "-="), (ARROW, "->")]),
+        ("* / ^ ~", vec![(ASTERISK, "*"), (SLASH, "/"), (POWER, "^"), (TILDE, "~")]),
+        ("& | << >>", vec![(AMPERSAND, "&"), (PIPE, "|"), (SHIFTLEFT, "<<"), (SHIFTRIGHT, ">>")]),
+        ("< <= > >=", vec![(LT, "<"), (LTEQ, "<="), (GT, ">"), (GTEQ, ">=")]),
+        (",;:.", vec![(COMMA, ","), (SEMICOLON, ";"), (COLON, ":"), (DOT, ".")]),
+        ("(){}[]", vec![(LPAREN, "("), (RPAREN, ")"), (LBRACE, "{"), (RBRACE, "}"), (LBRACKET, "["), (RBRACKET, "]")]),
+        // no whitespace needed between operators
+        ("a<=-b", vec![(IDENT, "a"), (LTEQ, "<="), (MINUS, "-"), (IDENT, "b")]),
+        ("x>>=1", vec![(IDENT, "x"), (SHIFTRIGHT, ">>"), (ASSIGN, "="), (INT, "1")]),
+    ]);
+}
+
+#[test]
+fn lexes_keywords_identifiers_and_reserved_words() {
+    use TokenType::*;
+    check(&[
+        ("fn let true false if else return", vec![
+            (FUNCTION, "fn"), (LET, "let"), (TRUE, "true"), (FALSE, "false"), (IF, "if"), (ELSE, "else"), (RETURN, "return"),
+        ]),
+        ("import export as from", vec![(IMPORT, "import"), (EXPORT, "export"), (AS, "as"), (FROM, "from")]),
+        ("while null struct", vec![(RESERVED, "while"), (RESERVED, "null"), (RESERVED, "struct")]),
+        // keywords are case sensitive
+        ("Let LET iff fn_", vec![(IDENT, "Let"), (IDENT, "LET"), (IDENT, "iff"), (IDENT, "fn_")]),
+        ("_x x1 größe ünïcödé", vec![(IDENT, "_x"), (IDENT, "x1"), (IDENT, "größe"), (IDENT, "ünïcödé")]),
+        // an identifier can not start with a digit
+        ("1x", vec![(INT, "1"), (IDENT, "x")]),
+    ]);
+}
+
+#[test]
+fn keeps_integer_literals_as_written() {
+    use TokenType::*;
+    check(&[
+        ("0 42 1_000_000", vec![(INT, "0"), (INT, "42"), (INT, "1_000_000")]),
+        ("0xFF 0Xff 0o17 0b1010 0b_1", vec![(INT, "0xFF"), (INT, "0Xff"), (INT, "0o17"), (INT, "0b1010"), (INT, "0b_1")]),
+        // the parser reports invalid digits, the lexer takes every alphanumeric char after the prefix
+        ("0xZZ", vec![(INT, "0xZZ")]),
+        ("-5", vec![(MINUS, "-"), (INT, "5")]),
+    ]);
+}
+
+#[test]
+fn unescapes_string_literals() {
+    use TokenType::*;
+    check(&[
+        ("\"hello world\"", vec![(STRING, "hello world")]),
+        ("\"\"", vec![(STRING, "")]),
+        ("\"a\\nb\\tc\\rd\"", vec![(STRING, "a\nb\tc\rd")]),
+        ("\"say \\\"hi\\\" \\\\o/\"", vec![(STRING, "say \"hi\" \\o/")]),
+        // unknown escapes are kept
+        ("\"\\q\"", vec![(STRING, "\\q")]),
+        ("\"héllo ✓\"", vec![(STRING, "héllo ✓")]),
+        ("\"a\" \"b\"", vec![(STRING, "a"), (STRING, "b")]),
+    ]);
+}
+
+#[test]
+fn skips_comments() {
+    use TokenType::*;
+    check(&[
+        ("1 // the rest of the line\n2", vec![(INT, "1"), (INT, "2")]),
+        ("1 /* a block */ 2", vec![(INT, "1"), (INT, "2")]),
+        ("1 /* nested /* blocks */ still comment */ 2", vec![(INT, "1"), (INT, "2")]),
+        ("a / b", vec![(IDENT, "a"), (SLASH, "/"), (IDENT, "b")]),
+        ("// only a comment", vec![]),
+    ]);
+}
+
+#[test]
+fn reports_what_it_can_not_read_as_illegal() {
+    use TokenType::*;
+    check(&[
+        ("@", vec![(ILLEGAL, "@")]),
+        ("a $ b", vec![(IDENT, "a"), (ILLEGAL, "$"), (IDENT, "b")]),
+        ("\"never closed", vec![(ILLEGAL, "\"never closed")]),
+        ("1 /* never closed", vec![(INT, "1"), (ILLEGAL, "/* never closed")]),
+    ]);
+}
+
+#[test]
+fn hands_doc_comments_to_the_next_token() {
+    let tokens = lex("/// first line\n/// second line\nlet x = 1; // not a doc comment\n//// not one either\nlet y = 2;");
+    assert_eq!(tokens[0].doc.as_deref(), Some("first line\nsecond line"));
+    assert!(tokens[1..].iter().all(|x| x.doc.is_none()));
+}
+
+#[test]
+fn tracks_spans() {
+    let tokens = lex("let x = 10;\n  größe\n\"a\\n\"");
+    let spans: Vec<Span> = tokens.iter().map(|x| x.span).collect();
+    assert_eq!(spans, vec![
+        Span::new(0, 3, 1, 1),
+        Span::new(4, 5, 1, 5),
+        Span::new(6, 7, 1, 7),
+        Span::new(8, 10, 1, 9),
+        Span::new(10, 11, 1, 11),
+        // columns count chars, start and end are byte offsets
+        Span::new(14, 21, 2, 3),
+        Span::new(22, 27, 3, 1),
+    ]);
+}
+
+#[test]
+fn keeps_returning_eof() {
+    let mut lexer = Lexer::new(String::from("x"));
+    assert_eq!(lexer.next_token().tokentype, TokenType::IDENT);
+    for _ in 0..3 {
+        assert_eq!(lexer.next_token().tokentype, TokenType::EOF);
+    }
+}
+
+#[test]
+fn interns_only_names_keywords_and_operators() {
+    let tokens = lex("let name = \"text\" + 12 @ \"open");
+    let interned: Vec<bool> = tokens.iter().map(|x| matches!(x.literal, Literal::SYMBOL(..))).collect();
+    assert_eq!(interned, vec![true, true, true, false, true, false, false, false]);
+    // the unterminated string keeps the rest of the input, it is not put into the symbol table
+    assert_eq!(tokens[7].literal.as_str(), "\"open");
+    assert_eq!(tokens[1].literal.symbol(), lex("name")[0].literal.symbol());
+}
@@ -0,0 +1,165 @@
+use std::fs;
+use std::process::Command;
+
+use monkey::json::{self, JsonValue};
+use monkey::lexer::Lexer;
+use monkey::lint::{lint, LintKind};
+use monkey::parser::Parser;
+
+fn warnings(source: &str) -> Vec<String> {
+    let program = match Parser::new(&mut Lexer::new(String::from(source))).parse_programm() {
+        Ok(x) => x,
+        Err(err) => panic!("could not parse {:?}: {}", source, err),
+    };
+    lint(&program).iter().map(|x| x.to_string()).collect()
+}
+
+// every case has to give exactly the expected warnings, all of them of the kind
+fn check(kind: LintKind, cases: &[(&str, &[&str])]) {
+    for (source, expected) in cases {
+        let found = warnings(source);
+        assert_eq!(found, *expected, "source: {:?}", source);
+        for warning in &found {
+            assert!(warning.contains(&format!("warning[{}]", kind.code())), "{} in {:?}", warning, source);
+        }
+    }
+}
+
+#[test]
+fn warns_about_undefined_names() {
+    check(LintKind::UNDEFINEDNAME, &[
+        ("puts(x);", &["1:6: warning[undefined-name]: x is not defined"]),
+        ("let f = fn(a) { a + b }; f(1);", &["1:21: warning[undefined-name]: b is not defined"]),
+        // builtins and the prelude are always defined
+        ("puts(len(map([1], fn(x) { x })));", &[]),
+    ]);
+}
+
+#[test]
+fn warns_about_names_used_before_their_definition() {
+    check(LintKind::USEDBEFOREDEFINITION, &[
+        ("puts(z);\nlet z = 1;\nputs(z);", &["1:6: warning[used-before-definition]: z is used before its definition"]),
+        // a function can use a name defined later if it is called after the let
+        ("let f = fn() { y }; let y = 1; f();", &[]),
+    ]);
+}
+
+#[test]
+fn warns_about_unused_variables() {
+    check(LintKind::UNUSEDVARIABLE, &[
+        ("let a = 1;", &["1:5: warning[unused-variable]: unused variable a"]),
+        ("let f = fn() { let inner = 2; 3 }; f();", &["1:20: warning[unused-variable]: unused variable inner"]),
+        ("let _a = 1;", &[]),
+        // the exports of a module are used by whoever imports it
+        ("export let a = 1;", &[]),
+    ]);
+}
+
+#[test]
+fn warns_about_unused_parameters() {
+    check(LintKind::UNUSEDPARAMETER, &[
+        ("let f = fn(a, b) { a }; f(1, 2);", &["1:15: warning[unused-parameter]: unused parameter b"]),
+        ("let f = fn(_a) { 1 }; f(1);", &[]),
+    ]);
+}
+
+#[test]
+fn warns_about_unused_imports() {
+    check(LintKind::UNUSEDIMPORT, &[
+        ("import { a, b } from \"lib\"; puts(a);", &["1:13: warning[unused-import]: unused import b"]),
+        ("import \"lib\" as lib;", &["1:17: warning[unused-import]: unused import lib"]),
+        ("import \"lib\" as lib; lib.f();", &[]),
+    ]);
+}
+
+#[test]
+fn warns_about_unreachable_code() {
+    check(LintKind::UNREACHABLECODE, &[
+        ("let f = fn() { return 1; puts(2); }; f();", &["1:26: warning[unreachable-code]: unreachable code after return"]),
+        ("let f = fn(x) { if (x) { return 1; }; 2 }; f(true);", &[]),
+    ]);
+}
+
+#[test]
+fn warns_about_constant_conditions() {
+    check(LintKind::CONSTANTCONDITION, &[
+        ("if (true) { puts(1) }", &["1:1: warning[constant-condition]: condition is always true"]),
+        ("if (false) { puts(1) } else { puts(2) }", &["1:1: warning[constant-condition]: condition is always false"]),
+        ("let x = 1; if (x > 2) { puts(1) }", &[]),
+    ]);
+}
+
+#[test]
+fn warns_about_shadowing() {
+    check(LintKind::SHADOWING, &[
+        ("let x = 1; let f = fn(x) { x }; f(x);", &["1:23: warning[shadowing]: x shadows a variable of an outer scope"]),
+        ("let x = 1; let f = fn() { let x = 2; x }; f(); x;", &["1:31: warning[shadowing]: x shadows a variable of an outer scope"]),
+        ("let x = 1; puts(x); let x = 2; puts(x);", &["1:25: warning[shadowing]: x is already defined in this scope"]),
+        // builtins and the prelude can be replaced
+        ("let len = 1; puts(len);", &[]),
+    ]);
+}
+
+#[test]
+fn gives_no_warnings_for_clean_code() {
+    let source = "import { square } from \"lib/math\";\n\
+        let total = fn(xs) { reduce(xs, 0, fn(acc, x) { acc + square(x) }) };\n\
+        let _ignored = 0;\n\
+        puts(total([1, 2, 3]));\n";
+    assert_eq!(warnings(source), Vec::<String>::new());
+}
+
+// runs monkey check on files with the given sources, returns the exit code and the printed diagnostics
+fn check_files(sources: &[&str]) -> (Option<i32>, Vec<JsonValue>) {
+    let dir = std::env::temp_dir().join(format!("monkey-lint-test-{}-{}", std::process::id(), sources.len()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_monkey"));
+    command.arg("check");
+    for (i, source) in sources.iter().enumerate() {
+        let file = dir.join(format!("{}.mk", i));
+        fs::write(&file, source).unwrap();
+        command.arg(file);
+    }
+    let output = command.output().unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    let diagnostics = json::parse(&String::from_utf8(output.stdout).unwrap()).unwrap();
+    (output.status.code(), diagnostics.as_array().unwrap().clone())
+}
+
+fn field<'a>(diagnostic: &'a JsonValue, key: &str) -> &'a JsonValue {
+    diagnostic.get(key).unwrap_or_else(|| panic!("{} is missing \"{}\"", diagnostic, key))
+}
+
+#[test]
+fn check_prints_the_warnings_as_json() {
+    let (code, diagnostics) = check_files(&["let x = 1;\nputs(x);\n"]);
+    assert_eq!((code, diagnostics.len()), (Some(0), 0));
+
+    let (code, diagnostics) = check_files(&["puts(1);\nlet unused = 1;\n"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(diagnostics.len(), 1);
+    let warning = &diagnostics[0];
+    assert!(field(warning, "file").as_str().unwrap().ends_with("0.mk"));
+    assert_eq!(field(warning, "severity").as_str(), Some("warning"));
+    assert_eq!(field(warning, "code").as_str(), Some("unused-variable"));
+    assert_eq!(field(warning, "message").as_str(), Some("unused variable unused"));
+    assert_eq!(field(warning, "line").as_i64(), Some(2));
+    assert_eq!(field(warning, "column").as_i64(), Some(5));
+    assert_eq!(field(warning, "start").as_i64(), Some(13));
+    assert_eq!(field(warning, "end").as_i64(), Some(19));
+
+    // the worst file decides the exit code, a file that does not parse is an error
+    let (code, diagnostics) = check_files(&["let a = 1;\n", "let b = ;\n", "puts(c);\n"]);
+    assert_eq!(code, Some(2));
+    let codes: Vec<(&str, &str)> = diagnostics.iter().map(|x| (field(x, "severity").as_str().unwrap(), field(x, "code").as_str().unwrap())).collect();
+    assert_eq!(codes, vec![("warning", "unused-variable"), ("error", "parse-error"), ("warning", "undefined-name")]);
+    assert!(field(&diagnostics[1], "file").as_str().unwrap().ends_with("1.mk"));
+}
+
+#[test]
+fn check_reports_files_that_can_not_be_read() {
+    let output = Command::new(env!("CARGO_BIN_EXE_monkey")).args(["check", "/nonexistent/file.mk"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "[]\n");
+    assert!(String::from_utf8(output.stderr).unwrap().starts_with("Error: could not read /nonexistent/file.mk"));
+}
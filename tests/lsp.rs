@@ -0,0 +1,160 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use monkey::json::{self, JsonValue};
+
+// talks to "monkey lsp" the way an editor does
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i64,
+    notifications: Vec<JsonValue>,
+}
+impl Client {
+    fn start() -> Client {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_monkey"))
+            .arg("lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Client { child, stdin, stdout, next_id: 1, notifications: Vec::new() }
+    }
+
+    fn send(&mut self, body: &str) {
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> JsonValue {
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            self.stdout.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break
+            }
+            if let Some(value) = line.strip_prefix("Content-Length: ") {
+                length = value.parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.stdout.read_exact(&mut body).unwrap();
+        json::parse(&String::from_utf8(body).unwrap()).unwrap()
+    }
+
+    fn notify(&mut self, method: &str, params: &str) {
+        self.send(&format!("{{\"jsonrpc\":\"2.0\",\"method\":\"{}\",\"params\":{}}}", method, params));
+    }
+
+    // sends a request and returns its response, notifications that arrive in between are kept
+    fn request(&mut self, method: &str, params: &str) -> JsonValue {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&format!("{{\"jsonrpc\":\"2.0\",\"id\":{},\"method\":\"{}\",\"params\":{}}}", id, method, params));
+        loop {
+            let message = self.receive();
+            if message.get("id").and_then(|x| x.as_i64()) == Some(id) {
+                return message
+            }
+            self.notifications.push(message);
+        }
+    }
+
+    fn next_notification(&mut self) -> JsonValue {
+        if self.notifications.is_empty() {
+            self.receive()
+        } else {
+            self.notifications.remove(0)
+        }
+    }
+}
+
+fn result(response: &JsonValue) -> &JsonValue {
+    match response.get("result") {
+        Some(x) => x,
+        None => panic!("no result in {}", response),
+    }
+}
+
+fn position(line: i64, character: i64) -> String {
+    format!("{{\"textDocument\":{{\"uri\":\"file:///a.mk\"}},\"position\":{{\"line\":{},\"character\":{}}}}}", line, character)
+}
+
+fn start_line(range: &JsonValue) -> (i64, i64) {
+    let start = range.get("start").unwrap();
+    (start.get("line").unwrap().as_i64().unwrap(), start.get("character").unwrap().as_i64().unwrap())
+}
+
+const DOCUMENT: &str = "/// adds two numbers\\nlet add = fn(a, b) { a + b };\\nlet total = add(1, 2);\\nputs(total, tota);\\n";
+
+#[test]
+fn serves_a_scripted_session() {
+    let mut client = Client::start();
+    let initialize = client.request("initialize", "{\"processId\":null,\"rootUri\":null,\"capabilities\":{}}");
+    let capabilities = result(&initialize).get("capabilities").unwrap();
+    assert_eq!(capabilities.get("hoverProvider"), Some(&JsonValue::BOOL(true)));
+    client.notify("initialized", "{}");
+
+    client.notify("textDocument/didOpen", &format!("{{\"textDocument\":{{\"uri\":\"file:///a.mk\",\"languageId\":\"monkey\",\"version\":1,\"text\":\"{}\"}}}}", DOCUMENT));
+    let published = client.next_notification();
+    assert_eq!(published.get("method").and_then(|x| x.as_str()), Some("textDocument/publishDiagnostics"));
+    let diagnostics = published.get("params").unwrap().get("diagnostics").unwrap().as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1, "{}", published);
+    assert_eq!(diagnostics[0].get("code").and_then(|x| x.as_str()), Some("undefined-name"));
+    assert_eq!(start_line(diagnostics[0].get("range").unwrap()), (3, 12));
+
+    // hover on the use of add shows the definition and its doc comment
+    let hover = client.request("textDocument/hover", &position(2, 13));
+    let contents = result(&hover).get("contents").unwrap().get("value").unwrap().as_str().unwrap();
+    assert!(contents.contains("let add = fn(a, b)"), "{}", contents);
+    assert!(contents.contains("adds two numbers"), "{}", contents);
+
+    let hover = client.request("textDocument/hover", &position(3, 1));
+    let contents = result(&hover).get("contents").unwrap().get("value").unwrap().as_str().unwrap();
+    assert!(contents.contains("builtin puts"), "{}", contents);
+
+    // go to definition of a parameter and of a let
+    let definition = client.request("textDocument/definition", &position(1, 21));
+    assert_eq!(start_line(result(&definition).get("range").unwrap()), (1, 13));
+    let definition = client.request("textDocument/definition", &position(3, 6));
+    assert_eq!(start_line(result(&definition).get("range").unwrap()), (2, 4));
+
+    // inside of the function body the parameters are offered, outside they are not
+    let labels = |response: &JsonValue| -> Vec<String> {
+        result(response).as_array().unwrap().iter().map(|x| String::from(x.get("label").unwrap().as_str().unwrap())).collect()
+    };
+    let inside = labels(&client.request("textDocument/completion", &position(1, 21)));
+    assert!(inside.contains(&String::from("a")) && inside.contains(&String::from("add")));
+    assert!(inside.contains(&String::from("map")) && inside.contains(&String::from("len")) && inside.contains(&String::from("let")));
+    let outside = labels(&client.request("textDocument/completion", &position(3, 0)));
+    assert!(!outside.contains(&String::from("a")) && outside.contains(&String::from("total")));
+
+    // a broken document reports every parse error and is not formatted
+    client.notify("textDocument/didChange", "{\"textDocument\":{\"uri\":\"file:///a.mk\",\"version\":2},\"contentChanges\":[{\"text\":\"let x = ;\\nlet y = 1 2;\"}]}");
+    let published = client.next_notification();
+    let diagnostics = published.get("params").unwrap().get("diagnostics").unwrap().as_array().unwrap();
+    assert_eq!(diagnostics.len(), 2, "{}", published);
+    for (diagnostic, line) in diagnostics.iter().zip([0, 1]) {
+        assert_eq!(diagnostic.get("severity").and_then(|x| x.as_i64()), Some(1));
+        assert_eq!(start_line(diagnostic.get("range").unwrap()).0, line);
+    }
+    let formatting = client.request("textDocument/formatting", "{\"textDocument\":{\"uri\":\"file:///a.mk\"},\"options\":{\"tabSize\":4,\"insertSpaces\":true}}");
+    assert!(formatting.get("error").is_some());
+
+    client.notify("textDocument/didChange", "{\"textDocument\":{\"uri\":\"file:///a.mk\",\"version\":3},\"contentChanges\":[{\"text\":\"let x=1;// one\\nx\"}]}");
+    let formatting = client.request("textDocument/formatting", "{\"textDocument\":{\"uri\":\"file:///a.mk\"},\"options\":{\"tabSize\":4,\"insertSpaces\":true}}");
+    let edits = result(&formatting).as_array().unwrap();
+    assert_eq!(edits[0].get("newText").and_then(|x| x.as_str()), Some("let x = 1; // one\nx;\n"));
+
+    let unknown = client.request("textDocument/rename", &position(0, 0));
+    assert_eq!(unknown.get("error").unwrap().get("code").and_then(|x| x.as_i64()), Some(-32601));
+
+    assert_eq!(result(&client.request("shutdown", "null")), &JsonValue::NULL);
+    client.notify("exit", "null");
+    assert_eq!(client.child.wait().unwrap().code(), Some(0));
+}
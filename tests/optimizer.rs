@@ -0,0 +1,120 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use monkey::ast::Programm;
+use monkey::evaluator::{eval, EvalError};
+use monkey::lexer::Lexer;
+use monkey::object::{Environment, MonkeyObject};
+use monkey::optimizer::optimize;
+use monkey::parser::Parser;
+
+mod common;
+use common::{next_random, Generator};
+
+fn parse(source: &str) -> Programm {
+    let mut lexer = Lexer::new(String::from(source));
+    match Parser::new(&mut lexer).parse_programm() {
+        Ok(x) => x,
+        Err(err) => panic!("could not parse {:?}: {}", source, err),
+    }
+}
+
+fn run(program: Programm) -> Result<Vec<MonkeyObject>, EvalError> {
+    eval(&program.statements, Rc::new(RefCell::new(Environment::new())))
+}
+
+// the value of a program is the result of its last statement. the optimizer can change how many statements there are
+fn value(result: Result<Vec<MonkeyObject>, EvalError>) -> Result<Option<MonkeyObject>, EvalError> {
+    result.map(|mut x| x.pop())
+}
+
+fn optimized(source: &str) -> String {
+    optimize(parse(source)).to_string()
+}
+
+#[test]
+fn folds_constant_expressions() {
+    let cases = [
+        ("4^5 + (1000/10 *3478 + 1)", "348825;"),
+        ("1 - 5", "-4;"),
+        ("-(-3)", "3;"),
+        ("!true == false", "true;"),
+        ("(1 << 4) | 3 > 18", "true;"),
+        ("\"a\" < \"b\"", "true;"),
+        ("let x = 0xFF + 1;", "let x = 256;"),
+        ("f(2 * 3, [1 + 1])", "f(6, [2]);"),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(optimized(source), expected, "source: {:?}", source);
+    }
+}
+
+#[test]
+fn keeps_expressions_that_fail_or_do_not_fit() {
+    let cases = [
+        "1 / 0;",
+        "9223372036854775807 + 1;",
+        "2 ^ -1;",
+        "1 + true;",
+        "-true;",
+        "\"a\" + \"b\";",
+        "if (1) { 2 };",
+    ];
+    for source in cases {
+        assert_eq!(optimized(source), source);
+    }
+}
+
+#[test]
+fn removes_dead_branches() {
+    assert_eq!(optimized("if (1 < 2) { a } else { b }"), "a;");
+    assert_eq!(optimized("if (true) { let w = 1; w } else { b }"), "let w = 1;\nw;");
+    assert_eq!(optimized("let v = if (2 < 1) { a } else { b };"), "let v = b;");
+    assert_eq!(optimized("if (false) { a }; 1"), "1;");
+    // at the end the if has to stay, the statements give null and not the value of the one before
+    assert_eq!(optimized("1; if (false) { a }"), "1;\nif (false) {};");
+    // a block with a let can not become an expression, the let belongs to the environment around it
+    assert_eq!(optimized("let v = if (true) { let w = 1; w } else { 2 };"), "let v = if (true) {\n    let w = 1;\n    w\n} else {\n    2\n};");
+}
+
+// the taken branch goes into the statements around the if, a block on its own would print as a hash literal
+#[test]
+fn prints_source_that_parses_again() {
+    let cases = [
+        ("if (true) { let z = 1; z };", "let z = 1;\nz;"),
+        ("let f = fn() { if (1 > 0) { let y = 2; y * 3 } else { 0 } }; f()", "let f = fn() {\n    let y = 2;\n    y * 3\n};\n\nf();"),
+        ("if (x) { if (false) { 1 } else { let v = 2; }; v }", "if (x) {\n    let v = 2;\n    v\n};"),
+        ("if (true) { if (true) { let a = 1; }; a }", "let a = 1;\na;"),
+    ];
+    for (source, expected) in cases {
+        let output = optimized(source);
+        assert_eq!(output, expected, "source: {:?}", source);
+        assert_eq!(parse(&output).to_string(), output);
+        assert_eq!(value(run(parse(&output))), value(run(parse(source))), "source: {:?}", source);
+    }
+}
+
+#[test]
+fn inlines_constants_bound_once() {
+    assert_eq!(optimized("let x = 2; let f = fn(y) { x * y + x * 3 };"), "let x = 2;\nlet f = fn(y) { 2 * y + 6 };");
+    // x is bound twice, the function sees whichever let ran last before it is called
+    assert_eq!(optimized("let x = 1; let f = fn() { x }; let x = 2;"), "let x = 1;\nlet f = fn() { x };\nlet x = 2;");
+    // a let inside of an if block might not run
+    assert_eq!(optimized("let x = 1; if (c) { let x = 2; }; x"), "let x = 1;\n\nif (c) {\n    let x = 2;\n};\n\nx;");
+    // the parameter hides the constant
+    assert_eq!(optimized("let x = 1; let f = fn(x) { x };"), "let x = 1;\nlet f = fn(x) { x };");
+    // above its let the name is still looked up in the outer environment
+    assert_eq!(optimized("let f = fn() { x }; let x = 1;"), "let f = fn() { x };\nlet x = 1;");
+}
+
+// random programs evaluated with and without the optimizer have to give the same results or the same error
+#[test]
+fn optimized_programs_evaluate_the_same() {
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    for _ in 0..3000 {
+        let source = Generator::new(next_random(&mut seed)).program();
+        let expected = value(run(parse(&source)));
+        let actual = value(run(optimize(parse(&source))));
+        assert_eq!(actual, expected, "program:\n{}", source);
+    }
+}
@@ -0,0 +1,242 @@
+use monkey::ast::{MonkeyExpression, Programm, Statement, TypeAnnotation};
+use monkey::lexer::Lexer;
+use monkey::parser::{ParseError, Parser, Precedence};
+
+fn try_parse(source: &str) -> Result<Programm, ParseError> {
+    let mut lexer = Lexer::new(String::from(source));
+    Parser::new(&mut lexer).parse_programm()
+}
+
+fn parse(source: &str) -> Programm {
+    match try_parse(source) {
+        Ok(x) => x,
+        Err(err) => panic!("could not parse {:?}: {}", source, err),
+    }
+}
+
+// the expression with parentheses around every operator, so the tests see how it was grouped
+fn grouped(expr: &MonkeyExpression) -> String {
+    let list = |elements: &[MonkeyExpression]| elements.iter().map(grouped).collect::<Vec<String>>().join(", ");
+    match expr {
+        MonkeyExpression::PREFIX(x) => format!("({}{})", x.operator, grouped(&x.right)),
+        MonkeyExpression::INFIX(x) => format!("({} {} {})", grouped(&x.left), x.operator, grouped(&x.right)),
+        MonkeyExpression::CALL(x) => format!("{}({})", grouped(&x.function), list(x.arguments.as_deref().unwrap_or_default())),
+        MonkeyExpression::INDEX(x) => format!("({}[{}])", grouped(&x.left), grouped(&x.index)),
+        MonkeyExpression::MEMBER(x) => format!("({}.{})", grouped(&x.object), x.property),
+        MonkeyExpression::ARRAY(x) => format!("[{}]", list(&x.elements)),
+        other => other.to_string(),
+    }
+}
+
+fn grouped_expression(source: &str) -> String {
+    match &parse(source).statements[..] {
+        [Statement::EXPRESSION(x)] => grouped(&x.expression),
+        other => panic!("{:?} is not a single expression: {:?}", source, other),
+    }
+}
+
+fn check_grouping(cases: &[(&str, &str)]) {
+    for (source, expected) in cases {
+        assert_eq!(grouped_expression(source), *expected, "source: {:?}", source);
+    }
+}
+
+// the binary operators of every infix precedence level, from the weakest to the strongest
+const LEVELS: [(Precedence, &str); 8] = [
+    (Precedence::EQUAL, "=="),
+    (Precedence::LESSGREATER, "<"),
+    (Precedence::BITOR, "|"),
+    (Precedence::BITAND, "&"),
+    (Precedence::SHIFT, "<<"),
+    (Precedence::SUM, "+"),
+    (Precedence::PRODUCT, "*"),
+    (Precedence::POWER, "^"),
+];
+
+#[test]
+fn every_infix_level_binds_stronger_than_the_ones_below() {
+    for (i, (weak_level, weak)) in LEVELS.iter().enumerate() {
+        for (strong_level, strong) in &LEVELS[i + 1..] {
+            assert!(weak_level.into_i32() < strong_level.into_i32());
+            check_grouping(&[
+                (&format!("a {} b {} c", weak, strong), &format!("(a {} (b {} c))", weak, strong)),
+                (&format!("a {} b {} c", strong, weak), &format!("((a {} b) {} c)", strong, weak)),
+            ]);
+        }
+    }
+}
+
+#[test]
+fn operators_of_one_level_group_to_the_left() {
+    check_grouping(&[
+        ("a == b != c", "((a == b) != c)"),
+        ("a < b > c <= d >= e", "((((a < b) > c) <= d) >= e)"),
+        ("a | b | c", "((a | b) | c)"),
+        ("a & b & c", "((a & b) & c)"),
+        ("a << b >> c", "((a << b) >> c)"),
+        ("a + b - c", "((a + b) - c)"),
+        ("a * b / c", "((a * b) / c)"),
+        ("a ^ b ^ c", "((a ^ b) ^ c)"),
+    ]);
+}
+
+#[test]
+fn prefix_call_and_index_bind_stronger_than_every_infix_operator() {
+    check_grouping(&[
+        // PREFIX
+        ("-a ^ b", "((-a) ^ b)"),
+        ("!a == b", "((!a) == b)"),
+        ("~a & b", "((~a) & b)"),
+        ("- -a", "(-(-a))"),
+        ("a * -b", "(a * (-b))"),
+        // CALL, "." has the same precedence
+        ("-f(x)", "(-f(x))"),
+        ("f(x) ^ g(y)", "(f(x) ^ g(y))"),
+        ("f(a + b, c * d)", "f((a + b), (c * d))"),
+        ("f(x)(y)", "f(x)(y)"),
+        ("-m.x", "(-(m.x))"),
+        ("m.f(x)", "(m.f)(x)"),
+        // INDEX
+        ("-a[0]", "(-(a[0]))"),
+        ("a * b[1]", "(a * (b[1]))"),
+        ("f(x)[0]", "(f(x)[0])"),
+        ("a[0][1]", "((a[0])[1])"),
+        ("m.x[0]", "((m.x)[0])"),
+        ("[1, 2 + 3][a * b]", "([1, (2 + 3)][(a * b)])"),
+    ]);
+}
+
+#[test]
+fn parentheses_override_precedence() {
+    check_grouping(&[
+        ("(a + b) * c", "((a + b) * c)"),
+        ("a ^ (b ^ c)", "(a ^ (b ^ c))"),
+        ("-(a + b)", "(-(a + b))"),
+        ("(((a)))", "a"),
+        ("!(true == false)", "(!(true == false))"),
+    ]);
+}
+
+#[test]
+fn precedence_levels_round_trip_through_i32() {
+    for level in 1..=12 {
+        assert_eq!(Precedence::from_i32(level).map(|x| x.into_i32()), Some(level));
+    }
+    assert!(Precedence::from_i32(0).is_none());
+    assert!(Precedence::from_i32(13).is_none());
+    assert!(Precedence::LOWEST.into_i32() < Precedence::EQUAL.into_i32());
+    assert!(Precedence::POWER.into_i32() < Precedence::PREFIX.into_i32());
+    assert!(Precedence::PREFIX.into_i32() < Precedence::CALL.into_i32());
+    assert!(Precedence::CALL.into_i32() < Precedence::INDEX.into_i32());
+}
+
+#[test]
+fn prints_statements_in_their_canonical_form() {
+    let cases = [
+        ("let x = 5;", "let x = 5;"),
+        ("let   name:int=1+2 ;", "let name: int = 1 + 2;"),
+        ("return x*2;", "return x * 2;"),
+        ("x", "x;"),
+        ("\"a\\\"b\"", "\"a\\\"b\";"),
+        ("0xFF + 1_000", "0xFF + 1_000;"),
+        ("[1,2,[3]]", "[1, 2, [3]];"),
+        ("{\"a\":1,true:2}", "{\"a\": 1, true: 2};"),
+        ("fn(a,b){a+b}", "fn(a, b) { a + b };"),
+        ("fn(a: int) -> [int] { [a] }", "fn(a: int) -> [int] { [a] };"),
+        ("if(x){1}else{2}", "if (x) { 1 } else { 2 };"),
+        ("import \"lib\" as lib;", "import \"lib\" as lib;"),
+        ("import {a,b} from \"lib\";", "import { a, b } from \"lib\";"),
+        ("export let x = 1;", "export let x = 1;"),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(parse(source).to_string(), expected, "source: {:?}", source);
+    }
+}
+
+#[test]
+fn parses_type_annotations() {
+    let program = parse("let f: fn(int, {string: [bool]}) -> any = fn(a: int, b) { a };");
+    let statement = match &program.statements[0] {
+        Statement::LET(x) => x,
+        other => panic!("not a let statement: {:?}", other),
+    };
+    let annotation = statement.annotation.as_ref().unwrap();
+    assert_eq!(annotation.to_string(), "fn(int, {string: [bool]}) -> any");
+    assert!(matches!(annotation, TypeAnnotation::FUNCTION(_, params, _) if params.len() == 2));
+    let function = match &statement.value {
+        MonkeyExpression::FUNCTIONLITERAL(x) => x,
+        other => panic!("not a function: {:?}", other),
+    };
+    let types: Vec<Option<String>> = function.parameter_types.iter().map(|x| x.as_ref().map(|x| x.to_string())).collect();
+    assert_eq!(types, vec![Some(String::from("int")), None]);
+}
+
+#[test]
+fn reports_parse_errors() {
+    let cases = [
+        ("", "failed to parse any statements"),
+        ("let = 5;", "the name of the variable contains illegal characters or keywords"),
+        ("let while = 5;", "reserved word can not be used as a variable name"),
+        ("let x 5;", "for assigning values to a variable a \"=\" is required"),
+        ("let x: number = 5;", "unknown type, types are int, bool, string, any, [<type>], {<type>: <type>} and fn(<types>) -> <type>"),
+        ("* 5", "no expression can start with this token"),
+        ("while", "reserved word can not be used as an identifier"),
+        ("@", "illegal character"),
+        ("\"open", "unterminated string literal"),
+        ("/* open", "unterminated block comment"),
+        ("(1 + 2", "Error during parsing grouped expression. Did not find closing )"),
+        ("[1, 2", "Error parsing array: missing closing ]"),
+        ("{\"a\" 1}", "Error parsing hash: key and value need to be separated by \":\""),
+        ("f(1, 2", "Error parsing arguments of function call: missing closing )"),
+        ("fn x", "Error parsing function: fn needs to be followed up with ()"),
+        ("fn(1) { 1 }", "function parameters need to be identifiers"),
+        ("let f = fn() { 1", "Error parsing block: missing closing }"),
+        ("if (x) { let y = 1;", "Error parsing block: missing closing }"),
+        ("if x { 1 }", "Opening braces missing. condition of If-expression needs to be in brackets => (<condition>)"),
+        ("m.1", "\".\" needs to be followed by the name of a binding"),
+        ("99999999999999999999", "integer literal is out of range for a 64-bit integer"),
+        ("0x", "integer literal has no digits"),
+        ("0b102", "integer literal contains an invalid digit"),
+        ("import lib;", "import needs to be followed by a module path or a list of names in {}"),
+        ("import \"lib\";", "a module import needs a name => import \"<path>\" as <name>;"),
+        ("export fn() {};", "only let statements can be exported => export let <name> = <value>;"),
+        ("fn() { import \"a\" as a; }", "imports are only allowed at the top level of a file"),
+    ];
+    for (source, expected) in cases {
+        match try_parse(source) {
+            Ok(x) => panic!("{:?} should not parse, got {}", source, x),
+            Err(err) => assert_eq!(err.message, expected, "source: {:?}", source),
+        }
+    }
+}
+
+#[test]
+fn errors_point_at_the_token() {
+    let err = try_parse("let x = 1;\nlet y = 2 + * 3;").unwrap_err();
+    assert_eq!((err.token.span.line, err.token.span.column), (2, 13));
+    assert_eq!(err.to_string(), "no expression can start with this token at line 2, column 13 (token: ASTERISK \"*\")");
+}
+
+// after an error the parser goes on with the next statement that starts a line
+#[test]
+fn recovers_from_errors() {
+    let errors = |source: &str| -> Vec<String> {
+        match Parser::new(&mut Lexer::new(String::from(source))).parse_programm_recovering() {
+            Ok(x) => panic!("{:?} should not parse, got {}", source, x),
+            Err(errors) => errors.iter().map(|x| format!("{}:{}: {}", x.token.span.line, x.token.span.column, x.message)).collect(),
+        }
+    };
+    assert_eq!(errors("let x = ;\nlet y = 1 2;\nputs(x, y);\nlet z = (1;"), vec![
+        "1:9: no expression can start with this token",
+        "2:11: let statements need to end with \";\"",
+        "4:11: Error during parsing grouped expression. Did not find closing )",
+    ]);
+    // the indented statements of the function and its "}" are skipped
+    assert_eq!(errors("let f = fn() {\n    let a = * 2;\n    a\n};\nf(;"), vec![
+        "2:13: no expression can start with this token",
+        "5:3: no expression can start with this token",
+    ]);
+    assert_eq!(errors(""), vec!["1:1: failed to parse any statements"]);
+    // parse_programm gives the first of them
+    assert_eq!(try_parse("let x = ;\nlet y = 1 2;").unwrap_err().token.span.line, 1);
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use monkey::json::{self, JsonValue};
+use monkey::lexer::Lexer;
+use monkey::parser::Parser;
+use monkey::typecheck;
+
+// the errors of the type checker as "line:column: message"
+fn type_errors(source: &str) -> Vec<String> {
+    let program = match Parser::new(&mut Lexer::new(String::from(source))).parse_programm() {
+        Ok(x) => x,
+        Err(err) => panic!("could not parse {:?}: {}", source, err),
+    };
+    typecheck::check(&program).iter().map(|x| format!("{}:{}: {}", x.span.line, x.span.column, x.message)).collect()
+}
+
+fn check(cases: &[(&str, &[&str])]) {
+    for (source, expected) in cases {
+        assert_eq!(type_errors(source), *expected, "source: {:?}", source);
+    }
+}
+
+#[test]
+fn accepts_well_typed_code() {
+    let cases: &[(&str, &[&str])] = &[
+        ("let x = 1; let y = x + 2;", &[]),
+        ("let greeting = \"hello\" + \" \" + \"world\"; len(greeting) > 3;", &[]),
+        // a function bound with let can be used with different types
+        ("let id = fn(x) { x }; id(1); id(\"a\");", &[]),
+        ("let f = fn(x: int) -> int { x * 2 }; f(3);", &[]),
+        ("let xs: [int] = [1, 2, 3]; let h: {string: int} = {\"a\": 1};", &[]),
+        ("let apply = fn(f: fn(int) -> int, x: int) -> int { f(x) }; apply(fn(y) { y + 1 }, 2);", &[]),
+        ("let m = map([1, 2], fn(x) { x * 2 }); let s: int = sum(m);", &[]),
+        ("len(\"abc\") + len([1]) + len({1: 2});", &[]),
+        ("let f = fn(n: int) -> int { if (n > 0) { return n; }; 0 };", &[]),
+        // what the checker can not know gets the type any
+        ("let mixed = [1, \"a\"]; mixed[0] + 1;", &[]),
+        ("let x: [[int]] = [[1], [\"a\"]];", &[]),
+        ("let later = fn() { g() }; let g = fn() { 1 };", &[]),
+        ("let v = if (true) { 1 } else { \"a\" }; v + 1;", &[]),
+        // the let in the if might not run, so x is an int or a string
+        ("let x = 1; let c = len([]) > 0; if (c) { let x = \"a\"; }; x + 1;", &[]),
+        // a key of another type gives null like any key that is not there
+        ("{\"a\": 1}[1];", &[]),
+        ("let h: {string: int} = {\"a\": 1}; let v: bool = h[1];", &[]),
+    ];
+    check(cases);
+}
+
+#[test]
+fn infers_the_types_of_unannotated_code() {
+    let cases: &[(&str, &[&str])] = &[
+        ("true + 1", &["1:6: + can not be used with bool and int"]),
+        ("let f = fn(x) { x + 1 }; let s: string = f(1);", &["1:30: s is declared as string, but the value is int"]),
+        ("let f = fn(a, b) { a < b }; f(1, \"a\");", &["1:34: argument 2 has to be int, found string"]),
+        ("let xs = [1, 2]; push(xs, \"a\");", &["1:27: argument 2 has to be int, found string"]),
+        ("let h = {\"a\": [1]}; h[\"a\"][0] + \"b\";", &["1:31: + can not be used with int and string"]),
+        ("[1, 2][\"a\"]", &["1:8: an array index has to be an int, found string"]),
+        ("let h = {\"a\": 1}; let s: string = h[\"a\"];", &["1:23: s is declared as string, but the value is int"]),
+        ("-\"a\"", &["1:1: - can only be used with int, found string"]),
+        ("let x = 1; if (true) { let x = 2; }; let s: string = x;", &["1:42: s is declared as string, but the value is int"]),
+        ("if (1) { 2 }", &["1:5: the condition of an if has to be a bool, found int"]),
+        ("let f = fn(x) { x }; f(1, 2);", &["1:22: wrong number of arguments: expected 1, got 2"]),
+        ("5(1)", &["1:1: int is not a function"]),
+        ("reverse(1); join([1], 2);", &["1:9: argument 1 has to be array or string, found int", "1:23: argument 2 has to be string, found int"]),
+    ];
+    check(cases);
+}
+
+#[test]
+fn checks_values_against_annotations() {
+    let cases: &[(&str, &[&str])] = &[
+        ("let x: int = \"a\";", &["1:5: x is declared as int, but the value is string"]),
+        ("let f = fn(x: int) -> int { x }; f(\"a\");", &["1:36: argument 1 has to be int, found string"]),
+        ("let f = fn(x) -> string { x + 1 };", &["1:29: the function is declared to return string, but returns int"]),
+        ("let f = fn(n: int) -> bool { if (n > 0) { return 1; }; false };", &["1:50: the function is declared to return bool, but returns int"]),
+        ("let xs: [int] = [\"a\"];", &["1:5: xs is declared as [int], but the value is [string]"]),
+        ("let h: {string: int} = {1: 2};", &["1:5: h is declared as {string: int}, but the value is {int: int}"]),
+        ("let g: fn(int) -> int = fn(x: string) { x };", &["1:5: g is declared as fn(int) -> int, but the value is fn(string) -> string"]),
+    ];
+    check(cases);
+}
+
+#[test]
+fn points_at_the_code_with_the_error() {
+    let cases: &[(&str, &[&str])] = &[
+        ("let a = 1;\nlet b: string =\n    a;", &["2:5: b is declared as string, but the value is int"]),
+        ("let add = fn(a, b) { a + b };\n\nadd(1,\n    true);", &["4:5: argument 2 has to be int, found bool"]),
+        ("let ok = 1;\n  ok + \"x\";\nlet bad: bool = ok;", &["2:6: + can not be used with int and string", "3:5: bad is declared as bool, but the value is int"]),
+    ];
+    check(cases);
+}
+
+fn check_command(source: &str, types: bool) -> (Option<i32>, Vec<JsonValue>) {
+    let file = std::env::temp_dir().join(format!("monkey-typecheck-test-{}-{}.mk", std::process::id(), source.len()));
+    fs::write(&file, source).unwrap();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_monkey"));
+    command.arg("check");
+    if types {
+        command.arg("--types");
+    }
+    let output = command.arg(&file).output().unwrap();
+    fs::remove_file(&file).unwrap();
+    let diagnostics = json::parse(&String::from_utf8(output.stdout).unwrap()).unwrap();
+    (output.status.code(), diagnostics.as_array().unwrap().clone())
+}
+
+#[test]
+fn check_with_types_exits_with_2_on_type_errors() {
+    // (source, exit code without --types, exit code with --types)
+    let cases = [
+        ("let x: int = 1;\nputs(x);\n", 0, 0),
+        ("puts(true + 1);\n", 0, 2),
+        ("let unused = 1;\n", 1, 1),
+        ("let unused: string = 1;\n", 1, 2),
+    ];
+    for (source, without, with) in cases {
+        assert_eq!(check_command(source, false).0, Some(without), "source: {:?}", source);
+        assert_eq!(check_command(source, true).0, Some(with), "source: {:?}", source);
+    }
+    let (_, diagnostics) = check_command("let a = 1;\nputs(a + \"b\");\n", true);
+    assert_eq!(diagnostics.len(), 1);
+    let error = &diagnostics[0];
+    assert_eq!(error.get("severity").and_then(|x| x.as_str()), Some("error"));
+    assert_eq!(error.get("code").and_then(|x| x.as_str()), Some("type-error"));
+    assert_eq!(error.get("message").and_then(|x| x.as_str()), Some("+ can not be used with int and string"));
+    assert_eq!(error.get("line").and_then(|x| x.as_i64()), Some(2));
+    assert_eq!(error.get("column").and_then(|x| x.as_i64()), Some(8));
+}
+
+#[test]
+fn golden_scripts_only_have_the_errors_they_show() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+    for name in ["arithmetic", "closures", "collections", "early_return", "modules"] {
+        let source = fs::read_to_string(dir.join(format!("{}.mk", name))).unwrap();
+        assert_eq!(type_errors(&source), Vec::<String>::new(), "{}.mk", name);
+    }
+    let source = fs::read_to_string(dir.join("runtime_error.mk")).unwrap();
+    assert_eq!(type_errors(&source), vec!["3:8: argument 2 has to be int, found bool"]);
+}